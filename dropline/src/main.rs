@@ -8,6 +8,11 @@ const GRAVITY: prelude::Vec2 = prelude::Vec2 { x: 0.0, y: 0.3 };
 const TICKS_PER_FRAME: usize = 15;
 const STEP: f32 = 1.0 / (TICKS_PER_FRAME as f32);
 const MAX_COLLISION_ITERATIONS: usize = 10;
+const KILL_ZONE: prelude::FileEntity = 16;
+const ELEVATOR: prelude::FileEntity = 17;
+const ELEVATOR_STOPS: (f32, f32) = (100.0, 500.0);
+const CONVEYOR: prelude::FileEntity = 18;
+const CONVEYOR_STOPS: (f32, f32) = (2200.0, 2900.0);
 
 // this is an example of how to define a custom component, how to add it to the world, how to access its SparseSet and how to attach it to an entity using the map file
 //
@@ -110,6 +115,29 @@ fn init_world() -> prelude::World<0> {
     prelude::World::default()
 }
 
+/// reverses the velocity of a kinematic platform when it goes past one of its stops
+fn patrol(world: &mut prelude::World<0>, entity: prelude::Entity, axis: prelude::Axis, stops: (f32, f32)) {
+    let Some(transform) = world.engine().transform.get(entity) else {
+        return;
+    };
+
+    let pos = match axis {
+        prelude::Axis::X => transform.pos().x,
+        prelude::Axis::Y => transform.pos().y,
+    };
+
+    if let Some(translation) = world.engine_mut().translation.get_mut(entity) {
+        let lin_vel = match axis {
+            prelude::Axis::X => &mut translation.lin_vel_mut().x,
+            prelude::Axis::Y => &mut translation.lin_vel_mut().y,
+        };
+
+        if (pos <= stops.0 && *lin_vel < 0.0) || (pos >= stops.1 && *lin_vel > 0.0) {
+            *lin_vel = -*lin_vel;
+        }
+    }
+}
+
 #[macroquad::main(get_window_config())]
 async fn main() {
     println!(
@@ -121,6 +149,8 @@ async fn main() {
     let mut pause = false;
     let mut entity_manager = prelude::EntityManager::new();
     let mut world = init_world();
+    let mut contacts = prelude::ContactManager::new();
+    let mut controller = prelude::CharacterController::new(GRAVITY);
 
     // load game map
     let map_path = "assets/map.yaml";
@@ -128,6 +158,7 @@ async fn main() {
 
     // create player
     let player = 0;
+    let spawn_pos = world.engine().transform.get(player).expect("missing transform").pos();
 
     // create camera
     let mut camera = prelude::Camera::new(
@@ -135,6 +166,7 @@ async fn main() {
         prelude::Rect::new_checked(mq_prelude::screen_width(), mq_prelude::screen_height()).expect("error creating camera"),
     );
 
+    let mut input = prelude::CharacterInput::default();
     let mut frame_idx = 1;
     let hot_reload_frames = 10;
 
@@ -157,24 +189,24 @@ async fn main() {
             // reset linear and angular acceleration
             prelude::set_all_lin_acc(&mut world, GRAVITY);
             prelude::set_all_ang_acc(&mut world, 0.0);
+            prelude::apply_all_springs(&mut world);
+            prelude::apply_all_force_fields(&mut world);
 
-            // handle user inputs
-            if mq_prelude::is_key_down(mq_prelude::KeyCode::W) && world.engine().translation.get(player).unwrap().rest() {
-                prelude::apply_lin_vel_axis(&mut world, player, -12.0, prelude::Axis::Y);
-                prelude::clamp_min(&mut world.engine_mut().translation.get_mut(player).unwrap().lin_vel_mut().y, -12.0);
-            }
+            // handle user inputs, the jump is only given to the first tick of the frame
+            input.jump = mq_prelude::is_key_pressed(mq_prelude::KeyCode::W);
+            input.move_x = 0.0;
             if mq_prelude::is_key_down(mq_prelude::KeyCode::D) {
-                prelude::apply_lin_vel_axis(&mut world, player, 1.0, prelude::Axis::X).unwrap();
-                prelude::clamp_max(&mut world.engine_mut().translation.get_mut(player).unwrap().lin_vel_mut().x, 12.0);
+                input.move_x += 1.0;
             }
             if mq_prelude::is_key_down(mq_prelude::KeyCode::A) {
-                prelude::apply_lin_vel_axis(&mut world, player, -1.0, prelude::Axis::X).unwrap();
-                prelude::clamp_min(&mut world.engine_mut().translation.get_mut(player).unwrap().lin_vel_mut().x, -12.0);
+                input.move_x -= 1.0;
             }
             if mq_prelude::is_key_pressed(mq_prelude::KeyCode::R) {
                 // reset environment
                 entity_manager.reset();
                 world = init_world();
+                contacts = prelude::ContactManager::new();
+                controller = prelude::CharacterController::new(GRAVITY);
 
                 // load game map
                 map_cache = prelude::load(map_path, &mut world, &mut entity_manager, None).unwrap();
@@ -193,11 +225,41 @@ async fn main() {
                 prelude::integrate_all_lin_vel(&mut world, STEP);
                 prelude::integrate_all_ang_vel(&mut world, STEP);
                 prelude::reset_all_rest(&mut world);
-                prelude::resolve_collisions(&mut world, MAX_COLLISION_ITERATIONS, STEP).unwrap();
+
+                // move the platforms back and forth, riders follow them inside resolve_collisions()
+                if let Some(&elevator) = map_cache.entity_map.get(&ELEVATOR) {
+                    patrol(&mut world, elevator, prelude::Axis::Y, ELEVATOR_STOPS);
+                }
+                if let Some(&conveyor) = map_cache.entity_map.get(&CONVEYOR) {
+                    patrol(&mut world, conveyor, prelude::Axis::X, CONVEYOR_STOPS);
+                }
+
+                prelude::resolve_collisions(&mut world, &mut contacts, MAX_COLLISION_ITERATIONS, STEP).unwrap();
+                prelude::apply_all_destruction(&mut world, &mut entity_manager, &contacts).unwrap();
+
+                // respawn the player when it enters the kill zone
+                if let Some(&kill_zone) = map_cache.entity_map.get(&KILL_ZONE)
+                    && contacts
+                        .sensor_events()
+                        .iter()
+                        .any(|event| event.sensor == kill_zone && event.other == player && event.kind == prelude::SensorEventKind::Enter)
+                {
+                    *world.engine_mut().transform.get_mut(player).unwrap().pos_mut() = spawn_pos;
+                    *world.engine_mut().translation.get_mut(player).unwrap().lin_vel_mut() = prelude::Vec2::ZERO;
+                    controller.reset();
+                }
+
+                // move the player, it must be done after resolve_collisions() since the controller sets the velocity used by
+                // integrate_all_pos()
+                controller.update(&mut world, player, &input, STEP).unwrap();
+                input.jump = false;
+
                 prelude::integrate_all_pos(&mut world, STEP);
                 prelude::integrate_all_rot_mat(&mut world, STEP);
                 prelude::set_all_lin_acc(&mut world, GRAVITY);
                 prelude::set_all_ang_acc(&mut world, 0.0);
+                prelude::apply_all_springs(&mut world);
+                prelude::apply_all_force_fields(&mut world);
             }
 
             camera.update(world.engine().transform.get(player).expect("missing transform").pos());
@@ -220,6 +282,7 @@ async fn main() {
         let mut msg = String::new();
         _ = write!(msg, "pause: {}\n\n", pause);
         _ = write!(msg, "player_id: {}\n", player);
+        _ = writeln!(msg, "player_grounded: {}", controller.grounded());
         match world.engine().transform.get(player) {
             Some(component) => _ = write!(msg, "player_transform: {}\n", component),
             None => (),
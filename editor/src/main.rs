@@ -1,7 +1,4 @@
-use lithium_engine::{
-    math::{ApplyTransformationShape, ToHitBox},
-    prelude,
-};
+use lithium_engine::prelude;
 
 use macroquad::{
     input::{is_mouse_button_pressed, is_mouse_button_released},
@@ -14,6 +11,7 @@ const GRAVITY: prelude::Vec2 = prelude::Vec2 { x: 0.0, y: 0.3 };
 const TICKS_PER_FRAME: usize = 15;
 const STEP: f32 = 1.0 / (TICKS_PER_FRAME as f32);
 const MAX_COLLISION_ITERATIONS: usize = 10;
+const PICK_DISTANCE: f32 = 5.0;
 
 fn get_window_config() -> mq_prelude::Conf {
     mq_prelude::Conf {
@@ -30,6 +28,7 @@ async fn main() {
     // initialize environment
     let mut entity_manager = prelude::EntityManager::new();
     let mut world = prelude::World::default();
+    let mut contacts = prelude::ContactManager::new();
     let mut simulate = false;
 
     // load assets
@@ -78,6 +77,8 @@ async fn main() {
         if simulate {
             prelude::set_all_lin_acc(&mut world, GRAVITY);
             prelude::set_all_ang_acc(&mut world, 0.0);
+            prelude::apply_all_springs(&mut world);
+            prelude::apply_all_force_fields(&mut world);
         }
 
         // get mouse pos
@@ -107,6 +108,7 @@ async fn main() {
             // reset environment
             entity_manager.reset();
             world = prelude::World::default();
+            contacts = prelude::ContactManager::new();
 
             // load game map
             hot_reload_caches = load_assets(&mut world, &mut entity_manager);
@@ -119,32 +121,25 @@ async fn main() {
         }
         if is_mouse_button_pressed(mq_prelude::MouseButton::Left) {
             // drag stuff
-            let mats = world.engine.material.get_comps();
-            let ents = world.engine.material.get_ents();
-            let mut pairs: Vec<(&prelude::Material, &u32)> = mats.iter().zip(ents).collect();
-            pairs.sort_by_key(|(m, _)| m.layer());
-            for &(material, &entity) in pairs.iter().rev() {
-                if !material.show() {
-                    continue;
-                }
-
-                if let Some(transform) = world.engine.transform.get(entity)
-                    && let Some(body) = world.engine.body.get(entity)
-                {
-                    let mut shape = body.shape().clone();
-                    if let Some(rot_mat) = world.engine.rotation_matrix.get(entity) {
-                        shape = shape.apply_mat2x3_checked(rot_mat.rot_mat()).unwrap();
-                    }
-                    shape = shape.apply_vec2_checked(transform.pos()).unwrap();
-                    let hitbox = shape.to_hitbox();
-                    if hitbox.min_x() <= pointer_pos.x && pointer_pos.x <= hitbox.max_x() {
-                        if hitbox.min_y() <= pointer_pos.y && pointer_pos.y <= hitbox.max_y() {
-                            dragging_entity = Some(entity);
-                            pointer_rel_pos = transform.pos().sub(pointer_pos);
-                            break;
-                        }
-                    }
-                }
+            let filter = prelude::QueryFilter {
+                sensors: true,
+                ..Default::default()
+            };
+            let shown = |entity: prelude::Entity| world.engine.material.get(entity).is_some_and(|material| material.show());
+            let picked = prelude::point_query(&world, pointer_pos, &filter)
+                .into_iter()
+                .find(|&entity| shown(entity))
+                .or_else(|| {
+                    // thin shapes like segments cannot contain the pointer, so the closest one is picked if it is near enough
+                    prelude::nearest_query(&world, pointer_pos, PICK_DISTANCE, &filter)
+                        .map(|hit| hit.entity)
+                        .filter(|&entity| shown(entity))
+                });
+            if let Some(entity) = picked
+                && let Some(transform) = world.engine.transform.get(entity)
+            {
+                dragging_entity = Some(entity);
+                pointer_rel_pos = transform.pos().sub(pointer_pos);
             }
         }
         if is_mouse_button_released(mq_prelude::MouseButton::Left) {
@@ -156,11 +151,14 @@ async fn main() {
                 prelude::integrate_all_lin_vel(&mut world, STEP);
                 prelude::integrate_all_ang_vel(&mut world, STEP);
                 prelude::reset_all_rest(&mut world);
-                prelude::resolve_collisions(&mut world, MAX_COLLISION_ITERATIONS, STEP).unwrap();
+                prelude::resolve_collisions(&mut world, &mut contacts, MAX_COLLISION_ITERATIONS, STEP).unwrap();
+                prelude::apply_all_destruction(&mut world, &mut entity_manager, &contacts).unwrap();
                 prelude::integrate_all_pos(&mut world, STEP);
                 prelude::integrate_all_rot_mat(&mut world, STEP);
                 prelude::set_all_lin_acc(&mut world, GRAVITY);
                 prelude::set_all_ang_acc(&mut world, 0.0);
+                prelude::apply_all_springs(&mut world);
+                prelude::apply_all_force_fields(&mut world);
             }
         }
 
@@ -168,6 +166,7 @@ async fn main() {
             *world.engine_mut().transform.get_mut(entity).unwrap().pos_mut() = pointer_pos.add(pointer_rel_pos);
             if let Some(translation) = world.engine_mut().translation.get_mut(entity) {
                 *translation.lin_vel_mut() = prelude::Vec2::ZERO;
+                translation.wake();
             }
         }
 
@@ -0,0 +1,144 @@
+// measures how much energy each integrator gains or loses on scenarios without dissipation
+//
+// run with: cargo run -p lithium-engine --example energy_drift
+//
+// the drift is the largest difference from the initial energy reached during the run, divided by a reference energy of
+// the scenario; the run fails if a scheme exceeds its tolerance
+
+use lithium_engine::prelude;
+
+/// a body orbiting a fixed attractor placed at the origin
+struct Orbit {
+    gm: f32, // gravitational parameter of the attractor
+}
+
+impl Orbit {
+    fn force(&self, pos: prelude::Vec2, mass: f32) -> prelude::Vec2 {
+        let dist = pos.mag();
+        pos.scale(-self.gm * mass / (dist * dist * dist))
+    }
+
+    fn energy(&self, pos: prelude::Vec2, lin_vel: prelude::Vec2, mass: f32) -> f32 {
+        0.5 * mass * lin_vel.dot(lin_vel) - self.gm * mass / pos.mag()
+    }
+}
+
+/// a bob hanging from the origin through a stiff spring, under gravity (screen coordinates, y-down)
+struct Pendulum {
+    length: f32,
+    stiffness: f32,
+    gravity: prelude::Vec2,
+}
+
+impl Pendulum {
+    fn force(&self, pos: prelude::Vec2, mass: f32) -> prelude::Vec2 {
+        let dist = pos.mag();
+        pos.scale(-self.stiffness * (dist - self.length) / dist)
+            .add(self.gravity.scale(mass))
+    }
+
+    fn energy(&self, pos: prelude::Vec2, lin_vel: prelude::Vec2, mass: f32) -> f32 {
+        let stretch = pos.mag() - self.length;
+        0.5 * mass * lin_vel.dot(lin_vel) + 0.5 * self.stiffness * stretch * stretch - mass * self.gravity.dot(pos)
+    }
+}
+
+/// simulates a single body and returns the drift of its energy
+fn run<F, E>(
+    integrator: prelude::Integrator,
+    pos: prelude::Vec2,
+    lin_vel: prelude::Vec2,
+    step: f32,
+    steps: usize,
+    force: F,
+    energy: E,
+) -> f32
+where
+    F: Fn(prelude::Vec2, f32) -> prelude::Vec2,
+    E: Fn(prelude::Vec2, prelude::Vec2, f32) -> f32,
+{
+    const MASS: f32 = 1.0;
+
+    let mut entity_manager = prelude::EntityManager::new();
+    let mut world = prelude::World::<0>::default();
+
+    let entity = entity_manager.create();
+    world.engine_mut().transform.upsert(entity, prelude::Transform::new(pos));
+    world
+        .engine_mut()
+        .translation
+        .upsert(entity, prelude::Translation::new(lin_vel, prelude::Vec2::ZERO, MASS).unwrap());
+
+    let initial_energy = energy(pos, lin_vel, MASS);
+    let mut max_drift: f32 = 0.0;
+
+    for _ in 0..steps {
+        prelude::integrate_all(&mut world, integrator, step, |_, pos, _| force(pos, MASS));
+
+        let pos = world.engine().transform.get(entity).unwrap().pos();
+        let lin_vel = world.engine().translation.get(entity).unwrap().lin_vel();
+        max_drift = max_drift.max((energy(pos, lin_vel, MASS) - initial_energy).abs());
+    }
+
+    max_drift
+}
+
+fn main() {
+    let integrators = [
+        (prelude::Integrator::SymplecticEuler, "symplectic euler", 5e-2),
+        (prelude::Integrator::VelocityVerlet, "velocity verlet", 5e-3),
+        (prelude::Integrator::Rk4, "rk4", 1e-4),
+    ];
+
+    let mut failed = false;
+
+    // eccentric orbit, about 10 revolutions
+    let orbit = Orbit { gm: 1000.0 };
+    let orbit_pos = prelude::Vec2::new(100.0, 0.0);
+    let orbit_lin_vel = prelude::Vec2::new(0.0, 0.8 * (orbit.gm / orbit_pos.mag()).sqrt());
+    let orbit_reference = orbit.energy(orbit_pos, orbit_lin_vel, 1.0).abs();
+
+    // pendulum released horizontally, the spring is at rest length
+    let pendulum = Pendulum {
+        length: 100.0,
+        stiffness: 5.0,
+        gravity: prelude::Vec2::new(0.0, 0.3),
+    };
+    let pendulum_pos = prelude::Vec2::new(pendulum.length, 0.0);
+    let pendulum_reference = pendulum.gravity.mag() * pendulum.length;
+
+    println!("{:<20} {:>12} {:>12}", "integrator", "orbit", "pendulum");
+
+    for (integrator, name, tolerance) in integrators {
+        let orbit_drift = run(
+            integrator,
+            orbit_pos,
+            orbit_lin_vel,
+            0.5,
+            3000,
+            |pos, mass| orbit.force(pos, mass),
+            |pos, lin_vel, mass| orbit.energy(pos, lin_vel, mass),
+        ) / orbit_reference;
+
+        let pendulum_drift = run(
+            integrator,
+            pendulum_pos,
+            prelude::Vec2::ZERO,
+            0.05,
+            20000,
+            |pos, mass| pendulum.force(pos, mass),
+            |pos, lin_vel, mass| pendulum.energy(pos, lin_vel, mass),
+        ) / pendulum_reference;
+
+        println!("{name:<20} {orbit_drift:>12.3e} {pendulum_drift:>12.3e}");
+
+        if orbit_drift > tolerance || pendulum_drift > tolerance {
+            println!("  -> {name} exceeds its tolerance of {tolerance:e}");
+            failed = true;
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
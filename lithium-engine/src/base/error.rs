@@ -116,6 +116,9 @@ impl fmt::Display for ComponentError {
 #[derive(Debug)]
 pub enum MathError {
     NonPositive(&'static str),
+    Negative(&'static str),
+    InvalidRange(&'static str),
+    Zero(&'static str),
 }
 
 impl error::Error for MathError {}
@@ -124,6 +127,9 @@ impl fmt::Display for MathError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             MathError::NonPositive(param) => write!(f, "{param} must be positive"),
+            MathError::Negative(param) => write!(f, "{param} must not be negative"),
+            MathError::InvalidRange(param) => write!(f, "{param} lower bound must not exceed its upper bound"),
+            MathError::Zero(param) => write!(f, "{param} must not be zero"),
         }
     }
 }
@@ -135,9 +141,13 @@ pub enum GeometryError {
     CollinearVertices,
     SelfIntersecting,
     NotCounterClockWise,
+    HoleNotClockWise,
+    HoleOutside,
     NotConvex,
     NormalizationError,
     InvalidShape,
+    InvalidScale,
+    NonPositiveRadius,
 }
 
 impl error::Error for GeometryError {}
@@ -150,9 +160,13 @@ impl fmt::Display for GeometryError {
             GeometryError::CollinearVertices => write!(f, "shape has collinear vertices"),
             GeometryError::SelfIntersecting => write!(f, "shape has self-intersecting vertices"),
             GeometryError::NotCounterClockWise => write!(f, "shape's vertices are store clockwise, but counterclockwise is required"),
+            GeometryError::HoleNotClockWise => write!(f, "shape's hole vertices are stored counterclockwise, but clockwise is required"),
+            GeometryError::HoleOutside => write!(f, "shape has a hole that is not inside its boundary or inside another hole"),
             GeometryError::NotConvex => write!(f, "shape must be convex"),
             GeometryError::NormalizationError => write!(f, "number of vertices changed during normalization"),
             GeometryError::InvalidShape => write!(f, "shape is not valid"),
+            GeometryError::InvalidScale => write!(f, "shape cannot be scaled by this factor"),
+            GeometryError::NonPositiveRadius => write!(f, "circle radius must be positive"),
         }
     }
 }
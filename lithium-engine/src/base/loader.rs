@@ -61,7 +61,7 @@ pub fn load<const N: usize>(
 
                 let kind = component.kind.as_str();
 
-                match_engine_upsert(world, entity, kind, component.data.clone())?;
+                match_engine_upsert(world, entity, kind, component.data.clone(), &mut entity_map, entity_manager)?;
                 match_user_upsert(world, entity, kind, component.data.clone())?;
                 storage.insert((component.entity, component.kind), component.data);
             }
@@ -70,7 +70,14 @@ pub fn load<const N: usize>(
             for component in parsed_file {
                 let entity = *entity_map.entry(component.entity).or_insert_with(|| entity_manager.create());
 
-                match_engine_upsert(world, entity, component.kind.as_str(), component.data.clone())?;
+                match_engine_upsert(
+                    world,
+                    entity,
+                    component.kind.as_str(),
+                    component.data.clone(),
+                    &mut entity_map,
+                    entity_manager,
+                )?;
                 storage.insert((component.entity, component.kind), component.data);
             }
         }
@@ -127,7 +134,7 @@ pub fn hot_reload<const N: usize>(
 
                         let kind = new_kind.as_str();
 
-                        match_engine_upsert(world, entity, kind, new_value.clone())?; // update world
+                        match_engine_upsert(world, entity, kind, new_value.clone(), &mut cache.entity_map, entity_manager)?; // update world
                         match_user_upsert(world, entity, kind, new_value.clone())?;
                         *value = new_value.clone(); // update cache
                     }
@@ -138,7 +145,7 @@ pub fn hot_reload<const N: usize>(
 
                         let kind = new_kind.as_str();
 
-                        match_engine_upsert(world, entity, kind, new_value.clone())?; // update world
+                        match_engine_upsert(world, entity, kind, new_value.clone(), &mut cache.entity_map, entity_manager)?; // update world
                         match_user_upsert(world, entity, kind, new_value.clone())?;
                         cache.storage.insert((*new_entity, new_kind.clone()), new_value.clone()); // update cache
                     }
@@ -154,7 +161,7 @@ pub fn hot_reload<const N: usize>(
 
                         let kind = new_kind.as_str();
 
-                        match_engine_upsert(world, entity, kind, new_value.clone())?; // update world
+                        match_engine_upsert(world, entity, kind, new_value.clone(), &mut cache.entity_map, entity_manager)?; // update world
                         *value = new_value.clone(); // update cache
                     }
                     Some(_) => (), // value didn't change
@@ -164,7 +171,7 @@ pub fn hot_reload<const N: usize>(
 
                         let kind = new_kind.as_str();
 
-                        match_engine_upsert(world, entity, kind, new_value.clone())?; // update world
+                        match_engine_upsert(world, entity, kind, new_value.clone(), &mut cache.entity_map, entity_manager)?; // update world
                         cache.storage.insert((*new_entity, new_kind.clone()), new_value.clone()); // update cache
                     }
                 }
@@ -212,11 +219,19 @@ fn match_engine_upsert<const N: usize>(
     entity: ecs::Entity,
     kind: &str,
     data: Value,
+    entity_map: &mut HashMap<FileEntity, ecs::Entity>,
+    entity_manager: &mut ecs::EntityManager,
 ) -> Result<(), base::EngineError> {
     match kind {
         "transform" => {
             let transform_spec = ecs::TransformSpec::deserialize(data).map_err(base::FileError::from)?;
             world.engine.transform.upsert(entity, transform_spec.into());
+
+            if let Some(translation) = world.engine.translation.get_mut(entity) {
+                // a sleeping entity moved by hand must be simulated again
+                translation.wake();
+            }
+
             Ok(())
         }
         "rotation_matrix" => {
@@ -236,16 +251,18 @@ fn match_engine_upsert<const N: usize>(
         }
         "surface" => {
             let surface_spec = ecs::SurfaceSpec::deserialize(data).map_err(base::FileError::from)?;
-            world.engine.surface.upsert(entity, surface_spec.into());
+            world.engine.surface.upsert(entity, surface_spec.try_into()?);
             Ok(())
         }
         "body" => {
-            let body_spec = ecs::BodySpec::deserialize(data).map_err(base::FileError::from)?;
-            let mut body: ecs::Body = body_spec.into();
+            let mut body_spec = ecs::BodySpec::deserialize(data).map_err(base::FileError::from)?;
 
             // normalize vertices: this is much more flexible at the cost of losing information about what causes the error
-            body.shape.normalize()?;
-            body.shape.validate(true)?;
+            body_spec.shape.normalize()?;
+            body_spec.shape.validate(true)?;
+
+            // the scale is applied to the normalized shape and validated again
+            let body: ecs::Body = body_spec.try_into()?;
 
             world.engine.body.upsert(entity, body);
             Ok(())
@@ -255,6 +272,49 @@ fn match_engine_upsert<const N: usize>(
             world.engine.material.upsert(entity, material_spec.into());
             Ok(())
         }
+        "collision_filter" => {
+            let collision_filter_spec = ecs::CollisionFilterSpec::deserialize(data).map_err(base::FileError::from)?;
+            world.engine.collision_filter.upsert(entity, collision_filter_spec.into());
+            Ok(())
+        }
+        "joint" => {
+            let joint_spec = ecs::JointSpec::deserialize(data).map_err(base::FileError::from)?;
+
+            // joints reference other entities of the file, which may not have been loaded yet
+            let entity_1 = *entity_map.entry(joint_spec.entity_1).or_insert_with(|| entity_manager.create());
+            let entity_2 = *entity_map.entry(joint_spec.entity_2).or_insert_with(|| entity_manager.create());
+
+            world.engine.joint.upsert(entity, joint_spec.to_joint(entity_1, entity_2)?);
+            Ok(())
+        }
+        "spring" => {
+            let spring_spec = ecs::SpringSpec::deserialize(data).map_err(base::FileError::from)?;
+
+            let spring_entity = *entity_map.entry(spring_spec.entity).or_insert_with(|| entity_manager.create());
+            let anchor = match spring_spec.anchor {
+                ecs::SpringAnchorSpec::Entity {
+                    entity: anchor_entity,
+                    attach,
+                } => ecs::SpringAnchor::Entity {
+                    entity: *entity_map.entry(anchor_entity).or_insert_with(|| entity_manager.create()),
+                    attach,
+                },
+                ecs::SpringAnchorSpec::World { point } => ecs::SpringAnchor::World { point },
+            };
+
+            world.engine.spring.upsert(entity, spring_spec.to_spring(spring_entity, anchor)?);
+            Ok(())
+        }
+        "force_field" => {
+            let force_field_spec = ecs::ForceFieldSpec::deserialize(data).map_err(base::FileError::from)?;
+            world.engine.force_field.upsert(entity, force_field_spec.try_into()?);
+            Ok(())
+        }
+        "breakable" => {
+            let breakable_spec = ecs::BreakableSpec::deserialize(data).map_err(base::FileError::from)?;
+            world.engine.breakable.upsert(entity, breakable_spec.try_into()?);
+            Ok(())
+        }
         _ => Ok(()),
     }
 }
@@ -282,6 +342,21 @@ fn match_engine_remove<const N: usize>(world: &mut ecs::World<N>, entity: ecs::E
         "material" => {
             world.engine.material.remove(entity);
         }
+        "collision_filter" => {
+            world.engine.collision_filter.remove(entity);
+        }
+        "joint" => {
+            world.engine.joint.remove(entity);
+        }
+        "spring" => {
+            world.engine.spring.remove(entity);
+        }
+        "force_field" => {
+            world.engine.force_field.remove(entity);
+        }
+        "breakable" => {
+            world.engine.breakable.remove(entity);
+        }
         _ => (),
     }
 }
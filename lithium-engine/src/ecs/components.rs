@@ -1,5 +1,5 @@
 use crate::math::Centroid;
-use crate::{base, math};
+use crate::{base, ecs, math};
 
 use std::{any::Any, fmt};
 
@@ -36,6 +36,12 @@ impl Transform {
     pub fn pos_mut(&mut self) -> &mut math::Vec2 {
         &mut self.pos
     }
+
+    /// combines the position with the rotation matrix of the same entity, if any
+    #[inline]
+    pub fn to_transform_2d(&self, rot_mat: Option<&RotationMatrix>) -> math::Transform2D {
+        math::Transform2D::new(rot_mat.map_or(math::Mat2x3::IDENTITY, |rot_mat| rot_mat.rot_mat.clone()), self.pos)
+    }
 }
 
 impl fmt::Display for Transform {
@@ -150,6 +156,16 @@ pub struct TranslationSpec {
     pub lin_vel: math::Vec2,
     pub force: math::Vec2,
     pub mass: f32,
+    #[serde(default)]
+    pub kinematic: bool,
+    #[serde(default = "default_gravity_scale")]
+    pub gravity_scale: f32,
+    #[serde(default)]
+    pub lin_damping: f32,
+}
+
+const fn default_gravity_scale() -> f32 {
+    1.0
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
@@ -159,6 +175,11 @@ pub struct Translation {
     mass: f32,
     inv_mass: f32,
     pub(crate) rest: bool,
+    pub(crate) sleeping: bool,
+    pub(crate) sleep_time: f32,    // time spent below the sleep thresholds
+    pub(crate) kinematic: bool,    // moved only by its velocity, treated as infinite mass by the solvers
+    pub(crate) gravity_scale: f32, // multiplies the global acceleration and the gravity of force fields
+    pub(crate) lin_damping: f32,   // fraction of the linear velocity lost per unit of time
 }
 
 impl Translation {
@@ -174,6 +195,31 @@ impl Translation {
             mass,
             inv_mass: 1.0 / mass,
             rest: false,
+            sleeping: false,
+            sleep_time: 0.0,
+            kinematic: false,
+            gravity_scale: 1.0,
+            lin_damping: 0.0,
+        })
+    }
+
+    #[inline]
+    pub const fn new_kinematic(lin_vel: math::Vec2, mass: f32) -> Result<Self, base::MathError> {
+        if mass <= 0.0 {
+            return Err(base::MathError::NonPositive("mass"));
+        }
+
+        Ok(Self {
+            lin_vel,
+            force: math::Vec2::ZERO,
+            mass,
+            inv_mass: 0.0,
+            rest: false,
+            sleeping: false,
+            sleep_time: 0.0,
+            kinematic: true,
+            gravity_scale: 1.0,
+            lin_damping: 0.0,
         })
     }
 
@@ -202,6 +248,26 @@ impl Translation {
         self.rest
     }
 
+    #[inline]
+    pub fn sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    #[inline]
+    pub fn kinematic(&self) -> bool {
+        self.kinematic
+    }
+
+    #[inline]
+    pub fn gravity_scale(&self) -> f32 {
+        self.gravity_scale
+    }
+
+    #[inline]
+    pub fn lin_damping(&self) -> f32 {
+        self.lin_damping
+    }
+
     #[inline]
     pub fn lin_vel_mut(&mut self) -> &mut math::Vec2 {
         &mut self.lin_vel
@@ -215,7 +281,29 @@ impl Translation {
     #[inline]
     pub fn set_mass(&mut self, new_mass: f32) {
         self.mass = new_mass;
-        self.inv_mass = 1.0 / new_mass;
+        self.inv_mass = if self.kinematic { 0.0 } else { 1.0 / new_mass };
+    }
+
+    #[inline]
+    pub fn set_kinematic(&mut self, new_kinematic: bool) {
+        self.kinematic = new_kinematic;
+        self.inv_mass = if new_kinematic { 0.0 } else { 1.0 / self.mass };
+        self.wake();
+    }
+
+    #[inline]
+    pub fn gravity_scale_mut(&mut self) -> &mut f32 {
+        &mut self.gravity_scale
+    }
+
+    #[inline]
+    pub fn set_lin_damping(&mut self, new_lin_damping: f32) -> Result<(), base::MathError> {
+        if new_lin_damping < 0.0 {
+            return Err(base::MathError::Negative("lin_damping"));
+        }
+
+        self.lin_damping = new_lin_damping;
+        Ok(())
     }
 
     #[inline]
@@ -223,6 +311,18 @@ impl Translation {
         &mut self.rest
     }
 
+    #[inline]
+    pub fn wake(&mut self) {
+        self.sleeping = false;
+        self.sleep_time = 0.0;
+    }
+
+    #[inline]
+    pub fn sleep(&mut self) {
+        self.sleeping = true;
+        self.lin_vel = math::Vec2::ZERO;
+    }
+
     #[inline]
     pub fn apply_lin_vel_axis(&mut self, lin_vel: f32, axis: math::Axis) {
         match axis {
@@ -262,8 +362,8 @@ impl fmt::Display for Translation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "translation (lin_vel: {}, force: {}, mass: {:.4}, rest: {})",
-            self.lin_vel, self.force, self.mass, self.rest
+            "translation (lin_vel: {}, force: {}, mass: {:.4}, rest: {}, sleeping: {}, kinematic: {}, gravity_scale: {:.4}, lin_damping: {:.4})",
+            self.lin_vel, self.force, self.mass, self.rest, self.sleeping, self.kinematic, self.gravity_scale, self.lin_damping
         )
     }
 }
@@ -272,7 +372,16 @@ impl TryFrom<TranslationSpec> for Translation {
     type Error = base::MathError;
 
     fn try_from(spec: TranslationSpec) -> Result<Self, Self::Error> {
-        Self::new(spec.lin_vel, spec.force, spec.mass)
+        let mut translation = if spec.kinematic {
+            Self::new_kinematic(spec.lin_vel, spec.mass)?
+        } else {
+            Self::new(spec.lin_vel, spec.force, spec.mass)?
+        };
+
+        translation.gravity_scale = spec.gravity_scale;
+        translation.set_lin_damping(spec.lin_damping)?;
+
+        Ok(translation)
     }
 }
 
@@ -281,6 +390,8 @@ pub struct RotationSpec {
     pub ang_vel: f32,
     pub torque: f32,
     pub inertia: f32,
+    #[serde(default)]
+    pub ang_damping: f32,
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
@@ -289,6 +400,7 @@ pub struct Rotation {
     pub(crate) torque: f32,
     inertia: f32,
     inv_inertia: f32,
+    pub(crate) ang_damping: f32, // fraction of the angular velocity lost per unit of time
 }
 
 impl Rotation {
@@ -303,6 +415,7 @@ impl Rotation {
             torque,
             inertia,
             inv_inertia: 1.0 / inertia,
+            ang_damping: 0.0,
         })
     }
 
@@ -326,6 +439,11 @@ impl Rotation {
         self.inv_inertia
     }
 
+    #[inline]
+    pub fn ang_damping(&self) -> f32 {
+        self.ang_damping
+    }
+
     #[inline]
     pub fn ang_vel_mut(&mut self) -> &mut f32 {
         &mut self.ang_vel
@@ -342,6 +460,16 @@ impl Rotation {
         self.inv_inertia = 1.0 / new_inertia;
     }
 
+    #[inline]
+    pub fn set_ang_damping(&mut self, new_ang_damping: f32) -> Result<(), base::MathError> {
+        if new_ang_damping < 0.0 {
+            return Err(base::MathError::Negative("ang_damping"));
+        }
+
+        self.ang_damping = new_ang_damping;
+        Ok(())
+    }
+
     #[inline]
     pub fn apply_ang_vel(&mut self, ang_vel: f32) {
         self.ang_vel += ang_vel;
@@ -357,8 +485,8 @@ impl fmt::Display for Rotation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "rotation (ang_vel: {:.4}, torque: {:.4}, inertia: {:.4})",
-            self.ang_vel, self.torque, self.inertia
+            "rotation (ang_vel: {:.4}, torque: {:.4}, inertia: {:.4}, ang_damping: {:.4})",
+            self.ang_vel, self.torque, self.inertia, self.ang_damping
         )
     }
 }
@@ -367,7 +495,58 @@ impl TryFrom<RotationSpec> for Rotation {
     type Error = base::MathError;
 
     fn try_from(spec: RotationSpec) -> Result<Self, Self::Error> {
-        Self::new(spec.ang_vel, spec.torque, spec.inertia)
+        let mut rotation = Self::new(spec.ang_vel, spec.torque, spec.inertia)?;
+        rotation.set_ang_damping(spec.ang_damping)?;
+
+        Ok(rotation)
+    }
+}
+
+/// how the values of a property of 2 surfaces are merged when they touch, if the surfaces use different rules the one
+/// coming later in the list is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, Decode, Deserialize)]
+pub enum CombineRule {
+    Average,
+    GeometricMean,
+    Min,
+    Multiply,
+    Max,
+}
+
+impl CombineRule {
+    #[inline]
+    pub fn combine(self, other: Self, value_1: f32, value_2: f32) -> f32 {
+        match self.max(other) {
+            Self::Average => 0.5 * (value_1 + value_2),
+            Self::GeometricMean => (value_1 * value_2).sqrt(),
+            Self::Min => value_1.min(value_2),
+            Self::Multiply => value_1 * value_2,
+            Self::Max => value_1.max(value_2),
+        }
+    }
+}
+
+/// combine rule of each property of a surface, static and kinetic friction share the same rule
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode, Deserialize)]
+#[serde(default)]
+pub struct CombineRules {
+    pub elast: CombineRule,
+    pub friction: CombineRule,
+    pub rolling_resistance: CombineRule,
+}
+
+impl CombineRules {
+    pub const DEFAULT: Self = Self {
+        elast: CombineRule::Min,
+        friction: CombineRule::GeometricMean,
+        rolling_resistance: CombineRule::GeometricMean,
+    };
+}
+
+impl Default for CombineRules {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
     }
 }
 
@@ -376,6 +555,16 @@ pub struct SurfaceSpec {
     pub elast: f32,
     pub static_friction: f32,
     pub kinetic_friction: f32,
+    #[serde(default)]
+    pub sensor: bool,
+    #[serde(default)]
+    pub one_way: Option<math::Vec2>,
+    #[serde(default)]
+    pub rolling_resistance: f32,
+    #[serde(default)]
+    pub surface_vel: Option<math::Vec2>,
+    #[serde(default)]
+    pub combine: CombineRules,
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
@@ -383,6 +572,11 @@ pub struct Surface {
     pub(crate) elast: f32,
     pub(crate) static_friction: f32,
     pub(crate) kinetic_friction: f32,
+    pub(crate) sensor: bool,                // sensors detect overlaps but never react to them
+    pub(crate) one_way: Option<math::Vec2>, // local unit direction in which other entities can pass through
+    pub(crate) rolling_resistance: f32,     // resists the relative rotation of the entities, proportionally to the normal impulse
+    pub(crate) surface_vel: math::Vec2,     // local velocity of the surface itself, along which touching entities are dragged
+    pub(crate) combine: CombineRules,
 }
 
 impl Surface {
@@ -392,7 +586,39 @@ impl Surface {
             elast,
             static_friction,
             kinetic_friction,
+            sensor: false,
+            one_way: None,
+            rolling_resistance: 0.0,
+            surface_vel: math::Vec2::ZERO,
+            combine: CombineRules::DEFAULT,
+        }
+    }
+
+    #[inline]
+    pub const fn new_sensor() -> Self {
+        Self {
+            elast: 0.0,
+            static_friction: 0.0,
+            kinetic_friction: 0.0,
+            sensor: true,
+            one_way: None,
+            rolling_resistance: 0.0,
+            surface_vel: math::Vec2::ZERO,
+            combine: CombineRules::DEFAULT,
+        }
+    }
+
+    /// one-way surface, other entities moving along pass_dir go through it while the others collide
+    #[inline]
+    pub fn new_one_way(elast: f32, static_friction: f32, kinetic_friction: f32, pass_dir: math::Vec2) -> Result<Self, base::MathError> {
+        if pass_dir.approx_equal_zero() {
+            return Err(base::MathError::Zero("pass_dir"));
         }
+
+        Ok(Self {
+            one_way: Some(pass_dir.norm()),
+            ..Self::new(elast, static_friction, kinetic_friction)
+        })
     }
 
     #[inline]
@@ -410,6 +636,31 @@ impl Surface {
         self.kinetic_friction
     }
 
+    #[inline]
+    pub fn sensor(&self) -> bool {
+        self.sensor
+    }
+
+    #[inline]
+    pub fn one_way(&self) -> Option<math::Vec2> {
+        self.one_way
+    }
+
+    #[inline]
+    pub fn rolling_resistance(&self) -> f32 {
+        self.rolling_resistance
+    }
+
+    #[inline]
+    pub fn surface_vel(&self) -> math::Vec2 {
+        self.surface_vel
+    }
+
+    #[inline]
+    pub fn combine(&self) -> CombineRules {
+        self.combine
+    }
+
     #[inline]
     pub fn elast_mut(&mut self) -> &mut f32 {
         &mut self.elast
@@ -424,40 +675,218 @@ impl Surface {
     pub fn kinetic_friction_mut(&mut self) -> &mut f32 {
         &mut self.kinetic_friction
     }
+
+    #[inline]
+    pub fn sensor_mut(&mut self) -> &mut bool {
+        &mut self.sensor
+    }
+
+    #[inline]
+    pub fn set_one_way(&mut self, new_pass_dir: Option<math::Vec2>) -> Result<(), base::MathError> {
+        self.one_way = match new_pass_dir {
+            Some(pass_dir) if pass_dir.approx_equal_zero() => return Err(base::MathError::Zero("pass_dir")),
+            Some(pass_dir) => Some(pass_dir.norm()),
+            None => None,
+        };
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_rolling_resistance(&mut self, new_rolling_resistance: f32) -> Result<(), base::MathError> {
+        if new_rolling_resistance < 0.0 {
+            return Err(base::MathError::Negative("rolling_resistance"));
+        }
+
+        self.rolling_resistance = new_rolling_resistance;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn surface_vel_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.surface_vel
+    }
+
+    #[inline]
+    pub fn combine_mut(&mut self) -> &mut CombineRules {
+        &mut self.combine
+    }
 }
 
 impl fmt::Display for Surface {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "surface (elast: {:.4}, static_friction: {:.4}, kinetic_friction: {:.4})",
-            self.elast, self.static_friction, self.kinetic_friction
+            "surface (elast: {:.4}, static_friction: {:.4}, kinetic_friction: {:.4}, sensor: {}, one_way: ",
+            self.elast, self.static_friction, self.kinetic_friction, self.sensor
+        )?;
+
+        match self.one_way {
+            Some(pass_dir) => write!(f, "{}", pass_dir)?,
+            None => write!(f, "none")?,
+        }
+
+        write!(
+            f,
+            ", rolling_resistance: {:.4}, surface_vel: {}, combine: {:?})",
+            self.rolling_resistance, self.surface_vel, self.combine
         )
     }
 }
 
-impl From<SurfaceSpec> for Surface {
-    fn from(spec: SurfaceSpec) -> Self {
-        Self::new(spec.elast, spec.static_friction, spec.kinetic_friction)
+impl TryFrom<SurfaceSpec> for Surface {
+    type Error = base::MathError;
+
+    fn try_from(spec: SurfaceSpec) -> Result<Self, Self::Error> {
+        let mut surface = Self {
+            sensor: spec.sensor,
+            surface_vel: spec.surface_vel.unwrap_or(math::Vec2::ZERO),
+            combine: spec.combine,
+            ..Self::new(spec.elast, spec.static_friction, spec.kinetic_friction)
+        };
+        surface.set_one_way(spec.one_way)?;
+        surface.set_rolling_resistance(spec.rolling_resistance)?;
+
+        Ok(surface)
+    }
+}
+
+#[inline]
+const fn default_category() -> u32 {
+    CollisionFilter::DEFAULT.category
+}
+
+#[inline]
+const fn default_mask() -> u32 {
+    CollisionFilter::DEFAULT.mask
+}
+
+#[derive(Deserialize)]
+pub struct CollisionFilterSpec {
+    #[serde(default = "default_category")]
+    pub category: u32,
+    #[serde(default = "default_mask")]
+    pub mask: u32,
+    #[serde(default)]
+    pub group: i32,
+}
+
+/// decides which entities can collide:
+/// - entities sharing a non-zero group always collide if the group is positive and never collide if it is negative
+/// - otherwise each entity's category must be in the mask of the other one
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct CollisionFilter {
+    pub(crate) category: u32,
+    pub(crate) mask: u32,
+    pub(crate) group: i32,
+}
+
+impl CollisionFilter {
+    /// filter used for entities without the component, collides with everything
+    pub const DEFAULT: Self = Self {
+        category: 1,
+        mask: u32::MAX,
+        group: 0,
+    };
+
+    #[inline]
+    pub const fn new(category: u32, mask: u32, group: i32) -> Self {
+        Self { category, mask, group }
+    }
+
+    #[inline]
+    pub fn category(&self) -> u32 {
+        self.category
+    }
+
+    #[inline]
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    #[inline]
+    pub fn group(&self) -> i32 {
+        self.group
+    }
+
+    #[inline]
+    pub fn category_mut(&mut self) -> &mut u32 {
+        &mut self.category
+    }
+
+    #[inline]
+    pub fn mask_mut(&mut self) -> &mut u32 {
+        &mut self.mask
+    }
+
+    #[inline]
+    pub fn group_mut(&mut self) -> &mut i32 {
+        &mut self.group
+    }
+
+    #[inline]
+    pub fn should_collide(&self, other: &Self) -> bool {
+        if self.group != 0 && self.group == other.group {
+            return self.group > 0;
+        }
+
+        (self.category & other.mask) != 0 && (other.category & self.mask) != 0
+    }
+}
+
+impl fmt::Display for CollisionFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "collision_filter (category: {:#034b}, mask: {:#034b}, group: {})",
+            self.category, self.mask, self.group
+        )
+    }
+}
+
+impl From<CollisionFilterSpec> for CollisionFilter {
+    fn from(spec: CollisionFilterSpec) -> Self {
+        Self::new(spec.category, spec.mask, spec.group)
     }
 }
 
 #[derive(Deserialize)]
 pub struct BodySpec {
     pub shape: math::Shape,
+    #[serde(default = "default_scale")]
+    pub scale: math::Vec2,
+}
+
+const fn default_scale() -> math::Vec2 {
+    math::Vec2::ONE
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
 pub struct Body {
-    pub(crate) shape: math::Shape,
-    pub(crate) centroid: math::Vec2,
+    pub(crate) shape: math::Shape,   // base shape with the scale applied, the one used by every system
+    pub(crate) centroid: math::Vec2, // centroid of the scaled shape
+    pub(crate) base_shape: Option<Box<math::Shape>>, // shape as authored, only stored when it differs from shape (scale is not one)
+    pub(crate) scale: math::Vec2,    // non-uniform scale along the local axes, negative factors mirror the shape
 }
 
 impl Body {
     #[inline]
     pub fn new(shape: math::Shape) -> Self {
         let centroid = shape.centroid();
-        Self { shape, centroid }
+        Self {
+            shape,
+            centroid,
+            base_shape: None,
+            scale: math::Vec2::ONE,
+        }
+    }
+
+    #[inline]
+    pub fn new_scaled(base_shape: math::Shape, scale: math::Vec2) -> Result<Self, base::GeometryError> {
+        let mut body = Self::new(base_shape);
+        body.set_scale(scale)?;
+
+        Ok(body)
     }
 
     #[inline]
@@ -470,22 +899,60 @@ impl Body {
         self.centroid
     }
 
+    #[inline]
+    pub fn base_shape(&self) -> &math::Shape {
+        self.base_shape.as_deref().unwrap_or(&self.shape)
+    }
+
+    #[inline]
+    pub fn scale(&self) -> math::Vec2 {
+        self.scale
+    }
+
+    /// replaces the shape and resets the scale, the new shape is used as it is
     #[inline]
     pub fn set_shape(&mut self, new_shape: math::Shape) {
         self.shape = new_shape;
-        self.centroid = self.shape.centroid()
+        self.centroid = self.shape.centroid();
+        self.base_shape = None;
+        self.scale = math::Vec2::ONE;
+    }
+
+    /// rescales the base shape, mass and inertia are left untouched (see physics::set_scale())
+    #[inline]
+    pub fn set_scale(&mut self, new_scale: math::Vec2) -> Result<(), base::GeometryError> {
+        let shape = self.base_shape().scale_checked(new_scale)?;
+
+        // the base shape is kept only while it differs from the scaled one, so unscaled bodies are encoded once
+        if new_scale == math::Vec2::ONE {
+            self.base_shape = None;
+        } else if self.base_shape.is_none() {
+            self.base_shape = Some(Box::new(self.shape.clone()));
+        }
+
+        self.shape = shape;
+        self.centroid = self.shape.centroid();
+        self.scale = new_scale;
+
+        Ok(())
     }
 }
 
 impl fmt::Display for Body {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "body (shape: {}, centroid: {})", self.shape, self.centroid)
+        write!(
+            f,
+            "body (shape: {}, centroid: {}, scale: {})",
+            self.shape, self.centroid, self.scale
+        )
     }
 }
 
-impl From<BodySpec> for Body {
-    fn from(spec: BodySpec) -> Self {
-        Self::new(spec.shape)
+impl TryFrom<BodySpec> for Body {
+    type Error = base::GeometryError;
+
+    fn try_from(spec: BodySpec) -> Result<Self, Self::Error> {
+        Self::new_scaled(spec.shape, spec.scale)
     }
 }
 
@@ -551,3 +1018,645 @@ impl From<MaterialSpec> for Material {
         Self::new(spec.color, spec.layer, spec.show)
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode, Deserialize)]
+pub struct JointLimits {
+    pub lower: f32,
+    pub upper: f32,
+}
+
+impl JointLimits {
+    #[inline]
+    pub const fn new(lower: f32, upper: f32) -> Result<Self, base::MathError> {
+        if lower > upper {
+            return Err(base::MathError::InvalidRange("joint limits"));
+        }
+
+        Ok(Self { lower, upper })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode, Deserialize)]
+pub struct JointMotor {
+    pub speed: f32,
+    pub max_force: f32,
+}
+
+impl JointMotor {
+    #[inline]
+    pub const fn new(speed: f32, max_force: f32) -> Result<Self, base::MathError> {
+        if max_force < 0.0 {
+            return Err(base::MathError::Negative("motor max_force"));
+        }
+
+        Ok(Self { speed, max_force })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+pub enum JointKind {
+    /// keeps the anchors between min_length and max_length, a rope has min_length = 0
+    Distance { min_length: f32, max_length: f32 },
+    /// pins the anchors together, limits are relative angles in radians
+    Revolute {
+        limits: Option<JointLimits>,
+        motor: Option<JointMotor>,
+    },
+    /// lets the anchors slide along an axis fixed in the first body, limits are translations along the axis
+    Prismatic {
+        axis: math::Vec2,
+        limits: Option<JointLimits>,
+        motor: Option<JointMotor>,
+    },
+    /// glues the bodies together
+    Weld,
+}
+
+/// motor of a revolute joint in map files, the speed is in degrees like the limits
+#[derive(Clone, Copy, Deserialize)]
+pub struct RevoluteMotorSpec {
+    pub speed_degrees: f32,
+    pub max_force: f32,
+}
+
+#[derive(Deserialize)]
+pub enum JointKindSpec {
+    Distance {
+        min_length: f32,
+        max_length: f32,
+    },
+    Revolute {
+        #[serde(default)]
+        limits_degrees: Option<JointLimits>,
+        #[serde(default)]
+        motor: Option<RevoluteMotorSpec>,
+    },
+    Prismatic {
+        axis: math::Vec2,
+        #[serde(default)]
+        limits: Option<JointLimits>,
+        #[serde(default)]
+        motor: Option<JointMotor>,
+    },
+    Weld,
+}
+
+#[derive(Deserialize)]
+pub struct JointSpec {
+    pub entity_1: base::FileEntity,
+    pub entity_2: base::FileEntity,
+    pub anchor_1: math::Vec2,
+    pub anchor_2: math::Vec2,
+    #[serde(default)]
+    pub ref_degrees: f32,
+    pub kind: JointKindSpec,
+}
+
+impl JointSpec {
+    /// builds the joint once the file entities have been mapped to world entities
+    pub fn to_joint(&self, entity_1: ecs::Entity, entity_2: ecs::Entity) -> Result<Joint, base::MathError> {
+        let kind = match self.kind {
+            JointKindSpec::Distance { min_length, max_length } => {
+                if min_length < 0.0 {
+                    return Err(base::MathError::Negative("min_length"));
+                }
+                if min_length > max_length {
+                    return Err(base::MathError::InvalidRange("distance joint length"));
+                }
+
+                JointKind::Distance { min_length, max_length }
+            }
+            JointKindSpec::Revolute { limits_degrees, motor } => JointKind::Revolute {
+                limits: match limits_degrees {
+                    // not normalized, limits may cross 0
+                    Some(limits) => Some(JointLimits::new(limits.lower.to_radians(), limits.upper.to_radians())?),
+                    None => None,
+                },
+                motor: match motor {
+                    Some(motor) => Some(JointMotor::new(motor.speed_degrees.to_radians(), motor.max_force)?),
+                    None => None,
+                },
+            },
+            JointKindSpec::Prismatic { axis, limits, motor } => {
+                if axis.approx_equal_zero() {
+                    return Err(base::MathError::NonPositive("prismatic axis length"));
+                }
+
+                JointKind::Prismatic {
+                    axis: axis.norm(),
+                    limits: match limits {
+                        Some(limits) => Some(JointLimits::new(limits.lower, limits.upper)?),
+                        None => None,
+                    },
+                    motor: match motor {
+                        Some(motor) => Some(JointMotor::new(motor.speed, motor.max_force)?),
+                        None => None,
+                    },
+                }
+            }
+            JointKindSpec::Weld => JointKind::Weld,
+        };
+
+        Ok(Joint::new(
+            entity_1,
+            entity_2,
+            self.anchor_1,
+            self.anchor_2,
+            self.ref_degrees.to_radians(),
+            kind,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct Joint {
+    pub(crate) entity_1: ecs::Entity,
+    pub(crate) entity_2: ecs::Entity,
+    pub(crate) anchor_1: math::Vec2, // local space of entity_1
+    pub(crate) anchor_2: math::Vec2, // local space of entity_2
+    pub(crate) ref_angle: f32,       // relative angle of entity_2 with respect to entity_1 when the joint is at rest
+    pub(crate) kind: JointKind,
+    pub(crate) motor_impulse: f32, // accumulated during a step, used to clamp the motor
+}
+
+impl Joint {
+    #[inline]
+    pub const fn new(
+        entity_1: ecs::Entity,
+        entity_2: ecs::Entity,
+        anchor_1: math::Vec2,
+        anchor_2: math::Vec2,
+        ref_angle: f32,
+        kind: JointKind,
+    ) -> Self {
+        Self {
+            entity_1,
+            entity_2,
+            anchor_1,
+            anchor_2,
+            ref_angle,
+            kind,
+            motor_impulse: 0.0,
+        }
+    }
+
+    #[inline]
+    pub fn entity_1(&self) -> ecs::Entity {
+        self.entity_1
+    }
+
+    #[inline]
+    pub fn entity_2(&self) -> ecs::Entity {
+        self.entity_2
+    }
+
+    #[inline]
+    pub fn anchor_1(&self) -> math::Vec2 {
+        self.anchor_1
+    }
+
+    #[inline]
+    pub fn anchor_2(&self) -> math::Vec2 {
+        self.anchor_2
+    }
+
+    #[inline]
+    pub fn ref_angle(&self) -> f32 {
+        self.ref_angle
+    }
+
+    #[inline]
+    pub fn kind(&self) -> &JointKind {
+        &self.kind
+    }
+
+    #[inline]
+    pub fn kind_mut(&mut self) -> &mut JointKind {
+        &mut self.kind
+    }
+
+    #[inline]
+    pub fn anchor_1_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.anchor_1
+    }
+
+    #[inline]
+    pub fn anchor_2_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.anchor_2
+    }
+
+    #[inline]
+    pub fn ref_angle_mut(&mut self) -> &mut f32 {
+        &mut self.ref_angle
+    }
+}
+
+impl fmt::Display for Joint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "joint (entity_1: {}, entity_2: {}, anchor_1: {}, anchor_2: {}, ref_angle: {:.4}, kind: {:?})",
+            self.entity_1, self.entity_2, self.anchor_1, self.anchor_2, self.ref_angle, self.kind
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+pub enum SpringAnchor {
+    /// attach point in the local space of the anchor entity
+    Entity { entity: ecs::Entity, attach: math::Vec2 },
+    /// fixed point in world space
+    World { point: math::Vec2 },
+}
+
+#[derive(Deserialize)]
+pub enum SpringAnchorSpec {
+    Entity { entity: base::FileEntity, attach: math::Vec2 },
+    World { point: math::Vec2 },
+}
+
+#[derive(Deserialize)]
+pub struct SpringSpec {
+    pub entity: base::FileEntity,
+    pub attach: math::Vec2,
+    pub anchor: SpringAnchorSpec,
+    pub rest_length: f32,
+    pub stiffness: f32,
+    #[serde(default)]
+    pub damping: f32,
+}
+
+impl SpringSpec {
+    /// builds the spring once the file entities have been mapped to world entities
+    #[inline]
+    pub fn to_spring(&self, entity: ecs::Entity, anchor: SpringAnchor) -> Result<Spring, base::MathError> {
+        Spring::new(entity, self.attach, anchor, self.rest_length, self.stiffness, self.damping)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct Spring {
+    pub(crate) entity: ecs::Entity,
+    pub(crate) attach: math::Vec2, // local space of entity
+    pub(crate) anchor: SpringAnchor,
+    pub(crate) rest_length: f32,
+    pub(crate) stiffness: f32,
+    pub(crate) damping: f32,
+}
+
+impl Spring {
+    #[inline]
+    pub const fn new(
+        entity: ecs::Entity,
+        attach: math::Vec2,
+        anchor: SpringAnchor,
+        rest_length: f32,
+        stiffness: f32,
+        damping: f32,
+    ) -> Result<Self, base::MathError> {
+        if rest_length < 0.0 {
+            return Err(base::MathError::Negative("rest_length"));
+        }
+
+        if stiffness < 0.0 {
+            return Err(base::MathError::Negative("stiffness"));
+        }
+
+        if damping < 0.0 {
+            return Err(base::MathError::Negative("damping"));
+        }
+
+        Ok(Self {
+            entity,
+            attach,
+            anchor,
+            rest_length,
+            stiffness,
+            damping,
+        })
+    }
+
+    #[inline]
+    pub fn entity(&self) -> ecs::Entity {
+        self.entity
+    }
+
+    #[inline]
+    pub fn attach(&self) -> math::Vec2 {
+        self.attach
+    }
+
+    #[inline]
+    pub fn anchor(&self) -> SpringAnchor {
+        self.anchor
+    }
+
+    #[inline]
+    pub fn rest_length(&self) -> f32 {
+        self.rest_length
+    }
+
+    #[inline]
+    pub fn stiffness(&self) -> f32 {
+        self.stiffness
+    }
+
+    #[inline]
+    pub fn damping(&self) -> f32 {
+        self.damping
+    }
+
+    #[inline]
+    pub fn attach_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.attach
+    }
+
+    #[inline]
+    pub fn anchor_mut(&mut self) -> &mut SpringAnchor {
+        &mut self.anchor
+    }
+
+    #[inline]
+    pub fn set_rest_length(&mut self, new_rest_length: f32) -> Result<(), base::MathError> {
+        if new_rest_length < 0.0 {
+            return Err(base::MathError::Negative("rest_length"));
+        }
+
+        self.rest_length = new_rest_length;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_stiffness(&mut self, new_stiffness: f32) -> Result<(), base::MathError> {
+        if new_stiffness < 0.0 {
+            return Err(base::MathError::Negative("stiffness"));
+        }
+
+        self.stiffness = new_stiffness;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_damping(&mut self, new_damping: f32) -> Result<(), base::MathError> {
+        if new_damping < 0.0 {
+            return Err(base::MathError::Negative("damping"));
+        }
+
+        self.damping = new_damping;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Spring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "spring (entity: {}, attach: {}, anchor: {:?}, rest_length: {:.4}, stiffness: {:.4}, damping: {:.4})",
+            self.entity, self.attach, self.anchor, self.rest_length, self.stiffness, self.damping
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode, Deserialize)]
+pub enum ForceFieldKind {
+    /// constant acceleration, like a local gravity
+    Uniform { lin_acc: math::Vec2 },
+    /// acceleration toward the centroid of the region (away from it if strength is negative) falling with the squared
+    /// distance, the distance is clamped to min_distance
+    Radial { strength: f32, min_distance: f32 },
+    /// pushes entities toward the velocity of the wind
+    Wind { lin_vel: math::Vec2, drag: f32 },
+    /// slows entities down, the quadratic term grows with the square of the speed
+    Drag { linear: f32, quadratic: f32, angular: f32 },
+    /// fluid pushing entities against gravity proportionally to their submerged area, the drag is scaled the same way
+    Buoyancy {
+        density: f32,
+        gravity: math::Vec2,
+        lin_drag: f32,
+        ang_drag: f32,
+    },
+}
+
+#[derive(Deserialize)]
+pub struct ForceFieldSpec {
+    pub kind: ForceFieldKind,
+}
+
+/// applies forces to the entities inside the body of its own entity, the entity should have no surface so that it
+/// doesn't collide
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct ForceField {
+    pub(crate) kind: ForceFieldKind,
+}
+
+impl ForceField {
+    #[inline]
+    pub const fn new(kind: ForceFieldKind) -> Result<Self, base::MathError> {
+        match kind {
+            ForceFieldKind::Uniform { .. } => {}
+            ForceFieldKind::Radial { min_distance, .. } => {
+                if min_distance <= 0.0 {
+                    return Err(base::MathError::NonPositive("min_distance"));
+                }
+            }
+            ForceFieldKind::Wind { drag, .. } => {
+                if drag < 0.0 {
+                    return Err(base::MathError::Negative("drag"));
+                }
+            }
+            ForceFieldKind::Drag {
+                linear,
+                quadratic,
+                angular,
+            } => {
+                if linear < 0.0 || quadratic < 0.0 || angular < 0.0 {
+                    return Err(base::MathError::Negative("drag"));
+                }
+            }
+            ForceFieldKind::Buoyancy {
+                density,
+                lin_drag,
+                ang_drag,
+                ..
+            } => {
+                if density < 0.0 {
+                    return Err(base::MathError::Negative("density"));
+                }
+
+                if lin_drag < 0.0 || ang_drag < 0.0 {
+                    return Err(base::MathError::Negative("drag"));
+                }
+            }
+        }
+
+        Ok(Self { kind })
+    }
+
+    #[inline]
+    pub fn kind(&self) -> ForceFieldKind {
+        self.kind
+    }
+
+    #[inline]
+    pub fn set_kind(&mut self, new_kind: ForceFieldKind) -> Result<(), base::MathError> {
+        *self = Self::new(new_kind)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for ForceField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "force_field (kind: {:?})", self.kind)
+    }
+}
+
+impl TryFrom<ForceFieldSpec> for ForceField {
+    type Error = base::MathError;
+
+    fn try_from(spec: ForceFieldSpec) -> Result<Self, Self::Error> {
+        Self::new(spec.kind)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BreakableSpec {
+    pub threshold: f32,
+    #[serde(default)]
+    pub min_impulse: f32,
+    #[serde(default = "default_cuts")]
+    pub cuts: u32,
+    #[serde(default)]
+    pub min_area: f32,
+}
+
+const fn default_cuts() -> u32 {
+    2
+}
+
+/// splits the body of its entity into fragments once the damage dealt by contact impulses reaches threshold, see
+/// physics::apply_all_destruction()
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct Breakable {
+    pub(crate) threshold: f32,
+    pub(crate) min_impulse: f32, // impulses below this deal no damage, so resting contacts don't wear the entity down
+    pub(crate) cuts: u32,        // number of cut lines through the point of the strongest impact
+    pub(crate) min_area: f32,    // fragments smaller than this are discarded
+    pub(crate) damage: f32,
+}
+
+impl Breakable {
+    #[inline]
+    pub const fn new(threshold: f32, min_impulse: f32, cuts: u32, min_area: f32) -> Result<Self, base::MathError> {
+        if threshold <= 0.0 {
+            return Err(base::MathError::NonPositive("threshold"));
+        }
+
+        if min_impulse < 0.0 {
+            return Err(base::MathError::Negative("min_impulse"));
+        }
+
+        if cuts == 0 {
+            return Err(base::MathError::Zero("cuts"));
+        }
+
+        if min_area < 0.0 {
+            return Err(base::MathError::Negative("min_area"));
+        }
+
+        Ok(Self {
+            threshold,
+            min_impulse,
+            cuts,
+            min_area,
+            damage: 0.0,
+        })
+    }
+
+    #[inline]
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    #[inline]
+    pub fn min_impulse(&self) -> f32 {
+        self.min_impulse
+    }
+
+    #[inline]
+    pub fn cuts(&self) -> u32 {
+        self.cuts
+    }
+
+    #[inline]
+    pub fn min_area(&self) -> f32 {
+        self.min_area
+    }
+
+    #[inline]
+    pub fn damage(&self) -> f32 {
+        self.damage
+    }
+
+    #[inline]
+    pub fn set_threshold(&mut self, new_threshold: f32) -> Result<(), base::MathError> {
+        if new_threshold <= 0.0 {
+            return Err(base::MathError::NonPositive("threshold"));
+        }
+
+        self.threshold = new_threshold;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_min_impulse(&mut self, new_min_impulse: f32) -> Result<(), base::MathError> {
+        if new_min_impulse < 0.0 {
+            return Err(base::MathError::Negative("min_impulse"));
+        }
+
+        self.min_impulse = new_min_impulse;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cuts(&mut self, new_cuts: u32) -> Result<(), base::MathError> {
+        if new_cuts == 0 {
+            return Err(base::MathError::Zero("cuts"));
+        }
+
+        self.cuts = new_cuts;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_min_area(&mut self, new_min_area: f32) -> Result<(), base::MathError> {
+        if new_min_area < 0.0 {
+            return Err(base::MathError::Negative("min_area"));
+        }
+
+        self.min_area = new_min_area;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn damage_mut(&mut self) -> &mut f32 {
+        &mut self.damage
+    }
+}
+
+impl fmt::Display for Breakable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "breakable (threshold: {:.4}, min_impulse: {:.4}, cuts: {}, min_area: {:.4}, damage: {:.4})",
+            self.threshold, self.min_impulse, self.cuts, self.min_area, self.damage
+        )
+    }
+}
+
+impl TryFrom<BreakableSpec> for Breakable {
+    type Error = base::MathError;
+
+    fn try_from(spec: BreakableSpec) -> Result<Self, Self::Error> {
+        Self::new(spec.threshold, spec.min_impulse, spec.cuts, spec.min_area)
+    }
+}
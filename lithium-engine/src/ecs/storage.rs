@@ -6,6 +6,10 @@ use std::any::Any;
 /// - components[dense] stores the component value
 /// - entities[dense] stores the entity that owns components[dense]
 /// - sparse[entity] stores Some(dense) if that entity has a component, otherwise None
+///
+/// with the deterministic feature the dense arrays are kept sorted by entity, so the iteration order only depends on
+/// which entities have the component and not on the order of insertions and removals (e.g. a client rebuilding the
+/// world from snapshots iterates like the server)
 #[derive(Debug, Clone)]
 pub struct SparseSet<T> {
     components: Vec<T>,
@@ -39,6 +43,7 @@ impl<T> SparseSet<T> {
         self.sparse.get(entity as usize).and_then(|slot| *slot)
     }
 
+    #[cfg(not(feature = "deterministic"))]
     #[inline]
     fn push_new(&mut self, entity: ecs::Entity, component: T) {
         let sparse_idx = self.ensure_sparse_idx(entity);
@@ -49,6 +54,26 @@ impl<T> SparseSet<T> {
         self.sparse[sparse_idx] = Some(dense_idx);
     }
 
+    #[cfg(feature = "deterministic")]
+    #[inline]
+    fn push_new(&mut self, entity: ecs::Entity, component: T) {
+        self.ensure_sparse_idx(entity);
+        let dense_idx = self.entities.partition_point(|&other| other < entity);
+
+        self.components.insert(dense_idx, component);
+        self.entities.insert(dense_idx, entity);
+        self.reindex_from(dense_idx);
+    }
+
+    /// updates the sparse indices of the entities from dense_idx onwards, after they have been shifted
+    #[cfg(feature = "deterministic")]
+    #[inline]
+    fn reindex_from(&mut self, dense_idx: usize) {
+        for (idx, &entity) in self.entities.iter().enumerate().skip(dense_idx) {
+            self.sparse[entity as usize] = Some(idx);
+        }
+    }
+
     #[inline]
     pub fn insert(&mut self, entity: ecs::Entity, component: T) -> Result<(), base::ComponentError> {
         let sparse_idx = self.ensure_sparse_idx(entity);
@@ -81,6 +106,7 @@ impl<T> SparseSet<T> {
         Ok(())
     }
 
+    #[cfg(not(feature = "deterministic"))]
     #[inline]
     pub fn remove(&mut self, entity: ecs::Entity) -> Option<T> {
         let sparse_idx = entity as usize;
@@ -100,6 +126,18 @@ impl<T> SparseSet<T> {
         self.components.pop()
     }
 
+    #[cfg(feature = "deterministic")]
+    #[inline]
+    pub fn remove(&mut self, entity: ecs::Entity) -> Option<T> {
+        let dense_idx = self.sparse.get_mut(entity as usize)?.take()?;
+
+        self.entities.remove(dense_idx);
+        let component = self.components.remove(dense_idx);
+        self.reindex_from(dense_idx);
+
+        Some(component)
+    }
+
     #[inline]
     pub fn get(&self, entity: ecs::Entity) -> Option<&T> {
         let dense_idx = self.dense_idx(entity)?;
@@ -54,6 +54,11 @@ pub struct EngineComponents {
     pub surface: ecs::SparseSet<ecs::Surface>,
     pub body: ecs::SparseSet<ecs::Body>,
     pub material: ecs::SparseSet<ecs::Material>,
+    pub joint: ecs::SparseSet<ecs::Joint>,
+    pub spring: ecs::SparseSet<ecs::Spring>,
+    pub collision_filter: ecs::SparseSet<ecs::CollisionFilter>,
+    pub force_field: ecs::SparseSet<ecs::ForceField>,
+    pub breakable: ecs::SparseSet<ecs::Breakable>,
 }
 
 impl EngineComponents {
@@ -67,6 +72,11 @@ impl EngineComponents {
             surface: ecs::SparseSet::new(),
             body: ecs::SparseSet::new(),
             material: ecs::SparseSet::new(),
+            joint: ecs::SparseSet::new(),
+            spring: ecs::SparseSet::new(),
+            collision_filter: ecs::SparseSet::new(),
+            force_field: ecs::SparseSet::new(),
+            breakable: ecs::SparseSet::new(),
         }
     }
 }
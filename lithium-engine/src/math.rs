@@ -1,7 +1,15 @@
 pub mod algebra;
+pub mod boolean;
+pub mod curve;
 pub mod geometry;
+pub mod outline;
+pub mod proximity;
 pub mod render;
 
 pub use algebra::*;
+pub use boolean::*;
+pub use curve::*;
 pub use geometry::*;
+pub use outline::*;
+pub use proximity::*;
 pub use render::*;
@@ -1,4 +1,7 @@
-use std::fmt;
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign},
+};
 
 use bincode::{Decode, Encode};
 use serde::Deserialize;
@@ -11,6 +14,72 @@ pub fn pow2(x: f32) -> f32 {
     x * x
 }
 
+// transcendental and fused functions give slightly different results on different platforms, with the deterministic
+// feature they are computed in software and fused operations are split in a multiplication and an addition; basic
+// operations and sqrt are correctly rounded by IEEE 754 so they are left untouched
+
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn sin(x: f32) -> f32 {
+    x.sin()
+}
+
+#[cfg(feature = "deterministic")]
+#[inline]
+pub fn sin(x: f32) -> f32 {
+    libm::sinf(x)
+}
+
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn cos(x: f32) -> f32 {
+    x.cos()
+}
+
+#[cfg(feature = "deterministic")]
+#[inline]
+pub fn cos(x: f32) -> f32 {
+    libm::cosf(x)
+}
+
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    y.atan2(x)
+}
+
+#[cfg(feature = "deterministic")]
+#[inline]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    libm::atan2f(y, x)
+}
+
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn acos(x: f32) -> f32 {
+    x.acos()
+}
+
+#[cfg(feature = "deterministic")]
+#[inline]
+pub fn acos(x: f32) -> f32 {
+    libm::acosf(x)
+}
+
+/// a * b + c
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn mul_add(a: f32, b: f32, c: f32) -> f32 {
+    a.mul_add(b, c)
+}
+
+/// a * b + c
+#[cfg(feature = "deterministic")]
+#[inline]
+pub fn mul_add(a: f32, b: f32, c: f32) -> f32 {
+    a * b + c
+}
+
 #[inline]
 pub fn clamp(value: &mut f32, min: f32, max: f32) {
     *value = value.clamp(min, max)
@@ -26,15 +95,135 @@ pub fn clamp_min(value: &mut f32, min: f32) {
     *value = value.max(min);
 }
 
-/// checks if 2 segments are intersecting
+// orientation predicates in the style of shewchuk's "adaptive precision floating-point arithmetic and fast robust
+// geometric predicates": the determinant is first computed in f64 and trusted when it is larger than its error bound,
+// otherwise it is summed exactly; products of two f32 always fit in a f64, so the exact sum only needs expansions of
+// f64 components and not the product splitting of the original paper
+
+/// relative error bound of the f64 orientation determinant, (3 + 16e)e with e = 2^-53
+const ORIENT_2D_ERR_BOUND: f64 = (3.0 + 8.0 * f64::EPSILON) * 0.5 * f64::EPSILON;
+
+/// a + b as the rounded sum and its rounding error, the two add up exactly to a + b
+#[inline]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+
+    (sum, (a - a_virtual) + (b - b_virtual))
+}
+
+/// exact sum of the terms as an expansion (shewchuk's grow-expansion), its components do not overlap and grow in
+/// magnitude, so the sign of the sum is the sign of the last non zero component
+#[inline]
+fn exact_sum<const N: usize>(terms: [f64; N]) -> [f64; N] {
+    let mut expansion = [0.0; N];
+
+    for (len, term) in terms.into_iter().enumerate() {
+        let mut sum = term;
+
+        for component in &mut expansion[..len] {
+            let (new_sum, err) = two_sum(sum, *component);
+            *component = err;
+            sum = new_sum;
+        }
+
+        expansion[len] = sum;
+    }
+
+    expansion
+}
+
+/// determinant with the same sign as a.signed_area(b, c), the sign is always exact while the magnitude is approximated
+#[inline]
+pub fn orient_2d(a: Vec2, b: Vec2, c: Vec2) -> f64 {
+    let (ax, ay, bx, by, cx, cy) = (a.x as f64, a.y as f64, b.x as f64, b.y as f64, c.x as f64, c.y as f64);
+
+    let det_left = (bx - ax) * (cy - ay);
+    let det_right = (by - ay) * (cx - ax);
+    let det = det_left - det_right;
+
+    if det.abs() > ORIENT_2D_ERR_BOUND * (det_left.abs() + det_right.abs()) {
+        return det;
+    }
+
+    // every product of two f32 is exact in f64, so only their sum needs care; the components are summed from the
+    // smallest, which cannot flip the sign of the largest one
+    exact_sum([bx * cy, -bx * ay, -ax * cy, -by * cx, by * ax, ay * cx]).iter().sum()
+}
+
+/// orientation of the triangle a, b, c; counterclockwise follows the rest of the engine and means a negative signed area
+#[inline]
+pub fn orientation(a: Vec2, b: Vec2, c: Vec2) -> Orientation {
+    let det = orient_2d(a, b, c);
+
+    if det < 0.0 {
+        Orientation::CounterClockWise
+    } else if det > 0.0 {
+        Orientation::ClockWise
+    } else {
+        Orientation::Collinear
+    }
+}
+
+/// checks if point lies inside the bounding box of the segment from a to b, for points known to be collinear with it
+#[inline]
+fn collinear_on_segment(a: Vec2, b: Vec2, point: Vec2) -> bool {
+    a.x.min(b.x) <= point.x && point.x <= a.x.max(b.x) && a.y.min(b.y) <= point.y && point.y <= a.y.max(b.y)
+}
+
+/// how the segment from a1 to a2 meets the segment from b1 to b2, computed with exact orientations
+#[inline]
+pub fn segments_intersection(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> SegmentIntersection {
+    let o1 = orientation(a1, a2, b1);
+    let o2 = orientation(a1, a2, b2);
+    let o3 = orientation(b1, b2, a1);
+    let o4 = orientation(b1, b2, a2);
+
+    if o1.opposite(o2) && o3.opposite(o4) {
+        return SegmentIntersection::Crossing;
+    }
+
+    if (o1 == Orientation::Collinear && collinear_on_segment(a1, a2, b1))
+        || (o2 == Orientation::Collinear && collinear_on_segment(a1, a2, b2))
+        || (o3 == Orientation::Collinear && collinear_on_segment(b1, b2, a1))
+        || (o4 == Orientation::Collinear && collinear_on_segment(b1, b2, a2))
+    {
+        return SegmentIntersection::Touching;
+    }
+
+    SegmentIntersection::Disjoint
+}
+
+/// checks if 2 segments cross each other, segments that only touch are not intersecting
 #[inline]
 pub fn check_segments_intersection(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> bool {
-    let d1 = a1.signed_area(a2, b1);
-    let d2 = a1.signed_area(a2, b2);
-    let d3 = b1.signed_area(b2, a1);
-    let d4 = b1.signed_area(b2, a2);
+    segments_intersection(a1, a2, b1, b2) == SegmentIntersection::Crossing
+}
 
-    d1 * d2 < -EPS && d3 * d4 < -EPS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    CounterClockWise, // negative signed area
+    ClockWise,        // positive signed area
+    Collinear,        // the three points lie on a line
+}
+
+impl Orientation {
+    /// checks if the orientations are counterclockwise and clockwise, in any order
+    #[inline]
+    pub fn opposite(self, orientation: Self) -> bool {
+        matches!(
+            (self, orientation),
+            (Self::CounterClockWise, Self::ClockWise) | (Self::ClockWise, Self::CounterClockWise)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentIntersection {
+    Disjoint, // no common point
+    Crossing, // the segments cross at a single point inside both of them
+    Touching, // an end point lies on the other segment, or the segments overlap along a line
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Encode, Decode, Deserialize)]
@@ -45,7 +234,7 @@ pub struct Vec2 {
 
 impl Vec2 {
     pub const ZERO: Self = Self { x: 0.0, y: 0.0 };
-    pub const ONE: Self = Self { x: 0.0, y: 0.0 };
+    pub const ONE: Self = Self { x: 1.0, y: 1.0 };
 
     #[inline]
     pub const fn new(x: f32, y: f32) -> Self {
@@ -205,7 +394,7 @@ impl Vec2 {
 
     #[inline]
     pub fn dot(self, vec2: Self) -> f32 {
-        self.x.mul_add(vec2.x, self.y * vec2.y)
+        mul_add(self.x, vec2.x, self.y * vec2.y)
     }
 
     #[inline]
@@ -248,6 +437,41 @@ impl Vec2 {
         self.add(point).scale(0.5)
     }
 
+    /// linear interpolation, t = 0 gives self and t = 1 gives point
+    #[inline]
+    pub fn lerp(self, point: Self, t: f32) -> Self {
+        Self::new(mul_add(point.x - self.x, t, self.x), mul_add(point.y - self.y, t, self.y))
+    }
+
+    #[inline]
+    pub fn lerp_mut(&mut self, point: Self, t: f32) {
+        *self = self.lerp(point, t);
+    }
+
+    /// rotates around the origin, with the same orientation as Mat2x3::from_rot_and_pivot()
+    #[inline]
+    pub fn rotate(self, rot: Radians) -> Self {
+        let (cos, sin) = (cos(rot.0), sin(rot.0));
+        Self::new(self.x * cos - self.y * sin, mul_add(self.x, sin, self.y * cos))
+    }
+
+    #[inline]
+    pub fn rotate_mut(&mut self, rot: Radians) {
+        *self = self.rotate(rot);
+    }
+
+    /// angle from the x axis, in the range (-PI, PI]
+    #[inline]
+    pub fn angle(self) -> Radians {
+        Radians(atan2(self.y, self.x))
+    }
+
+    /// signed angle that rotates self onto vec2, in the range (-PI, PI]
+    #[inline]
+    pub fn angle_between(self, vec2: Self) -> Radians {
+        Radians(atan2(self.cross(vec2), self.dot(vec2)))
+    }
+
     /// barycentric coordinate method
     #[inline]
     pub fn is_inside_3_vec2(self, a: Self, b: Self, c: Self) -> bool {
@@ -272,9 +496,12 @@ impl Vec2 {
         }
     }
 
+    /// checks if the point is inside the counterclockwise triangle a, b, c or on its boundary, with exact orientations
     #[inline]
     pub fn is_inside_3_ccw_vec2(self, a: Self, b: Self, c: Self) -> bool {
-        a.signed_area(b, self) < EPS && b.signed_area(c, self) < EPS && c.signed_area(a, self) < EPS
+        orientation(a, b, self) != Orientation::ClockWise
+            && orientation(b, c, self) != Orientation::ClockWise
+            && orientation(c, a, self) != Orientation::ClockWise
     }
 }
 
@@ -284,6 +511,148 @@ impl fmt::Display for Vec2 {
     }
 }
 
+impl Add for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, vec2: Self) -> Self {
+        Self::new(self.x + vec2.x, self.y + vec2.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    #[inline]
+    fn add_assign(&mut self, vec2: Self) {
+        self.x += vec2.x;
+        self.y += vec2.y;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, vec2: Self) -> Self {
+        Self::new(self.x - vec2.x, self.y - vec2.y)
+    }
+}
+
+impl SubAssign for Vec2 {
+    #[inline]
+    fn sub_assign(&mut self, vec2: Self) {
+        self.x -= vec2.x;
+        self.y -= vec2.y;
+    }
+}
+
+/// component-wise, like Vec2.mul()
+impl Mul for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, vec2: Self) -> Self {
+        Self::new(self.x * vec2.x, self.y * vec2.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, scalar: f32) -> Self {
+        Self::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl Mul<Vec2> for f32 {
+    type Output = Vec2;
+
+    #[inline]
+    fn mul(self, vec2: Vec2) -> Vec2 {
+        Vec2::new(self * vec2.x, self * vec2.y)
+    }
+}
+
+impl MulAssign<f32> for Vec2 {
+    #[inline]
+    fn mul_assign(&mut self, scalar: f32) {
+        self.x *= scalar;
+        self.y *= scalar;
+    }
+}
+
+impl Div<f32> for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, scalar: f32) -> Self {
+        Self::new(self.x / scalar, self.y / scalar)
+    }
+}
+
+impl DivAssign<f32> for Vec2 {
+    #[inline]
+    fn div_assign(&mut self, scalar: f32) {
+        self.x /= scalar;
+        self.y /= scalar;
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Index<usize> for Vec2 {
+    type Output = f32;
+
+    #[inline]
+    fn index(&self, idx: usize) -> &f32 {
+        match idx {
+            0 => &self.x,
+            1 => &self.y,
+            _ => panic!("index {idx} out of bounds for vec2"),
+        }
+    }
+}
+
+impl IndexMut<usize> for Vec2 {
+    #[inline]
+    fn index_mut(&mut self, idx: usize) -> &mut f32 {
+        match idx {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("index {idx} out of bounds for vec2"),
+        }
+    }
+}
+
+impl Index<Axis> for Vec2 {
+    type Output = f32;
+
+    #[inline]
+    fn index(&self, axis: Axis) -> &f32 {
+        match axis {
+            Axis::X => &self.x,
+            Axis::Y => &self.y,
+        }
+    }
+}
+
+impl IndexMut<Axis> for Vec2 {
+    #[inline]
+    fn index_mut(&mut self, axis: Axis) -> &mut f32 {
+        match axis {
+            Axis::X => &mut self.x,
+            Axis::Y => &mut self.y,
+        }
+    }
+}
+
 /// dedup for slices that uses Vec2.approx_equal()
 pub fn dedup_by_approx_equal(slice: &mut [Vec2]) -> &mut [Vec2] {
     let len = slice.len();
@@ -348,7 +717,7 @@ impl Mat2x3 {
 
     #[inline]
     pub fn from_rot_and_pivot(rot: Radians, pivot: Vec2) -> Self {
-        let (cos, sin) = (rot.0.cos(), rot.0.sin());
+        let (cos, sin) = (cos(rot.0), sin(rot.0));
 
         Self::new(
             (cos, sin),
@@ -357,6 +726,35 @@ impl Mat2x3 {
         )
     }
 
+    #[inline]
+    pub const fn from_translation(vec: Vec2) -> Self {
+        Self::new((1.0, 0.0), (0.0, 1.0), (vec.x, vec.y))
+    }
+
+    /// rotation around the origin
+    #[inline]
+    pub fn from_rot(rot: Radians) -> Self {
+        let (cos, sin) = (cos(rot.0), sin(rot.0));
+
+        Self::new((cos, sin), (-sin, cos), (0.0, 0.0))
+    }
+
+    /// scale along the axes, relative to the origin
+    #[inline]
+    pub const fn from_scale(scale: Vec2) -> Self {
+        Self::new((scale.x, 0.0), (0.0, scale.y), (0.0, 0.0))
+    }
+
+    /// scale along the axes, relative to pivot
+    #[inline]
+    pub fn from_scale_and_pivot(scale: Vec2, pivot: Vec2) -> Self {
+        Self::new(
+            (scale.x, 0.0),
+            (0.0, scale.y),
+            ((1.0 - scale.x) * pivot.x, (1.0 - scale.y) * pivot.y),
+        )
+    }
+
     #[inline]
     pub fn equal(&self, mat2: &Self) -> bool {
         self.x.0 == mat2.x.0
@@ -391,16 +789,16 @@ impl Mat2x3 {
     pub fn pre_mul(&self, mat2: &Self) -> Self {
         Self::new(
             (
-                self.x.0.mul_add(mat2.x.0, self.x.1 * mat2.y.0),
-                self.x.0.mul_add(mat2.x.1, self.x.1 * mat2.y.1),
+                mul_add(self.x.0, mat2.x.0, self.x.1 * mat2.y.0),
+                mul_add(self.x.0, mat2.x.1, self.x.1 * mat2.y.1),
             ),
             (
-                self.y.0.mul_add(mat2.x.0, self.y.1 * mat2.y.0),
-                self.y.0.mul_add(mat2.x.1, self.y.1 * mat2.y.1),
+                mul_add(self.y.0, mat2.x.0, self.y.1 * mat2.y.0),
+                mul_add(self.y.0, mat2.x.1, self.y.1 * mat2.y.1),
             ),
             (
-                self.z.0.mul_add(mat2.x.0, self.z.1.mul_add(mat2.y.0, mat2.z.0)),
-                self.z.0.mul_add(mat2.x.1, self.z.1.mul_add(mat2.y.1, mat2.z.1)),
+                mul_add(self.z.0, mat2.x.0, mul_add(self.z.1, mat2.y.0, mat2.z.0)),
+                mul_add(self.z.0, mat2.x.1, mul_add(self.z.1, mat2.y.1, mat2.z.1)),
             ),
         )
     }
@@ -417,6 +815,112 @@ impl Mat2x3 {
             vec.x * self.x.1 + vec.y * self.y.1 + self.z.1,
         )
     }
+
+    /// applies only the linear part of the matrix, useful for directions since they must not be translated
+    #[inline]
+    pub fn pre_mul_vec2_linear(&self, vec: Vec2) -> Vec2 {
+        Vec2::new(vec.x * self.x.0 + vec.y * self.y.0, vec.x * self.x.1 + vec.y * self.y.1)
+    }
+
+    /// rotation encoded in the matrix, in the range (-PI, PI]
+    #[inline]
+    pub fn angle(&self) -> Radians {
+        Radians(atan2(self.x.1, self.x.0))
+    }
+
+    /// determinant of the linear part, its sign tells if the matrix mirrors and its magnitude how it scales areas
+    #[inline]
+    pub fn determinant(&self) -> f32 {
+        self.x.0 * self.y.1 - self.y.0 * self.x.1
+    }
+
+    /// returns None if the matrix is singular
+    #[inline]
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+
+        if det.abs() < EPS {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        let x = (self.y.1 * inv_det, -self.x.1 * inv_det);
+        let y = (-self.y.0 * inv_det, self.x.0 * inv_det);
+        let z = (-mul_add(self.z.0, x.0, self.z.1 * y.0), -mul_add(self.z.0, x.1, self.z.1 * y.1));
+
+        Some(Self::new(x, y, z))
+    }
+
+    /// applies self and then mat2, same as pre_mul()
+    #[inline]
+    pub fn then(&self, mat2: &Self) -> Self {
+        self.pre_mul(mat2)
+    }
+
+    /// applies self and then a translation
+    #[inline]
+    pub fn translated(&self, vec: Vec2) -> Self {
+        let mut mat = self.clone();
+        mat.z.0 += vec.x;
+        mat.z.1 += vec.y;
+        mat
+    }
+
+    /// applies self and then a rotation around pivot
+    #[inline]
+    pub fn rotated(&self, rot: Radians, pivot: Vec2) -> Self {
+        self.pre_mul(&Self::from_rot_and_pivot(rot, pivot))
+    }
+
+    /// applies self and then a scale relative to pivot
+    #[inline]
+    pub fn scaled(&self, scale: Vec2, pivot: Vec2) -> Self {
+        self.pre_mul(&Self::from_scale_and_pivot(scale, pivot))
+    }
+}
+
+/// mat_1 * mat_2 applies mat_2 first and then mat_1, like the column-major product
+impl Mul for &Mat2x3 {
+    type Output = Mat2x3;
+
+    #[inline]
+    fn mul(self, mat2: Self) -> Mat2x3 {
+        mat2.pre_mul(self)
+    }
+}
+
+impl Mul for Mat2x3 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, mat2: Self) -> Self {
+        mat2.pre_mul(&self)
+    }
+}
+
+impl MulAssign<&Mat2x3> for Mat2x3 {
+    #[inline]
+    fn mul_assign(&mut self, mat2: &Self) {
+        *self = mat2.pre_mul(self);
+    }
+}
+
+impl Mul<Vec2> for &Mat2x3 {
+    type Output = Vec2;
+
+    #[inline]
+    fn mul(self, vec: Vec2) -> Vec2 {
+        self.pre_mul_vec2(vec)
+    }
+}
+
+impl Mul<Vec2> for Mat2x3 {
+    type Output = Vec2;
+
+    #[inline]
+    fn mul(self, vec: Vec2) -> Vec2 {
+        self.pre_mul_vec2(vec)
+    }
 }
 
 impl fmt::Display for Mat2x3 {
@@ -429,6 +933,102 @@ impl fmt::Display for Mat2x3 {
     }
 }
 
+/// placement of a body in world space: the rotation matrix is applied first and the position after, the same way
+/// the engine combines the Transform and RotationMatrix components
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct Transform2D {
+    pub rot_mat: Mat2x3,
+    pub pos: Vec2,
+}
+
+impl Transform2D {
+    pub const IDENTITY: Self = Self {
+        rot_mat: Mat2x3::IDENTITY,
+        pos: Vec2::ZERO,
+    };
+
+    #[inline]
+    pub const fn new(rot_mat: Mat2x3, pos: Vec2) -> Self {
+        Self { rot_mat, pos }
+    }
+
+    #[inline]
+    pub const fn from_pos(pos: Vec2) -> Self {
+        Self::new(Mat2x3::IDENTITY, pos)
+    }
+
+    #[inline]
+    pub fn from_rot_and_pivot(rot: Radians, pivot: Vec2, pos: Vec2) -> Self {
+        Self::new(Mat2x3::from_rot_and_pivot(rot, pivot), pos)
+    }
+
+    /// single matrix equivalent to the transform
+    #[inline]
+    pub fn to_mat2x3(&self) -> Mat2x3 {
+        self.rot_mat.translated(self.pos)
+    }
+
+    /// rotation encoded in the transform, in the range (-PI, PI]
+    #[inline]
+    pub fn angle(&self) -> Radians {
+        self.rot_mat.angle()
+    }
+
+    /// moves a point from local space to world space
+    #[inline]
+    pub fn to_world(&self, point: Vec2) -> Vec2 {
+        self.rot_mat.pre_mul_vec2(point).add(self.pos)
+    }
+
+    /// moves a direction from local space to world space, directions are not translated
+    #[inline]
+    pub fn to_world_linear(&self, dir: Vec2) -> Vec2 {
+        self.rot_mat.pre_mul_vec2_linear(dir)
+    }
+
+    /// moves a point from world space to local space, returns None if the rotation matrix is singular
+    #[inline]
+    pub fn to_local(&self, point: Vec2) -> Option<Vec2> {
+        Some(self.rot_mat.inverse()?.pre_mul_vec2(point.sub(self.pos)))
+    }
+
+    /// returns None if the rotation matrix is singular
+    #[inline]
+    pub fn inverse(&self) -> Option<Self> {
+        let inv = self.rot_mat.inverse()?;
+        let pos = inv.pre_mul_vec2_linear(self.pos).rev();
+
+        Some(Self::new(inv, pos))
+    }
+
+    /// applies self and then transform
+    #[inline]
+    pub fn then(&self, transform: &Self) -> Self {
+        Self::new(
+            self.rot_mat.pre_mul(&transform.rot_mat),
+            transform.rot_mat.pre_mul_vec2_linear(self.pos).add(transform.pos),
+        )
+    }
+
+    /// rotates the transform around a pivot given in local space, like RotationMatrix.update_mut()
+    #[inline]
+    pub fn rotate_mut(&mut self, delta_rot: Radians, pivot: Vec2) {
+        let pivot = self.rot_mat.pre_mul_vec2(pivot);
+        self.rot_mat.pre_mul_mut(&Mat2x3::from_rot_and_pivot(delta_rot, pivot));
+    }
+
+    #[inline]
+    pub fn translate_mut(&mut self, vec: Vec2) {
+        self.pos.add_mut(vec);
+    }
+}
+
+impl fmt::Display for Transform2D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transform 2d (rot_mat: {}, pos: {})", self.rot_mat, self.pos)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Axis {
     X,
@@ -459,6 +1059,30 @@ impl Radians {
     pub fn norm_mut(&mut self) {
         self.0 = self.0.rem_euclid(std::f32::consts::TAU);
     }
+
+    /// angle in the range (-PI, PI], useful for signed differences
+    #[inline]
+    pub fn wrap(self) -> Self {
+        let angle = self.norm().0;
+        Self::new(if angle > std::f32::consts::PI {
+            angle - std::f32::consts::TAU
+        } else {
+            angle
+        })
+    }
+
+    /// linear interpolation
+    #[inline]
+    pub fn lerp(self, rot: Self, t: f32) -> Self {
+        Self::new(mul_add(rot.0 - self.0, t, self.0))
+    }
+
+    /// interpolates along the shortest arc between the two angles, the result is normalized
+    #[inline]
+    pub fn slerp_angle(self, rot: Self, t: f32) -> Self {
+        let delta = Self::new(rot.0 - self.0).wrap();
+        Self::new(mul_add(delta.0, t, self.0)).norm()
+    }
 }
 
 impl fmt::Display for Radians {
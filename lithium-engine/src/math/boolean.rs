@@ -0,0 +1,591 @@
+use crate::math::Validate;
+use crate::{base, math};
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops,
+};
+
+const SNAP_TOLERANCE: f32 = 1e-5; // distance under which 2 vertices are merged, relative to the size of the shapes
+
+type EdgePiece = (usize, usize); // indices in the pool of the vertices where the piece of edge starts and ends
+type Splits = Vec<(f32, math::Vec2)>; // points along an edge, as (fraction of the edge, point)
+
+/// boolean operations between polygonal shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,        // area covered by either shape
+    Difference,   // area covered by the first shape but not by the second one
+    Intersection, // area covered by both shapes
+    Xor,          // area covered by exactly one shape
+}
+
+/// position of an edge of a shape relative to the other shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeSide {
+    Inside,
+    Outside,
+    SharedSame,     // the other shape has the same edge, in the same direction
+    SharedOpposite, // the other shape has the same edge, in the opposite direction
+}
+
+/// the boundary of a polygonal shape, the first ring is the outline and the others are the holes
+pub(crate) fn rings(shape: &math::Shape) -> Result<Vec<Vec<math::Vec2>>, base::GeometryError> {
+    Ok(match shape {
+        math::Shape::Triangle(triangle) => vec![vec![triangle.a, triangle.b, triangle.c]],
+        math::Shape::Quad(quad) => vec![vec![quad.a, quad.b, quad.c, quad.d]],
+        math::Shape::CvxPoly(cvx_poly) => vec![cvx_poly.verts.clone()],
+        math::Shape::CavePoly(cave_poly) => {
+            let mut rings = Vec::with_capacity(cave_poly.holes.len() + 1);
+            rings.push(cave_poly.verts.clone());
+            rings.extend(cave_poly.holes.iter().cloned());
+            rings
+        }
+        math::Shape::Segment(_) | math::Shape::Circle(_) => return Err(base::GeometryError::InvalidShape),
+    })
+}
+
+/// even-odd test on all the rings of a shape, so holes count as outside
+#[inline]
+pub(crate) fn rings_contain(rings: &[Vec<math::Vec2>], point: math::Vec2) -> bool {
+    rings.iter().filter(|ring| math::ring_contains(ring, point)).count() % 2 == 1
+}
+
+/// vertices shared by the shapes, vertices closer than the snap distance are the same vertex
+struct VertPool {
+    verts: Vec<math::Vec2>,
+    square_snap: f32,
+    grid: HashMap<(i64, i64), Vec<usize>>, // vertices by cell, cells are as big as the snap distance
+}
+
+impl VertPool {
+    /// the snap distance grows with the size of the shapes, their vertices come first so that intersections snap to
+    /// them and not the other way around
+    fn new(shapes: &[Vec<Vec<math::Vec2>>]) -> Self {
+        let scale = shapes
+            .iter()
+            .flatten()
+            .flatten()
+            .fold(1.0_f32, |scale, vert| scale.max(vert.x.abs()).max(vert.y.abs()));
+        let snap = SNAP_TOLERANCE * scale;
+
+        let mut pool = Self {
+            verts: Vec::new(),
+            square_snap: snap * snap,
+            grid: HashMap::new(),
+        };
+
+        for vert in shapes.iter().flatten().flatten() {
+            pool.intern(*vert);
+        }
+
+        pool
+    }
+
+    #[inline]
+    fn intern(&mut self, vert: math::Vec2) -> usize {
+        let snap = self.square_snap.sqrt();
+        let cell = ((vert.x / snap).floor() as i64, (vert.y / snap).floor() as i64);
+
+        // a vertex within the snap distance can only be in the same cell or in the ones around it
+        for cell_x in (cell.0 - 1)..=(cell.0 + 1) {
+            for cell_y in (cell.1 - 1)..=(cell.1 + 1) {
+                if let Some(&idx) = self
+                    .grid
+                    .get(&(cell_x, cell_y))
+                    .and_then(|idxs| idxs.iter().find(|&&idx| self.verts[idx].square_dist(vert) <= self.square_snap))
+                {
+                    return idx;
+                }
+            }
+        }
+
+        self.verts.push(vert);
+        self.grid.entry(cell).or_default().push(self.verts.len() - 1);
+        self.verts.len() - 1
+    }
+
+    #[inline]
+    fn midpoint(&self, (from, to): EdgePiece) -> math::Vec2 {
+        self.verts[from].midpoint(self.verts[to])
+    }
+}
+
+/// splits the edges of every shape at every point where they touch the edges of the other shapes, so that every piece of
+/// edge is either completely inside, completely outside, or shared with another shape; returns the pieces of each shape
+fn split_edges(shapes: &[Vec<Vec<math::Vec2>>], pool: &mut VertPool) -> Vec<Vec<EdgePiece>> {
+    let snap = pool.square_snap.sqrt();
+
+    let edges: Vec<Vec<(math::Vec2, math::Vec2)>> = shapes
+        .iter()
+        .map(|rings| rings.iter().flat_map(|ring| math::ring_edges(ring)).collect())
+        .collect();
+
+    // the outline of a shape contains its holes, so its hitbox is the hitbox of the shape
+    let hitboxes: Vec<math::HitBox> = shapes.iter().map(|rings| math::HitBox::from_verts_slice(&rings[0])).collect();
+
+    let mut splits: Vec<Vec<Splits>> = edges
+        .iter()
+        .map(|shape_edges| shape_edges.iter().map(|&(a, b)| vec![(0.0, a), (1.0, b)]).collect())
+        .collect();
+
+    // fraction of the edge from a to b closest to point, if point is on the edge
+    let project = |a: math::Vec2, b: math::Vec2, point: math::Vec2| {
+        let edge = b.sub(a);
+        let t = point.sub(a).dot(edge) / edge.square_mag();
+
+        (0.0..=1.0).contains(&t).then_some(t)
+    };
+
+    for shape_1 in 0..shapes.len() {
+        for shape_2 in (shape_1 + 1)..shapes.len() {
+            let (hitbox_1, hitbox_2) = (&hitboxes[shape_1], &hitboxes[shape_2]);
+
+            if hitbox_1.min_x > hitbox_2.max_x + snap
+                || hitbox_2.min_x > hitbox_1.max_x + snap
+                || hitbox_1.min_y > hitbox_2.max_y + snap
+                || hitbox_2.min_y > hitbox_1.max_y + snap
+            {
+                continue;
+            }
+
+            for (idx_1, &(a1, a2)) in edges[shape_1].iter().enumerate() {
+                for (idx_2, &(b1, b2)) in edges[shape_2].iter().enumerate() {
+                    let dir_1 = a2.sub(a1);
+                    let dir_2 = b2.sub(b1);
+                    let (len_1, len_2) = (dir_1.mag(), dir_2.mag());
+                    let denominator = dir_1.cross(dir_2);
+                    let offset = b1.sub(a1);
+
+                    if denominator.abs() > math::EPS * len_1 * len_2 {
+                        // the edges are not parallel, they can cross in a single point
+                        let t1 = offset.cross(dir_2) / denominator;
+                        let t2 = offset.cross(dir_1) / denominator;
+                        let (tolerance_1, tolerance_2) = (snap / len_1, snap / len_2);
+
+                        if (-tolerance_1..=1.0 + tolerance_1).contains(&t1) && (-tolerance_2..=1.0 + tolerance_2).contains(&t2) {
+                            let point = a1.add(dir_1.scale(t1));
+                            splits[shape_1][idx_1].push((t1.clamp(0.0, 1.0), point));
+                            splits[shape_2][idx_2].push((t2.clamp(0.0, 1.0), point));
+                        }
+                    } else if offset.cross(dir_1).abs() <= snap * len_1 {
+                        // the edges are on the same line, they can overlap
+                        for point in [b1, b2] {
+                            if let Some(t) = project(a1, a2, point) {
+                                splits[shape_1][idx_1].push((t, point));
+                            }
+                        }
+
+                        for point in [a1, a2] {
+                            if let Some(t) = project(b1, b2, point) {
+                                splits[shape_2][idx_2].push((t, point));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    splits
+        .into_iter()
+        .map(|shape_splits| {
+            let mut pieces = Vec::new();
+
+            for mut edge_splits in shape_splits {
+                edge_splits.sort_by(|(t1, _), (t2, _)| t1.total_cmp(t2));
+
+                let mut prev_idx = pool.intern(edge_splits[0].1);
+
+                for &(_, point) in &edge_splits[1..] {
+                    let idx = pool.intern(point);
+
+                    if idx != prev_idx {
+                        pieces.push((prev_idx, idx));
+                        prev_idx = idx;
+                    }
+                }
+            }
+
+            pieces
+        })
+        .collect()
+}
+
+/// classifies each piece of edge of a shape against the other shape
+fn classify(pieces: &[EdgePiece], other_pieces: &HashSet<EdgePiece>, other_rings: &[Vec<math::Vec2>], pool: &VertPool) -> Vec<EdgeSide> {
+    pieces
+        .iter()
+        .map(|&(from, to)| {
+            if other_pieces.contains(&(from, to)) {
+                EdgeSide::SharedSame
+            } else if other_pieces.contains(&(to, from)) {
+                EdgeSide::SharedOpposite
+            } else if rings_contain(other_rings, pool.midpoint((from, to))) {
+                EdgeSide::Inside
+            } else {
+                EdgeSide::Outside
+            }
+        })
+        .collect()
+}
+
+/// joins the directed edges into closed rings, when more edges leave the same vertex the one turning the most towards
+/// the inside is taken, so that shapes touching in a single vertex give separate rings
+fn stitch(edges: &[EdgePiece], pool: &VertPool) -> Vec<Vec<math::Vec2>> {
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); pool.verts.len()];
+    for (edge_idx, &(from, _)) in edges.iter().enumerate() {
+        outgoing[from].push(edge_idx);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+
+    for first_idx in 0..edges.len() {
+        if used[first_idx] {
+            continue;
+        }
+
+        used[first_idx] = true;
+
+        let (start, mut curr) = edges[first_idx];
+        let mut prev = start;
+        let mut ring = vec![pool.verts[start]];
+
+        while curr != start {
+            ring.push(pool.verts[curr]);
+
+            let dir_in = pool.verts[curr].sub(pool.verts[prev]);
+
+            // the inside of counterclockwise rings is on the side of negative cross products, so take the smallest angle
+            let next = outgoing[curr]
+                .iter()
+                .copied()
+                .filter(|&edge_idx| !used[edge_idx])
+                .map(|edge_idx| {
+                    let dir_out = pool.verts[edges[edge_idx].1].sub(pool.verts[curr]);
+                    let angle = math::atan2(dir_in.cross(dir_out), dir_in.dot(dir_out));
+
+                    // going straight back is the widest turn
+                    let angle = if angle <= -std::f32::consts::PI + math::EPS {
+                        std::f32::consts::PI
+                    } else {
+                        angle
+                    };
+
+                    (edge_idx, angle)
+                })
+                .min_by(|(_, angle_1), (_, angle_2)| angle_1.total_cmp(angle_2));
+
+            let Some((next_idx, _)) = next else {
+                // the chain is open, which can only happen with degenerate inputs
+                ring.clear();
+                break;
+            };
+
+            used[next_idx] = true;
+            prev = curr;
+            curr = edges[next_idx].1;
+        }
+
+        if !ring.is_empty() {
+            rings.push(ring);
+        }
+    }
+
+    rings
+}
+
+/// removes the vertices that are collinear with their neighbours, returns false if the ring has no area left
+pub(crate) fn clean_ring(ring: &mut Vec<math::Vec2>) -> bool {
+    let mut idx = 0;
+
+    while ring.len() >= 3 && idx < ring.len() {
+        let len = ring.len();
+        let prev = ring[(idx + len - 1) % len];
+        let next = ring[(idx + 1) % len];
+
+        if prev.signed_area(ring[idx], next).abs() < math::EPS {
+            ring.remove(idx);
+            // the previous vertex may have become collinear
+            idx = idx.saturating_sub(1);
+        } else {
+            idx += 1;
+        }
+    }
+
+    ring.len() >= 3 && math::ring_cross_sum(ring).abs() >= math::EPS
+}
+
+/// turns an outline and its holes into the simplest shape describing them
+pub(crate) fn ring_shape(verts: Vec<math::Vec2>, holes: Vec<Vec<math::Vec2>>) -> Result<math::Shape, base::GeometryError> {
+    let convex = (0..verts.len()).all(|idx| {
+        let len = verts.len();
+        verts[idx].signed_area(verts[(idx + 1) % len], verts[(idx + 2) % len]) < -math::EPS
+    });
+
+    let shape = if !holes.is_empty() || !convex {
+        math::Shape::CavePoly(math::CavePoly::with_holes_unchecked(verts, holes))
+    } else {
+        match *verts.as_slice() {
+            [a, b, c] => math::Shape::Triangle(math::Triangle::new_unchecked(a, b, c)),
+            [a, b, c, d] => math::Shape::Quad(math::Quad::new_unchecked(a, b, c, d)),
+            _ => math::Shape::CvxPoly(math::CvxPoly::new_unchecked(verts)),
+        }
+    };
+
+    shape.validate(false)?;
+
+    Ok(shape)
+}
+
+/// a hole touching its outline or another hole in a single vertex cannot be stored in a valid concave polygon, so the
+/// touching vertices of the holes are moved slightly towards the inside of the hole
+fn separate_touching_holes(holes: &mut [Vec<math::Vec2>], outlines: &[(Vec<math::Vec2>, Vec<Vec<math::Vec2>>)], snap: f32) {
+    for hole_idx in 0..holes.len() {
+        let len = holes[hole_idx].len();
+
+        for vert_idx in 0..len {
+            let vert = holes[hole_idx][vert_idx];
+
+            let touching = outlines
+                .iter()
+                .flat_map(|(outline, _)| outline.iter())
+                .chain(holes[..hole_idx].iter().flatten())
+                .any(|other| other.square_dist(vert) <= snap * snap);
+
+            if !touching {
+                continue;
+            }
+
+            let prev = holes[hole_idx][(vert_idx + len - 1) % len];
+            let next = holes[hole_idx][(vert_idx + 1) % len];
+
+            // holes are clockwise, so the inside is towards the neighbours at convex vertices and away from them at reflex ones
+            let towards = prev.midpoint(next).sub(vert);
+            let dir = if prev.signed_area(vert, next) > 0.0 {
+                towards
+            } else {
+                towards.rev()
+            };
+            let nudge = (4.0 * snap).min(0.25 * prev.dist(vert).min(next.dist(vert)));
+
+            if !dir.approx_equal_zero() {
+                holes[hole_idx][vert_idx] = vert.add(dir.norm().scale(nudge));
+            }
+        }
+    }
+}
+
+/// edges of the result of a boolean operation, xor is computed as 2 differences
+fn select_edges(rings_1: &[Vec<math::Vec2>], rings_2: &[Vec<math::Vec2>], op: BooleanOp, pool: &mut VertPool) -> Vec<EdgePiece> {
+    let mut pieces = split_edges(&[rings_1.to_vec(), rings_2.to_vec()], pool);
+    let pieces_2 = pieces.pop().unwrap();
+    let pieces_1 = pieces.pop().unwrap();
+
+    let set_1: HashSet<EdgePiece> = pieces_1.iter().copied().collect();
+    let set_2: HashSet<EdgePiece> = pieces_2.iter().copied().collect();
+
+    let sides_1 = classify(&pieces_1, &set_2, rings_2, pool);
+    let sides_2 = classify(&pieces_2, &set_1, rings_1, pool);
+
+    let mut edges = Vec::new();
+
+    for (&(from, to), side) in pieces_1.iter().zip(sides_1) {
+        let keep = match op {
+            BooleanOp::Union => matches!(side, EdgeSide::Outside | EdgeSide::SharedSame),
+            BooleanOp::Intersection => matches!(side, EdgeSide::Inside | EdgeSide::SharedSame),
+            BooleanOp::Difference => matches!(side, EdgeSide::Outside | EdgeSide::SharedOpposite),
+            BooleanOp::Xor => unreachable!(),
+        };
+
+        if keep {
+            edges.push((from, to));
+        }
+    }
+
+    // shared edges are already taken from the first shape
+    for (&(from, to), side) in pieces_2.iter().zip(sides_2) {
+        match (op, side) {
+            (BooleanOp::Union, EdgeSide::Outside) | (BooleanOp::Intersection, EdgeSide::Inside) => edges.push((from, to)),
+            // the second shape is subtracted, so its edges are walked the other way around
+            (BooleanOp::Difference, EdgeSide::Inside) => edges.push((to, from)),
+            _ => (),
+        }
+    }
+
+    edges
+}
+
+/// stitches the edges of the result of a boolean operation and builds its shapes
+fn build_shapes(edges: &[EdgePiece], pool: &VertPool) -> Result<Vec<math::Shape>, base::GeometryError> {
+    let mut outlines = Vec::new();
+    let mut holes: Vec<Vec<math::Vec2>> = Vec::new();
+
+    for mut ring in stitch(edges, pool) {
+        if !clean_ring(&mut ring) {
+            continue;
+        }
+
+        if math::ring_cross_sum(&ring) < 0.0 {
+            outlines.push((ring, Vec::new()));
+        } else {
+            holes.push(ring);
+        }
+    }
+
+    separate_touching_holes(&mut holes, &outlines, pool.square_snap.sqrt());
+
+    // every hole goes to the smallest outline containing it
+    for hole in holes {
+        let outline = outlines
+            .iter_mut()
+            .filter(|(outline, _)| math::ring_contains(outline, hole[0]))
+            .min_by(|(outline_1, _), (outline_2, _)| {
+                math::ring_cross_sum(outline_2).total_cmp(&math::ring_cross_sum(outline_1)) // areas are negative
+            });
+
+        match outline {
+            Some((_, outline_holes)) => outline_holes.push(hole),
+            None => return Err(base::GeometryError::InvalidShape),
+        }
+    }
+
+    outlines.into_iter().map(|(verts, holes)| ring_shape(verts, holes)).collect()
+}
+
+/// computes a boolean operation between 2 polygonal shapes (triangles, quads, convex and concave polygons), the result
+/// is made of the simplest valid shapes describing it, concave polygons get holes when needed; both shapes must be valid
+/// and expressed in the same space; holes touching the rest of the result in a single vertex are moved slightly apart
+/// (see separate_touching_holes()); returns an error for segments and circles, or if the result cannot be made valid
+pub fn boolean_op(shape_1: &math::Shape, shape_2: &math::Shape, op: BooleanOp) -> Result<Vec<math::Shape>, base::GeometryError> {
+    let rings_1 = rings(shape_1)?;
+    let rings_2 = rings(shape_2)?;
+
+    let mut pool = VertPool::new(&[rings_1.clone(), rings_2.clone()]);
+
+    let edges = match op {
+        BooleanOp::Xor => {
+            let mut edges = select_edges(&rings_1, &rings_2, BooleanOp::Difference, &mut pool);
+            edges.extend(select_edges(&rings_2, &rings_1, BooleanOp::Difference, &mut pool));
+            edges
+        }
+        _ => select_edges(&rings_1, &rings_2, op, &mut pool),
+    };
+
+    build_shapes(&edges, &pool)
+}
+
+/// area covered by either shape, see boolean_op()
+#[inline]
+pub fn union(shape_1: &math::Shape, shape_2: &math::Shape) -> Result<Vec<math::Shape>, base::GeometryError> {
+    boolean_op(shape_1, shape_2, BooleanOp::Union)
+}
+
+/// area covered by shape_1 but not by shape_2, see boolean_op()
+#[inline]
+pub fn difference(shape_1: &math::Shape, shape_2: &math::Shape) -> Result<Vec<math::Shape>, base::GeometryError> {
+    boolean_op(shape_1, shape_2, BooleanOp::Difference)
+}
+
+/// area covered by both shapes, see boolean_op()
+#[inline]
+pub fn intersection(shape_1: &math::Shape, shape_2: &math::Shape) -> Result<Vec<math::Shape>, base::GeometryError> {
+    boolean_op(shape_1, shape_2, BooleanOp::Intersection)
+}
+
+/// area covered by exactly one of the shapes, see boolean_op()
+#[inline]
+pub fn xor(shape_1: &math::Shape, shape_2: &math::Shape) -> Result<Vec<math::Shape>, base::GeometryError> {
+    boolean_op(shape_1, shape_2, BooleanOp::Xor)
+}
+
+/// pieces of edges of the shapes in range that are on the boundary of their union
+fn union_boundary(
+    shapes_rings: &[Vec<Vec<math::Vec2>>],
+    pieces: &[Vec<EdgePiece>],
+    pieces_sets: &[HashSet<EdgePiece>],
+    range: ops::Range<usize>,
+    pool: &VertPool,
+) -> Vec<EdgePiece> {
+    let mut edges = Vec::new();
+
+    for shape_idx in range.clone() {
+        for &(from, to) in &pieces[shape_idx] {
+            let midpoint = pool.midpoint((from, to));
+
+            // a piece is on the boundary of the union unless another shape covers the area beyond it
+            let covered = range.clone().filter(|&other_idx| other_idx != shape_idx).any(|other_idx| {
+                if pieces_sets[other_idx].contains(&(to, from)) {
+                    true
+                } else if pieces_sets[other_idx].contains(&(from, to)) {
+                    // shared edges are taken only from the first shape having them
+                    other_idx < shape_idx
+                } else {
+                    rings_contain(&shapes_rings[other_idx], midpoint)
+                }
+            });
+
+            if !covered {
+                edges.push((from, to));
+            }
+        }
+    }
+
+    edges
+}
+
+/// area covered by any of the shapes, computed in a single pass, which is faster and more robust than merging the shapes
+/// one by one with union(); see boolean_op()
+pub fn union_all(shapes: &[math::Shape]) -> Result<Vec<math::Shape>, base::GeometryError> {
+    let shapes_rings = shapes.iter().map(rings).collect::<Result<Vec<_>, _>>()?;
+
+    let mut pool = VertPool::new(&shapes_rings);
+    let pieces = split_edges(&shapes_rings, &mut pool);
+    let pieces_sets: Vec<HashSet<EdgePiece>> = pieces.iter().map(|shape_pieces| shape_pieces.iter().copied().collect()).collect();
+
+    let edges = union_boundary(&shapes_rings, &pieces, &pieces_sets, 0..shapes_rings.len(), &pool);
+
+    build_shapes(&edges, &pool)
+}
+
+/// area covered by shape but not by any of the others, computed in a single pass like union_all(); see boolean_op()
+pub fn difference_all(shape: &math::Shape, others: &[math::Shape]) -> Result<Vec<math::Shape>, base::GeometryError> {
+    let shapes_rings = std::iter::once(shape).chain(others).map(rings).collect::<Result<Vec<_>, _>>()?;
+
+    let mut pool = VertPool::new(&shapes_rings);
+    let pieces = split_edges(&shapes_rings, &mut pool);
+    let pieces_sets: Vec<HashSet<EdgePiece>> = pieces.iter().map(|shape_pieces| shape_pieces.iter().copied().collect()).collect();
+
+    let mut edges = Vec::new();
+
+    // the pieces of the shape outside the others, shared edges are kept only if the others are on the other side
+    for &(from, to) in &pieces[0] {
+        let midpoint = pool.midpoint((from, to));
+
+        let covered = (1..shapes_rings.len()).any(|other_idx| {
+            if pieces_sets[other_idx].contains(&(from, to)) {
+                true
+            } else if pieces_sets[other_idx].contains(&(to, from)) {
+                false
+            } else {
+                rings_contain(&shapes_rings[other_idx], midpoint)
+            }
+        });
+
+        if !covered {
+            edges.push((from, to));
+        }
+    }
+
+    // the boundary of the others inside the shape, walked the other way around since it is subtracted
+    for (from, to) in union_boundary(&shapes_rings, &pieces, &pieces_sets, 1..shapes_rings.len(), &pool) {
+        let shared = pieces_sets[0].contains(&(from, to)) || pieces_sets[0].contains(&(to, from));
+
+        if !shared && rings_contain(&shapes_rings[0], pool.midpoint((from, to))) {
+            edges.push((to, from));
+        }
+    }
+
+    build_shapes(&edges, &pool)
+}
@@ -0,0 +1,475 @@
+use crate::math::Validate;
+use crate::{base, math};
+
+use bincode::{Decode, Encode};
+use serde::Deserialize;
+
+/// subdivisions forced on every piece of a curve before testing flatness, so that s-shaped pieces whose samples happen
+/// to lie on the chord are not taken as straight
+const MIN_FLATTEN_DEPTH: usize = 2;
+
+/// subdivisions after which a piece is taken as flat anyway, bounds the work for tiny tolerances
+const MAX_FLATTEN_DEPTH: usize = 16;
+
+/// parametric curve, t goes from 0 at the start of the curve to 1 at its end
+pub trait Curve {
+    fn point(&self, t: f32) -> math::Vec2;
+    fn derivative(&self, t: f32) -> math::Vec2;
+
+    /// number of pieces the curve is made of, each one is flattened on its own so that the joints are kept
+    #[inline]
+    fn pieces(&self) -> usize {
+        1
+    }
+
+    /// unit direction of travel, falls back to the chord around t where the derivative vanishes (like on cusps)
+    #[inline]
+    fn tangent(&self, t: f32) -> math::Vec2 {
+        let derivative = self.derivative(t);
+
+        if !derivative.approx_equal_zero() {
+            return derivative.norm();
+        }
+
+        let chord = self.point((t + 1e-3).min(1.0)).sub(self.point((t - 1e-3).max(0.0)));
+
+        if chord.approx_equal_zero() {
+            math::Vec2::ZERO
+        } else {
+            chord.norm()
+        }
+    }
+
+    /// unit normal on the left of the direction of travel, so it points up for a curve going right
+    #[inline]
+    fn normal(&self, t: f32) -> math::Vec2 {
+        self.tangent(t).perp_cw()
+    }
+
+    /// parameters of the polyline that stays closer than tolerance to the curve, from 0 to 1 included
+    fn flatten_params(&self, tolerance: f32) -> Vec<f32> {
+        let tolerance = tolerance.max(math::EPS);
+        let pieces = self.pieces().max(1);
+        let mut params = vec![0.0];
+
+        for piece in 0..pieces {
+            let t_0 = piece as f32 / pieces as f32;
+            let t_1 = (piece + 1) as f32 / pieces as f32;
+
+            flatten_range(self, (t_0, self.point(t_0)), (t_1, self.point(t_1)), tolerance, 0, &mut params);
+        }
+
+        params
+    }
+
+    /// polyline that stays closer than tolerance to the curve, it includes both end points
+    #[inline]
+    fn flatten(&self, tolerance: f32) -> Vec<math::Vec2> {
+        self.flatten_params(tolerance).into_iter().map(|t| self.point(t)).collect()
+    }
+
+    /// length of the curve, measured on the polyline given by flatten()
+    #[inline]
+    fn length(&self, tolerance: f32) -> f32 {
+        self.flatten(tolerance).windows(2).map(|pair| pair[0].dist(pair[1])).sum()
+    }
+
+    /// flattens the curve into a chain of segments, degenerate ones are skipped
+    #[inline]
+    fn to_segments(&self, tolerance: f32) -> Vec<math::Segment> {
+        self.flatten(tolerance)
+            .windows(2)
+            .filter_map(|pair| math::Segment::new_checked(pair[0], pair[1]).ok())
+            .collect()
+    }
+
+    /// flattens the curve into the outline of a concave polygon, closed by joining its end to its start; the outline is
+    /// wound counterclockwise and cleaned of collinear vertices before validation
+    fn to_cave_poly(&self, tolerance: f32) -> Result<math::CavePoly, base::GeometryError> {
+        let mut verts = self.flatten(tolerance);
+
+        if verts.len() > 1 && verts[0].approx_equal(verts[verts.len() - 1]) {
+            verts.pop();
+        }
+
+        if !math::clean_ring(&mut verts) {
+            return Err(base::GeometryError::TooFewVertices(verts.len()));
+        }
+
+        let mut cave_poly = math::CavePoly::new_unchecked(verts);
+        cave_poly.normalize()?;
+        cave_poly.validate(false)?;
+
+        Ok(cave_poly)
+    }
+}
+
+/// recursively splits the range between the two (parameter, point) pairs until the curve is closer than tolerance to
+/// the chord, pushes every parameter except the first one
+fn flatten_range<C: Curve + ?Sized>(
+    curve: &C,
+    (t_0, point_0): (f32, math::Vec2),
+    (t_1, point_1): (f32, math::Vec2),
+    tolerance: f32,
+    depth: usize,
+    params: &mut Vec<f32>,
+) {
+    let t_mid = 0.5 * (t_0 + t_1);
+    let point_mid = curve.point(t_mid);
+
+    let flat = depth >= MIN_FLATTEN_DEPTH
+        && [point_mid, curve.point(0.5 * (t_0 + t_mid)), curve.point(0.5 * (t_mid + t_1))]
+            .iter()
+            .all(|&point| math::segment_dist(point, point_0, point_1) <= tolerance);
+
+    if flat || depth >= MAX_FLATTEN_DEPTH {
+        params.push(t_1);
+        return;
+    }
+
+    flatten_range(curve, (t_0, point_0), (t_mid, point_mid), tolerance, depth + 1, params);
+    flatten_range(curve, (t_mid, point_mid), (t_1, point_1), tolerance, depth + 1, params);
+}
+
+/// maps distances along a curve to its parameter, so that it can be traveled at constant speed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcLengthTable {
+    params: Vec<f32>,
+    lengths: Vec<f32>, // length of the polyline from the start of the curve to every parameter
+}
+
+impl ArcLengthTable {
+    /// samples the curve with flatten_params(), a lower tolerance gives a more uniform speed
+    pub fn new<C: Curve + ?Sized>(curve: &C, tolerance: f32) -> Self {
+        let params = curve.flatten_params(tolerance);
+        let mut lengths = Vec::with_capacity(params.len());
+        let mut length = 0.0;
+        let mut prev = curve.point(params[0]);
+
+        for &t in &params {
+            let point = curve.point(t);
+            length += prev.dist(point);
+            lengths.push(length);
+            prev = point;
+        }
+
+        Self { params, lengths }
+    }
+
+    #[inline]
+    pub fn length(&self) -> f32 {
+        self.lengths[self.lengths.len() - 1]
+    }
+
+    /// parameter of the point that is dist away from the start along the curve, dist is clamped to the curve length
+    pub fn param_at(&self, dist: f32) -> f32 {
+        let dist = dist.clamp(0.0, self.length());
+        let idx = self.lengths.partition_point(|&length| length < dist);
+
+        if idx == 0 {
+            return self.params[0];
+        }
+
+        let (length_0, length_1) = (self.lengths[idx - 1], self.lengths[idx]);
+        let (t_0, t_1) = (self.params[idx - 1], self.params[idx]);
+
+        if length_1 - length_0 < math::EPS {
+            return t_1;
+        }
+
+        math::mul_add(t_1 - t_0, (dist - length_0) / (length_1 - length_0), t_0)
+    }
+
+    /// point that is dist away from the start along the curve
+    #[inline]
+    pub fn point_at<C: Curve + ?Sized>(&self, curve: &C, dist: f32) -> math::Vec2 {
+        curve.point(self.param_at(dist))
+    }
+}
+
+/// quadratic bezier curve from start to end, pulled toward control
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Deserialize)]
+pub struct QuadBezier {
+    pub(crate) start: math::Vec2,
+    pub(crate) control: math::Vec2,
+    pub(crate) end: math::Vec2,
+}
+
+impl QuadBezier {
+    #[inline]
+    pub const fn new(start: math::Vec2, control: math::Vec2, end: math::Vec2) -> Self {
+        Self { start, control, end }
+    }
+
+    #[inline]
+    pub fn start(&self) -> math::Vec2 {
+        self.start
+    }
+
+    #[inline]
+    pub fn control(&self) -> math::Vec2 {
+        self.control
+    }
+
+    #[inline]
+    pub fn end(&self) -> math::Vec2 {
+        self.end
+    }
+
+    #[inline]
+    pub fn start_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.start
+    }
+
+    #[inline]
+    pub fn control_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.control
+    }
+
+    #[inline]
+    pub fn end_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.end
+    }
+}
+
+impl Curve for QuadBezier {
+    #[inline]
+    fn point(&self, t: f32) -> math::Vec2 {
+        let u = 1.0 - t;
+
+        self.start
+            .scale(u * u)
+            .add(self.control.scale(2.0 * u * t))
+            .add(self.end.scale(t * t))
+    }
+
+    #[inline]
+    fn derivative(&self, t: f32) -> math::Vec2 {
+        let u = 1.0 - t;
+
+        self.control
+            .sub(self.start)
+            .scale(2.0 * u)
+            .add(self.end.sub(self.control).scale(2.0 * t))
+    }
+}
+
+/// cubic bezier curve from start to end, leaving start toward control_1 and reaching end from control_2
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Deserialize)]
+pub struct CubicBezier {
+    pub(crate) start: math::Vec2,
+    pub(crate) control_1: math::Vec2,
+    pub(crate) control_2: math::Vec2,
+    pub(crate) end: math::Vec2,
+}
+
+impl CubicBezier {
+    #[inline]
+    pub const fn new(start: math::Vec2, control_1: math::Vec2, control_2: math::Vec2, end: math::Vec2) -> Self {
+        Self {
+            start,
+            control_1,
+            control_2,
+            end,
+        }
+    }
+
+    #[inline]
+    pub fn start(&self) -> math::Vec2 {
+        self.start
+    }
+
+    #[inline]
+    pub fn control_1(&self) -> math::Vec2 {
+        self.control_1
+    }
+
+    #[inline]
+    pub fn control_2(&self) -> math::Vec2 {
+        self.control_2
+    }
+
+    #[inline]
+    pub fn end(&self) -> math::Vec2 {
+        self.end
+    }
+
+    #[inline]
+    pub fn start_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.start
+    }
+
+    #[inline]
+    pub fn control_1_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.control_1
+    }
+
+    #[inline]
+    pub fn control_2_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.control_2
+    }
+
+    #[inline]
+    pub fn end_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.end
+    }
+}
+
+impl Curve for CubicBezier {
+    #[inline]
+    fn point(&self, t: f32) -> math::Vec2 {
+        let u = 1.0 - t;
+
+        self.start
+            .scale(u * u * u)
+            .add(self.control_1.scale(3.0 * u * u * t))
+            .add(self.control_2.scale(3.0 * u * t * t))
+            .add(self.end.scale(t * t * t))
+    }
+
+    #[inline]
+    fn derivative(&self, t: f32) -> math::Vec2 {
+        let u = 1.0 - t;
+
+        self.control_1
+            .sub(self.start)
+            .scale(3.0 * u * u)
+            .add(self.control_2.sub(self.control_1).scale(6.0 * u * t))
+            .add(self.end.sub(self.control_2).scale(3.0 * t * t))
+    }
+}
+
+/// uniform catmull-rom spline through every point; an open spline runs from the first point to the last one, a closed
+/// spline also joins the last point back to the first one
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Deserialize)]
+pub struct CatmullRom {
+    pub(crate) points: Vec<math::Vec2>,
+    #[serde(default)]
+    pub(crate) closed: bool,
+}
+
+impl CatmullRom {
+    #[inline]
+    pub fn new_checked(points: Vec<math::Vec2>, closed: bool) -> Result<Self, base::GeometryError> {
+        let spline = Self { points, closed };
+
+        spline.validate()?;
+
+        Ok(spline)
+    }
+
+    #[inline]
+    pub const fn new_unchecked(points: Vec<math::Vec2>, closed: bool) -> Self {
+        Self { points, closed }
+    }
+
+    /// an open spline needs 2 points and a closed one needs 3, consecutive points must not overlap
+    #[inline]
+    pub fn validate(&self) -> Result<(), base::GeometryError> {
+        let min_len = if self.closed { 3 } else { 2 };
+
+        if self.points.len() < min_len {
+            return Err(base::GeometryError::TooFewVertices(self.points.len()));
+        }
+
+        let len = self.points.len();
+        let edges = if self.closed { len } else { len - 1 };
+
+        if (0..edges).any(|idx| self.points[idx].approx_equal(self.points[(idx + 1) % len])) {
+            return Err(base::GeometryError::DuplicateVertices);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn points(&self) -> &[math::Vec2] {
+        &self.points
+    }
+
+    #[inline]
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    #[inline]
+    pub fn set_points(&mut self, new_points: Vec<math::Vec2>) -> Result<(), base::GeometryError> {
+        self.points = Self::new_checked(new_points, self.closed)?.points;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_points_unchecked(&mut self, new_points: Vec<math::Vec2>) {
+        self.points = new_points;
+    }
+
+    /// control point idx, wrapped on closed splines and mirrored past the ends of open ones
+    #[inline]
+    fn control(&self, idx: isize) -> math::Vec2 {
+        let len = self.points.len() as isize;
+
+        if self.closed {
+            return self.points[idx.rem_euclid(len) as usize];
+        }
+
+        if idx < 0 {
+            self.points[0].scale(2.0).sub(self.points[1])
+        } else if idx >= len {
+            self.points[len as usize - 1].scale(2.0).sub(self.points[len as usize - 2])
+        } else {
+            self.points[idx as usize]
+        }
+    }
+
+    /// index of the piece containing t and the local parameter inside it
+    #[inline]
+    fn locate(&self, t: f32) -> (isize, f32) {
+        let pieces = self.pieces();
+        let scaled = t.clamp(0.0, 1.0) * pieces as f32;
+        let piece = (scaled.floor() as usize).min(pieces - 1);
+
+        (piece as isize, scaled - piece as f32)
+    }
+}
+
+impl Curve for CatmullRom {
+    #[inline]
+    fn pieces(&self) -> usize {
+        if self.closed { self.points.len() } else { self.points.len() - 1 }
+    }
+
+    #[inline]
+    fn point(&self, t: f32) -> math::Vec2 {
+        let (piece, s) = self.locate(t);
+        let (p0, p1, p2, p3) = (
+            self.control(piece - 1),
+            self.control(piece),
+            self.control(piece + 1),
+            self.control(piece + 2),
+        );
+        let (s2, s3) = (s * s, s * s * s);
+
+        p1.scale(2.0)
+            .add(p2.sub(p0).scale(s))
+            .add(p0.scale(2.0).sub(p1.scale(5.0)).add(p2.scale(4.0)).sub(p3).scale(s2))
+            .add(p1.scale(3.0).sub(p0).sub(p2.scale(3.0)).add(p3).scale(s3))
+            .scale(0.5)
+    }
+
+    /// derivative with respect to t, so it is scaled by the number of pieces
+    #[inline]
+    fn derivative(&self, t: f32) -> math::Vec2 {
+        let (piece, s) = self.locate(t);
+        let (p0, p1, p2, p3) = (
+            self.control(piece - 1),
+            self.control(piece),
+            self.control(piece + 1),
+            self.control(piece + 2),
+        );
+
+        p2.sub(p0)
+            .add(p0.scale(2.0).sub(p1.scale(5.0)).add(p2.scale(4.0)).sub(p3).scale(2.0 * s))
+            .add(p1.scale(3.0).sub(p0).sub(p2.scale(3.0)).add(p3).scale(3.0 * s * s))
+            .scale(0.5 * self.pieces() as f32)
+    }
+}
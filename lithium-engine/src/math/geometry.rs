@@ -148,7 +148,36 @@ pub trait SatCompatible {
     fn sides_number(&self) -> usize;
     fn append_sides(&self, sides: &mut Vec<math::Vec2>);
     fn project(&self, axis: math::Vec2) -> (f32, f32);
-    fn split_cave(&self) -> Result<Option<&[CvxPoly]>, base::GeometryError>;
+    fn split_cave(&self) -> Result<Option<CaveParts<'_>>, base::GeometryError>;
+}
+
+/// convex pieces of a concave geometry together with their hitboxes
+#[derive(Debug, Clone, Copy)]
+pub struct CaveParts<'a> {
+    pub cvx_polys: &'a [CvxPoly],
+    pub hitboxes: &'a [HitBox],
+}
+
+/// support function used by gjk, returns the point of the geometry that is furthest along dir
+pub trait Support {
+    fn support(&self, dir: math::Vec2) -> math::Vec2;
+}
+
+/// furthest vertex along dir
+#[inline]
+fn support_verts(verts: &[math::Vec2], dir: math::Vec2) -> math::Vec2 {
+    let mut best = verts[0];
+    let mut best_dot = best.dot(dir);
+
+    for &vert in &verts[1..] {
+        let dot = vert.dot(dir);
+        if dot > best_dot {
+            best = vert;
+            best_dot = dot;
+        }
+    }
+
+    best
 }
 
 pub trait ApplyTransformationVerts {
@@ -202,7 +231,7 @@ pub fn convex_hull(mut verts: &mut [math::Vec2]) -> Result<CvxPoly, base::Geomet
     fn push_vert(boundary: &mut Vec<math::Vec2>, vert: math::Vec2, init_len: usize) {
         while boundary.len() >= init_len {
             let len = boundary.len();
-            if (boundary[len - 2]).signed_area(boundary[len - 1], vert) >= -math::EPS {
+            if math::orientation(boundary[len - 2], boundary[len - 1], vert) != math::Orientation::CounterClockWise {
                 boundary.pop();
             } else {
                 break;
@@ -259,7 +288,7 @@ impl Validate for Shape {
             Shape::Quad(quad) => quad.validate(warnings)?,
             Shape::CvxPoly(cvx_poly) => cvx_poly.validate(warnings)?,
             Shape::CavePoly(cave_poly) => cave_poly.validate(warnings)?,
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => circle.validate(warnings)?,
         };
 
         Ok(())
@@ -273,7 +302,7 @@ impl Validate for Shape {
             Shape::Quad(quad) => quad.normalize()?,
             Shape::CvxPoly(cvx_poly) => cvx_poly.normalize()?,
             Shape::CavePoly(cave_poly) => cave_poly.normalize()?,
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => circle.normalize()?,
         };
 
         Ok(())
@@ -289,7 +318,7 @@ impl Centroid for Shape {
             Shape::Quad(quad) => quad.centroid(),
             Shape::CvxPoly(cvx_poly) => cvx_poly.centroid(),
             Shape::CavePoly(cave_poly) => cave_poly.centroid(),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => circle.centroid(),
         }
     }
 }
@@ -317,7 +346,7 @@ impl SatCompatible for Shape {
             Shape::Quad(_) => 4,
             Shape::CvxPoly(cvx_poly) => cvx_poly.sides_number(),
             Shape::CavePoly(_) => unimplemented!(),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => circle.sides_number(),
         }
     }
 
@@ -329,7 +358,7 @@ impl SatCompatible for Shape {
             Shape::Quad(quad) => quad.append_sides(sides),
             Shape::CvxPoly(cvx_poly) => cvx_poly.append_sides(sides),
             Shape::CavePoly(_) => unimplemented!(),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => circle.append_sides(sides),
         }
     }
 
@@ -341,19 +370,33 @@ impl SatCompatible for Shape {
             Shape::Quad(quad) => quad.project(axis),
             Shape::CvxPoly(cvx_poly) => cvx_poly.project(axis),
             Shape::CavePoly(_) => unimplemented!(),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => circle.project(axis),
         }
     }
 
     #[inline]
-    fn split_cave(&self) -> Result<Option<&[CvxPoly]>, base::GeometryError> {
+    fn split_cave(&self) -> Result<Option<CaveParts<'_>>, base::GeometryError> {
         match self {
             Shape::Segment(_) => Ok(None),
             Shape::Triangle(_) => Ok(None),
             Shape::Quad(_) => Ok(None),
             Shape::CvxPoly(_) => Ok(None),
             Shape::CavePoly(cave_poly) => cave_poly.split_cave(),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => circle.split_cave(),
+        }
+    }
+}
+
+impl Support for Shape {
+    #[inline]
+    fn support(&self, dir: math::Vec2) -> math::Vec2 {
+        match self {
+            Shape::Segment(segment) => segment.support(dir),
+            Shape::Triangle(triangle) => triangle.support(dir),
+            Shape::Quad(quad) => quad.support(dir),
+            Shape::CvxPoly(cvx_poly) => cvx_poly.support(dir),
+            Shape::CavePoly(cave_poly) => cave_poly.support(dir),
+            Shape::Circle(circle) => circle.support(dir),
         }
     }
 }
@@ -367,7 +410,7 @@ impl ApplyTransformationShape for Shape {
             Shape::Quad(quad) => Shape::Quad(quad.apply_vec2_checked(vec)?),
             Shape::CvxPoly(cvx_poly) => Shape::CvxPoly(cvx_poly.apply_vec2_checked(vec)?),
             Shape::CavePoly(cave_poly) => Shape::CavePoly(cave_poly.apply_vec2_checked(vec)?),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => Shape::Circle(circle.apply_vec2_checked(vec)?),
         })
     }
 
@@ -379,7 +422,7 @@ impl ApplyTransformationShape for Shape {
             Shape::Quad(quad) => Shape::Quad(quad.apply_vec2_unchecked(vec)),
             Shape::CvxPoly(cvx_poly) => Shape::CvxPoly(cvx_poly.apply_vec2_unchecked(vec)),
             Shape::CavePoly(cave_poly) => Shape::CavePoly(cave_poly.apply_vec2_unchecked(vec)),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => Shape::Circle(circle.apply_vec2_unchecked(vec)),
         }
     }
 
@@ -391,7 +434,7 @@ impl ApplyTransformationShape for Shape {
             Shape::Quad(quad) => Shape::Quad(quad.apply_mat2x3_checked(mat)?),
             Shape::CvxPoly(cvx_poly) => Shape::CvxPoly(cvx_poly.apply_mat2x3_checked(mat)?),
             Shape::CavePoly(cave_poly) => Shape::CavePoly(cave_poly.apply_mat2x3_checked(mat)?),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => Shape::Circle(circle.apply_mat2x3_checked(mat)?),
         })
     }
 
@@ -403,7 +446,7 @@ impl ApplyTransformationShape for Shape {
             Shape::Quad(quad) => Shape::Quad(quad.apply_mat2x3_unchecked(mat)),
             Shape::CvxPoly(cvx_poly) => Shape::CvxPoly(cvx_poly.apply_mat2x3_unchecked(mat)),
             Shape::CavePoly(cave_poly) => Shape::CavePoly(cave_poly.apply_mat2x3_unchecked(mat)),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => Shape::Circle(circle.apply_mat2x3_unchecked(mat)),
         }
     }
 
@@ -415,7 +458,7 @@ impl ApplyTransformationShape for Shape {
             Shape::Quad(quad) => Shape::Quad(quad.apply_mat2x3_then_vec2_checked(vec, mat)?),
             Shape::CvxPoly(cvx_poly) => Shape::CvxPoly(cvx_poly.apply_mat2x3_then_vec2_checked(vec, mat)?),
             Shape::CavePoly(cave_poly) => Shape::CavePoly(cave_poly.apply_mat2x3_then_vec2_checked(vec, mat)?),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => Shape::Circle(circle.apply_mat2x3_then_vec2_checked(vec, mat)?),
         })
     }
 
@@ -427,11 +470,42 @@ impl ApplyTransformationShape for Shape {
             Shape::Quad(quad) => Shape::Quad(quad.apply_mat2x3_then_vec2_unchecked(vec, mat)),
             Shape::CvxPoly(cvx_poly) => Shape::CvxPoly(cvx_poly.apply_mat2x3_then_vec2_unchecked(vec, mat)),
             Shape::CavePoly(cave_poly) => Shape::CavePoly(cave_poly.apply_mat2x3_then_vec2_unchecked(vec, mat)),
-            Shape::Circle(_) => unimplemented!(),
+            Shape::Circle(circle) => Shape::Circle(circle.apply_mat2x3_then_vec2_unchecked(vec, mat)),
         }
     }
 }
 
+impl Shape {
+    /// scales the shape along the axes relative to its origin, a negative factor mirrors it and the winding is restored
+    /// afterwards; circles only accept the same positive factor on both axes
+    pub fn scale_checked(&self, scale: math::Vec2) -> Result<Self, base::GeometryError> {
+        if scale.x.abs() < math::EPS || scale.y.abs() < math::EPS {
+            return Err(base::GeometryError::InvalidScale);
+        }
+
+        if let Shape::Circle(circle) = self {
+            if (scale.x - scale.y).abs() > math::EPS || scale.x < 0.0 {
+                return Err(base::GeometryError::InvalidScale);
+            }
+
+            return Ok(Shape::Circle(Circle::new_checked(
+                circle.center.mul(scale),
+                circle.radius * scale.x,
+            )?));
+        }
+
+        let mut shape = self.apply_mat2x3_unchecked(&math::Mat2x3::from_scale(scale));
+
+        if scale.x * scale.y < 0.0 {
+            shape.normalize()?;
+        }
+
+        shape.validate(false)?;
+
+        Ok(shape)
+    }
+}
+
 impl fmt::Display for Shape {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -510,7 +584,7 @@ impl Segment {
         let m = delta_y / delta_x;
         let q = self.a.y - m * self.a.x;
 
-        Some(x.mul_add(m, q))
+        Some(math::mul_add(x, m, q))
     }
 
     #[inline]
@@ -571,6 +645,13 @@ impl ToHitBox for Segment {
     }
 }
 
+impl Support for Segment {
+    #[inline]
+    fn support(&self, dir: math::Vec2) -> math::Vec2 {
+        support_verts(&[self.a, self.b], dir)
+    }
+}
+
 impl SatCompatible for Segment {
     #[inline]
     fn sides_number(&self) -> usize {
@@ -592,7 +673,7 @@ impl SatCompatible for Segment {
     }
 
     #[inline]
-    fn split_cave(&self) -> Result<Option<&[CvxPoly]>, base::GeometryError> {
+    fn split_cave(&self) -> Result<Option<CaveParts<'_>>, base::GeometryError> {
         Ok(None)
     }
 }
@@ -771,7 +852,7 @@ impl Validate for Triangle {
         };
 
         // check non-collinear + clockwise winding
-        if self.a.signed_area(self.b, self.c) >= -math::EPS {
+        if math::orientation(self.a, self.b, self.c) != math::Orientation::CounterClockWise {
             return Err(base::GeometryError::NotConvex);
         }
 
@@ -780,7 +861,7 @@ impl Validate for Triangle {
 
     #[inline]
     fn normalize(&mut self) -> Result<(), base::GeometryError> {
-        if self.a.signed_area(self.b, self.c) > 0.0 {
+        if math::orientation(self.a, self.b, self.c) == math::Orientation::ClockWise {
             mem::swap(&mut self.b, &mut self.c);
         }
 
@@ -802,6 +883,13 @@ impl ToHitBox for Triangle {
     }
 }
 
+impl Support for Triangle {
+    #[inline]
+    fn support(&self, dir: math::Vec2) -> math::Vec2 {
+        support_verts(&[self.a, self.b, self.c], dir)
+    }
+}
+
 impl SatCompatible for Triangle {
     #[inline]
     fn sides_number(&self) -> usize {
@@ -825,7 +913,7 @@ impl SatCompatible for Triangle {
     }
 
     #[inline]
-    fn split_cave(&self) -> Result<Option<&[CvxPoly]>, base::GeometryError> {
+    fn split_cave(&self) -> Result<Option<CaveParts<'_>>, base::GeometryError> {
         Ok(None)
     }
 }
@@ -1097,10 +1185,10 @@ impl Validate for Quad {
         };
 
         // check if the quadrilateral is convex
-        if self.a.signed_area(self.b, self.c) >= -math::EPS
-            || self.b.signed_area(self.c, self.d) >= -math::EPS
-            || self.c.signed_area(self.d, self.a) >= -math::EPS
-            || self.d.signed_area(self.a, self.b) >= -math::EPS
+        if math::orientation(self.a, self.b, self.c) != math::Orientation::CounterClockWise
+            || math::orientation(self.b, self.c, self.d) != math::Orientation::CounterClockWise
+            || math::orientation(self.c, self.d, self.a) != math::Orientation::CounterClockWise
+            || math::orientation(self.d, self.a, self.b) != math::Orientation::CounterClockWise
         {
             return Err(base::GeometryError::NotConvex);
         }
@@ -1132,6 +1220,13 @@ impl ToHitBox for Quad {
     }
 }
 
+impl Support for Quad {
+    #[inline]
+    fn support(&self, dir: math::Vec2) -> math::Vec2 {
+        support_verts(&[self.a, self.b, self.c, self.d], dir)
+    }
+}
+
 impl SatCompatible for Quad {
     #[inline]
     fn sides_number(&self) -> usize {
@@ -1158,7 +1253,7 @@ impl SatCompatible for Quad {
     }
 
     #[inline]
-    fn split_cave(&self) -> Result<Option<&[CvxPoly]>, base::GeometryError> {
+    fn split_cave(&self) -> Result<Option<CaveParts<'_>>, base::GeometryError> {
         Ok(None)
     }
 }
@@ -1366,9 +1461,7 @@ impl Validate for CvxPoly {
             let i1 = (i + 1) % verts_len;
             let i2 = (i + 2) % verts_len;
 
-            let area = self.verts[i].signed_area(self.verts[i1], self.verts[i2]);
-
-            if area >= -math::EPS {
+            if math::orientation(self.verts[i], self.verts[i1], self.verts[i2]) != math::Orientation::CounterClockWise {
                 return Err(base::GeometryError::NotConvex);
             }
         }
@@ -1410,6 +1503,13 @@ impl ToHitBox for CvxPoly {
     }
 }
 
+impl Support for CvxPoly {
+    #[inline]
+    fn support(&self, dir: math::Vec2) -> math::Vec2 {
+        support_verts(&self.verts, dir)
+    }
+}
+
 impl SatCompatible for CvxPoly {
     #[inline]
     fn sides_number(&self) -> usize {
@@ -1447,7 +1547,7 @@ impl SatCompatible for CvxPoly {
     }
 
     #[inline]
-    fn split_cave(&self) -> Result<Option<&[CvxPoly]>, base::GeometryError> {
+    fn split_cave(&self) -> Result<Option<CaveParts<'_>>, base::GeometryError> {
         Ok(None)
     }
 }
@@ -1577,10 +1677,13 @@ impl fmt::Display for CvxPoly {
     }
 }
 
-#[derive(Debug, Default)]
+/// the decomposition is computed once in local space and then carried along by clones and transformations, so that global
+/// shapes never need to be decomposed again; hitboxes are recomputed from the transformed pieces since rotations change them
+#[derive(Debug, Default, Clone)]
 struct CaveCache {
     triangles: OnceCell<Vec<Triangle>>,
     cvx_polys: OnceCell<Vec<CvxPoly>>,
+    hitboxes: OnceCell<Vec<HitBox>>,
 }
 
 impl CaveCache {
@@ -1589,6 +1692,7 @@ impl CaveCache {
         Self {
             triangles: OnceCell::new(),
             cvx_polys: OnceCell::new(),
+            hitboxes: OnceCell::new(),
         }
     }
 
@@ -1607,6 +1711,11 @@ impl CaveCache {
         self.cvx_polys.get().map(Vec::as_slice)
     }
 
+    #[inline]
+    fn hitboxes(&self) -> Option<&[HitBox]> {
+        self.hitboxes.get().map(Vec::as_slice)
+    }
+
     #[inline]
     fn apply_transformation<T, U>(&self, triangles_transformation: T, cvx_polys_transformation: U) -> Self
     where
@@ -1626,17 +1735,11 @@ impl CaveCache {
         Self {
             triangles: new_triangles,
             cvx_polys: new_cvx_polys,
+            hitboxes: OnceCell::new(),
         }
     }
 }
 
-impl Clone for CaveCache {
-    #[inline]
-    fn clone(&self) -> Self {
-        Self::new()
-    }
-}
-
 impl PartialEq for CaveCache {
     #[inline]
     fn eq(&self, _other: &Self) -> bool {
@@ -1660,11 +1763,290 @@ impl<Context> Decode<Context> for CaveCache {
 
 bincode::impl_borrow_decode!(CaveCache);
 
-/// cave_poly must not self intersect, vertices must be stored counterclockwise, and there must be no collinear or duplicate vertices
+const BAYAZIT_MAX_DEPTH: usize = 100; // maximum number of nested cuts before bayazit_split() gives up
+
+/// iterates over the edges of a closed ring of vertices
+#[inline]
+pub(crate) fn ring_edges(verts: &[math::Vec2]) -> impl Iterator<Item = (math::Vec2, math::Vec2)> + '_ {
+    (0..verts.len()).map(move |idx| (verts[idx], verts[(idx + 1) % verts.len()]))
+}
+
+/// sum of the cross products of the edges of a ring, negative when the ring is counterclockwise
+#[inline]
+pub(crate) fn ring_cross_sum(verts: &[math::Vec2]) -> f32 {
+    ring_edges(verts).map(|(v0, v1)| v0.cross(v1)).sum()
+}
+
+/// checks that a ring has enough vertices and no collinear ones, then returns ring_cross_sum()
+fn check_ring(verts: &[math::Vec2]) -> Result<f32, base::GeometryError> {
+    let verts_len = verts.len();
+
+    if verts_len < 3 {
+        return Err(base::GeometryError::TooFewVertices(verts_len));
+    }
+
+    for i in 0..verts_len {
+        let v0 = verts[i];
+        let v1 = verts[(i + 1) % verts_len];
+        let v2 = verts[(i + 2) % verts_len];
+
+        if math::orientation(v0, v1, v2) == math::Orientation::Collinear {
+            return Err(base::GeometryError::CollinearVertices);
+        }
+    }
+
+    let signed_area_sum = ring_cross_sum(verts);
+
+    if signed_area_sum.abs() < math::EPS {
+        return Err(base::GeometryError::CollinearVertices);
+    }
+
+    Ok(signed_area_sum)
+}
+
+/// checks if 2 non adjacent edges of a ring intersect
+fn ring_self_intersects(verts: &[math::Vec2]) -> bool {
+    let verts_len = verts.len();
+
+    for i in 0..verts_len {
+        let a1 = verts[i];
+        let a2 = verts[(i + 1) % verts_len];
+
+        for j in (i + 2)..verts_len {
+            // skip adjacent edges, that can only happen between first and last edges
+            if i == 0 && j == verts_len - 1 {
+                continue;
+            }
+
+            let b1 = verts[j];
+            let b2 = verts[(j + 1) % verts_len];
+
+            if math::check_segments_intersection(a1, a2, b1, b2) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// checks if any edge of ring_1 intersects any edge of ring_2
+#[inline]
+fn rings_intersect(ring_1: &[math::Vec2], ring_2: &[math::Vec2]) -> bool {
+    ring_edges(ring_1).any(|(a1, a2)| ring_edges(ring_2).any(|(b1, b2)| math::check_segments_intersection(a1, a2, b1, b2)))
+}
+
+/// crossing number test, works for both convex and concave rings
+pub(crate) fn ring_contains(verts: &[math::Vec2], point: math::Vec2) -> bool {
+    let mut inside = false;
+
+    for (a, b) in ring_edges(verts) {
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y);
+
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+/// checks if point is inside the angle that a counterclockwise ring makes at vert, on the side of the inside of the ring;
+/// prev and next are the vertices before and after vert
+#[inline]
+fn in_cone(prev: math::Vec2, vert: math::Vec2, next: math::Vec2, point: math::Vec2) -> bool {
+    let inside_prev = math::orientation(prev, vert, point) == math::Orientation::CounterClockWise;
+    let inside_next = math::orientation(vert, next, point) == math::Orientation::CounterClockWise;
+
+    if math::orientation(prev, vert, next) == math::Orientation::CounterClockWise {
+        // convex vertex, the point must be inside both edges
+        inside_prev && inside_next
+    } else {
+        // reflex vertex, the point must be inside at least one edge
+        inside_prev || inside_next
+    }
+}
+
+/// intersection of the line through p1 and p2 with the line through q1 and q2, None if they are parallel
+#[inline]
+fn lines_intersection(p1: math::Vec2, p2: math::Vec2, q1: math::Vec2, q2: math::Vec2) -> Option<math::Vec2> {
+    let dir_p = p2.sub(p1);
+    let dir_q = q2.sub(q1);
+    let denominator = dir_p.cross(dir_q);
+
+    if denominator.abs() < math::EPS {
+        return None;
+    }
+
+    let t = q1.sub(p1).cross(dir_q) / denominator;
+
+    Some(p1.add(dir_p.scale(t)))
+}
+
+/// recursive step of bayazit's decomposition, verts must have a positive signed area (clockwise for this crate), so the
+/// pieces pushed are clockwise as well; returns false if the polygon is too degenerate to be split
+fn bayazit_split(verts: &[math::Vec2], depth: usize, pieces: &mut Vec<Vec<math::Vec2>>) -> bool {
+    let len = verts.len();
+
+    if depth > BAYAZIT_MAX_DEPTH || len < 3 {
+        return false;
+    }
+
+    // diagonal from verts[from] to verts[to] that does not cross any edge
+    let can_see = |from: usize, to: usize| {
+        (0..len).all(|idx| {
+            let next_idx = (idx + 1) % len;
+            idx == from
+                || idx == to
+                || next_idx == from
+                || next_idx == to
+                || !math::check_segments_intersection(verts[from], verts[to], verts[idx], verts[next_idx])
+        })
+    };
+
+    for i in 0..len {
+        let prev = verts[(i + len - 1) % len];
+        let vert = verts[i];
+        let next = verts[(i + 1) % len];
+
+        // only reflex vertices need a cut
+        if math::orientation(prev, vert, next) != math::Orientation::CounterClockWise {
+            continue;
+        }
+
+        // (square distance, intersection, index of the edge end) of the closest edges hit by the extensions of the edges of vert
+        let mut lower = (f32::INFINITY, math::Vec2::ZERO, 0);
+        let mut upper = (f32::INFINITY, math::Vec2::ZERO, 0);
+
+        for j in 0..len {
+            let prev_j = verts[(j + len - 1) % len];
+            let vert_j = verts[j];
+            let next_j = verts[(j + 1) % len];
+
+            // extend the edge entering vert until it hits the edge from prev_j to vert_j
+            if math::orientation(prev, vert, vert_j) == math::Orientation::ClockWise
+                && math::orientation(prev, vert, prev_j) != math::Orientation::ClockWise
+                && let Some(point) = lines_intersection(prev, vert, vert_j, prev_j)
+                && math::orientation(next, vert, point) == math::Orientation::CounterClockWise
+            {
+                let dist = vert.square_dist(point);
+                if dist < lower.0 {
+                    lower = (dist, point, j);
+                }
+            }
+
+            // extend the edge leaving vert until it hits the edge from vert_j to next_j
+            if math::orientation(next, vert, next_j) == math::Orientation::ClockWise
+                && math::orientation(next, vert, vert_j) != math::Orientation::ClockWise
+                && let Some(point) = lines_intersection(next, vert, vert_j, next_j)
+                && math::orientation(prev, vert, point) == math::Orientation::ClockWise
+            {
+                let dist = vert.square_dist(point);
+                if dist < upper.0 {
+                    upper = (dist, point, j);
+                }
+            }
+        }
+
+        if !lower.0.is_finite() || !upper.0.is_finite() {
+            return false;
+        }
+
+        let (lower_idx, upper_idx) = (lower.2, upper.2);
+        let mut lower_piece = Vec::with_capacity(len);
+        let mut upper_piece = Vec::with_capacity(len);
+
+        if lower_idx == (upper_idx + 1) % len {
+            // both extensions hit the same edge, cut towards a new point between the two intersections
+            let point = lower.1.midpoint(upper.1);
+
+            if i < upper_idx {
+                lower_piece.extend_from_slice(&verts[i..=upper_idx]);
+                lower_piece.push(point);
+                upper_piece.push(point);
+                if lower_idx != 0 {
+                    upper_piece.extend_from_slice(&verts[lower_idx..]);
+                }
+                upper_piece.extend_from_slice(&verts[..=i]);
+            } else {
+                if lower_idx > i {
+                    return false;
+                }
+                if i != 0 {
+                    lower_piece.extend_from_slice(&verts[i..]);
+                }
+                lower_piece.extend_from_slice(&verts[..=upper_idx]);
+                lower_piece.push(point);
+                upper_piece.push(point);
+                upper_piece.extend_from_slice(&verts[lower_idx..=i]);
+            }
+        } else {
+            // cut towards the closest visible vertex between the two hit edges
+            let upper_end = if lower_idx > upper_idx { upper_idx + len } else { upper_idx };
+            let mut closest: Option<(f32, usize)> = None;
+
+            for j in lower_idx..=upper_end {
+                let idx = j % len;
+
+                if idx == i || idx == (i + 1) % len || idx == (i + len - 1) % len {
+                    continue;
+                }
+
+                let vert_j = verts[idx];
+
+                if math::orientation(prev, vert, vert_j) != math::Orientation::CounterClockWise
+                    && math::orientation(next, vert, vert_j) != math::Orientation::ClockWise
+                {
+                    let dist = vert.square_dist(vert_j);
+
+                    if closest.is_none_or(|(closest_dist, _)| dist < closest_dist) && can_see(i, idx) {
+                        closest = Some((dist, idx));
+                    }
+                }
+            }
+
+            let Some((_, closest_idx)) = closest else {
+                return false;
+            };
+
+            if i < closest_idx {
+                lower_piece.extend_from_slice(&verts[i..=closest_idx]);
+                upper_piece.extend_from_slice(&verts[closest_idx..]);
+                upper_piece.extend_from_slice(&verts[..=i]);
+            } else {
+                if i != 0 {
+                    lower_piece.extend_from_slice(&verts[i..]);
+                }
+                lower_piece.extend_from_slice(&verts[..=closest_idx]);
+                upper_piece.extend_from_slice(&verts[closest_idx..=i]);
+            }
+        }
+
+        // split the smallest piece first
+        let (first, second) = if lower_piece.len() < upper_piece.len() {
+            (lower_piece, upper_piece)
+        } else {
+            (upper_piece, lower_piece)
+        };
+
+        return bayazit_split(&first, depth + 1, pieces) && bayazit_split(&second, depth + 1, pieces);
+    }
+
+    // no reflex vertices, the piece is already convex
+    pieces.push(verts.to_vec());
+    true
+}
+
+/// cave_poly must not self intersect, vertices must be stored counterclockwise, and there must be no collinear or duplicate vertices;
+/// holes follow the same rules but are stored clockwise, they must lie inside the polygon and must not touch it or each other
 /// notice that vertices are local positions, you may need to manually integrate them with a position
 #[derive(Debug, Clone, PartialEq, Encode, Decode, Deserialize)]
 pub struct CavePoly {
     pub(crate) verts: Vec<math::Vec2>,
+    #[serde(default)]
+    pub(crate) holes: Vec<Vec<math::Vec2>>,
     #[serde(skip)]
     cache: CaveCache,
 }
@@ -1672,8 +2054,19 @@ pub struct CavePoly {
 impl CavePoly {
     #[inline]
     pub fn new_checked(verts: Vec<math::Vec2>, warnings: bool) -> Result<Self, base::GeometryError> {
+        Self::with_holes_checked(verts, Vec::new(), warnings)
+    }
+
+    #[inline]
+    pub const fn new_unchecked(verts: Vec<math::Vec2>) -> Self {
+        Self::with_holes_unchecked(verts, Vec::new())
+    }
+
+    #[inline]
+    pub fn with_holes_checked(verts: Vec<math::Vec2>, holes: Vec<Vec<math::Vec2>>, warnings: bool) -> Result<Self, base::GeometryError> {
         let cave_poly = Self {
             verts,
+            holes,
             cache: CaveCache::new(),
         };
 
@@ -1683,9 +2076,10 @@ impl CavePoly {
     }
 
     #[inline]
-    pub const fn new_unchecked(verts: Vec<math::Vec2>) -> Self {
+    pub const fn with_holes_unchecked(verts: Vec<math::Vec2>, holes: Vec<Vec<math::Vec2>>) -> Self {
         Self {
             verts,
+            holes,
             cache: CaveCache::new(),
         }
     }
@@ -1695,9 +2089,14 @@ impl CavePoly {
         &self.verts
     }
 
+    #[inline]
+    pub fn holes(&self) -> &[Vec<math::Vec2>] {
+        &self.holes
+    }
+
     #[inline]
     pub fn set_verts(&mut self, new_verts: Vec<math::Vec2>, warnings: bool) -> Result<(), base::GeometryError> {
-        let new_poly = Self::new_checked(new_verts, warnings)?;
+        let new_poly = Self::with_holes_checked(new_verts, self.holes.clone(), warnings)?;
 
         self.verts = new_poly.verts;
         self.cache = CaveCache::new();
@@ -1711,6 +2110,64 @@ impl CavePoly {
         self.cache = CaveCache::new();
     }
 
+    #[inline]
+    pub fn set_holes(&mut self, new_holes: Vec<Vec<math::Vec2>>, warnings: bool) -> Result<(), base::GeometryError> {
+        let new_poly = Self::with_holes_checked(self.verts.clone(), new_holes, warnings)?;
+
+        self.holes = new_poly.holes;
+        self.cache = CaveCache::new();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_holes_unchecked(&mut self, new_holes: Vec<Vec<math::Vec2>>) {
+        self.holes = new_holes;
+        self.cache = CaveCache::new();
+    }
+
+    /// simplified copy of the polygon, see math::simplify_ring(), holes that collapse are removed
+    #[inline]
+    pub fn simplified(&self, tolerance: f32) -> Result<Self, base::GeometryError> {
+        self.map_rings(|ring| math::simplified_verts(ring, tolerance))
+    }
+
+    /// welded copy of the polygon, see math::weld_ring(), holes that collapse are removed
+    #[inline]
+    pub fn welded(&self, distance: f32) -> Result<Self, base::GeometryError> {
+        self.map_rings(|ring| math::welded_verts(ring, distance))
+    }
+
+    /// smoothed copy of the polygon, see math::smooth_ring()
+    #[inline]
+    pub fn smoothed(&self, iterations: usize) -> Result<Self, base::GeometryError> {
+        self.map_rings(|ring| math::smoothed_verts(ring, iterations))
+    }
+
+    fn map_rings<F>(&self, mut f: F) -> Result<Self, base::GeometryError>
+    where
+        F: FnMut(&[math::Vec2]) -> Vec<math::Vec2>,
+    {
+        let mut verts = f(&self.verts);
+
+        if !math::clean_ring(&mut verts) {
+            return Err(base::GeometryError::TooFewVertices(verts.len()));
+        }
+
+        let holes = self
+            .holes
+            .iter()
+            .map(|hole| f(hole))
+            .filter_map(|mut hole| math::clean_ring(&mut hole).then_some(hole))
+            .collect();
+
+        let mut cave_poly = Self::with_holes_unchecked(verts, holes);
+        cave_poly.normalize()?;
+        cave_poly.validate(false)?;
+
+        Ok(cave_poly)
+    }
+
     #[inline]
     pub fn clear_cache(&mut self) {
         self.cache.clear();
@@ -1726,6 +2183,7 @@ impl CavePoly {
     pub fn populate_cache(&self) -> Result<(), base::GeometryError> {
         self.triangles()?;
         self.cvx_polys()?;
+        self.cvx_hitboxes()?;
         Ok(())
     }
 
@@ -1755,14 +2213,100 @@ impl CavePoly {
         Ok(self.cache.cvx_polys().unwrap())
     }
 
+    /// hitboxes of the convex pieces returned by cvx_polys(), in the same order
+    #[inline]
+    pub fn cvx_hitboxes(&self) -> Result<&[HitBox], base::GeometryError> {
+        if let Some(hitboxes) = self.cache.hitboxes() {
+            return Ok(hitboxes);
+        }
+
+        let hitboxes = self.cvx_polys()?.iter().map(CvxPoly::to_hitbox).collect();
+
+        let _ = self.cache.hitboxes.set(hitboxes);
+
+        Ok(self.cache.hitboxes().unwrap())
+    }
+
+    /// joins every hole to the polygon through a bridge, a pair of overlapping edges going to the hole and back, so that
+    /// the polygon becomes a single ring; holes are joined from right to left, each one to the closest visible vertex
+    fn bridge_holes(&self) -> Result<Vec<math::Vec2>, base::GeometryError> {
+        let mut ring = self.verts.clone();
+
+        let max_x = |hole: &Vec<math::Vec2>| hole.iter().fold(f32::NEG_INFINITY, |max_x, vert| max_x.max(vert.x));
+        let mut holes: Vec<&Vec<math::Vec2>> = self.holes.iter().collect();
+        holes.sort_by(|hole_1, hole_2| max_x(hole_2).total_cmp(&max_x(hole_1)));
+
+        for (hole_idx, hole) in holes.iter().enumerate() {
+            let hole_len = hole.len();
+
+            if hole_len < 3 {
+                return Err(base::GeometryError::TooFewVertices(hole_len));
+            }
+
+            // the rightmost vertex of the hole is the start of the bridge
+            let mut start_idx = 0;
+            for (idx, vert) in hole.iter().enumerate() {
+                if vert.x > hole[start_idx].x {
+                    start_idx = idx;
+                }
+            }
+
+            let start = hole[start_idx];
+            let start_prev = hole[(start_idx + hole_len - 1) % hole_len];
+            let start_next = hole[(start_idx + 1) % hole_len];
+
+            let ring_len = ring.len();
+            let mut end: Option<(usize, f32)> = None;
+
+            for idx in 0..ring_len {
+                let vert = ring[idx];
+                let dist = vert.square_dist(start);
+
+                if end.is_some_and(|(_, end_dist)| dist >= end_dist) {
+                    continue;
+                }
+
+                // the bridge must leave both vertices towards the inside of the polygon, a vertex already used by a bridge
+                // appears twice in the ring and this also picks the right copy
+                if !in_cone(ring[(idx + ring_len - 1) % ring_len], vert, ring[(idx + 1) % ring_len], start)
+                    || !in_cone(start_prev, start, start_next, vert)
+                {
+                    continue;
+                }
+
+                // the bridge must not cross the ring built so far nor the holes still to join
+                let crosses = |verts: &[math::Vec2]| ring_edges(verts).any(|(a, b)| math::check_segments_intersection(start, vert, a, b));
+
+                if crosses(&ring) || holes[hole_idx..].iter().any(|hole| crosses(hole)) {
+                    continue;
+                }
+
+                end = Some((idx, dist));
+            }
+
+            let Some((end_idx, _)) = end else {
+                return Err(base::GeometryError::InvalidShape);
+            };
+
+            let mut bridge = Vec::with_capacity(hole_len + 2);
+            bridge.extend_from_slice(&hole[start_idx..]);
+            bridge.extend_from_slice(&hole[..=start_idx]);
+            bridge.push(ring[end_idx]);
+
+            ring.splice((end_idx + 1)..(end_idx + 1), bridge);
+        }
+
+        Ok(ring)
+    }
+
     fn triangulate(&self) -> Result<Vec<Triangle>, base::GeometryError> {
-        // ear clipping algorithm
+        // ear clipping algorithm, holes are bridged to the polygon first
         if self.verts.len() < 3 {
             return Err(base::GeometryError::TooFewVertices(self.verts.len()));
         }
 
-        let mut triangles: Vec<math::Triangle> = Vec::with_capacity(self.verts.len() - 2);
-        let mut verts = self.verts.clone();
+        let mut verts = self.bridge_holes()?;
+        let mut triangles: Vec<math::Triangle> = Vec::with_capacity(verts.len() - 2);
 
         while verts.len() > 3 {
             let verts_len = verts.len();
@@ -1783,7 +2327,8 @@ impl CavePoly {
                 };
 
                 'loop_2: for j in 0..verts_len {
-                    if j == i || j == i1 || j == i2 {
+                    // skip the vertices of the triangle and their copies made by bridges
+                    if j == i || j == i1 || j == i2 || verts[j] == v0 || verts[j] == v1 || verts[j] == v2 {
                         continue 'loop_2;
                     }
 
@@ -1793,6 +2338,11 @@ impl CavePoly {
                     }
                 }
 
+                if !self.holes.is_empty() && ring_edges(&verts).any(|(a, b)| math::check_segments_intersection(v0, v2, a, b)) {
+                    // triangle is not an ear because it crosses a bridge
+                    continue 'loop_1;
+                }
+
                 // triangle is an ear
                 triangles.push(triangle);
                 verts.remove(i1);
@@ -1816,6 +2366,48 @@ impl CavePoly {
     }
 
     fn convex_decomp(&self) -> Result<Vec<CvxPoly>, base::GeometryError> {
+        // bayazit only works on simple polygons, polygons with holes, or the ones it fails on, merge their triangles instead
+        if self.holes.is_empty()
+            && let Some(cvx_polys) = self.bayazit_decomp()
+        {
+            return Ok(cvx_polys);
+        }
+
+        self.merge_triangles()
+    }
+
+    /// bayazit's decomposition cuts the polygon at its reflex vertices, preferably towards other vertices, until all the pieces
+    /// are convex; it usually gives far less pieces than merge_triangles(), returns None if it fails
+    fn bayazit_decomp(&self) -> Option<Vec<CvxPoly>> {
+        let mut verts = self.verts.clone();
+        verts.reverse();
+
+        let mut pieces = Vec::new();
+
+        if !bayazit_split(&verts, 0, &mut pieces) {
+            return None;
+        }
+
+        let mut pieces_cross_sum = 0.0;
+        let mut cvx_polys = Vec::with_capacity(pieces.len());
+
+        for mut piece in pieces {
+            piece.reverse();
+            pieces_cross_sum += ring_cross_sum(&piece);
+            cvx_polys.push(CvxPoly::new_checked(piece, false).ok()?);
+        }
+
+        // the pieces must cover the polygon exactly
+        let cross_sum = ring_cross_sum(&self.verts);
+        if (pieces_cross_sum - cross_sum).abs() > cross_sum.abs() * 1e-4 {
+            return None;
+        }
+
+        Some(cvx_polys)
+    }
+
+    /// hertel-mehlhorn decomposition, merges the triangles back together as long as the pieces stay convex
+    fn merge_triangles(&self) -> Result<Vec<CvxPoly>, base::GeometryError> {
         enum Fragment {
             Triangle([math::Vec2; 3]),
             CvxPoly(CvxPoly),
@@ -1877,7 +2469,7 @@ impl CavePoly {
                             let next_vert_a_2 = verts_2[(vert_a_idx_2 + 1) % verts_2_len]; // <- vertex after a on fragment 2
 
                             // test convexity on a
-                            if prev_vert_a_1.signed_area(vert_a_1, next_vert_a_2) >= -math::EPS {
+                            if math::orientation(prev_vert_a_1, vert_a_1, next_vert_a_2) != math::Orientation::CounterClockWise {
                                 continue 'loop_2;
                             }
 
@@ -1885,7 +2477,7 @@ impl CavePoly {
                             let next_vert_b_1 = verts_1[(vert_b_idx_1 + 1) % verts_1_len]; // <- vertex after b on fragment 1
 
                             // test convexity on b
-                            if prev_vert_b_2.signed_area(vert_b_1, next_vert_b_1) >= -math::EPS {
+                            if math::orientation(prev_vert_b_2, vert_b_1, next_vert_b_1) != math::Orientation::CounterClockWise {
                                 continue 'loop_2;
                             }
 
@@ -1950,7 +2542,7 @@ impl Validate for CavePoly {
 
         if verts_len < 3 {
             return Err(base::GeometryError::TooFewVertices(verts_len));
-        } else if warnings {
+        } else if warnings && self.holes.is_empty() {
             if verts_len == 3 {
                 eprintln!("warning: Shape::CavePoly with 3 vertices, consider Shape::Triangle for efficiency");
             } else if verts_len == 4 {
@@ -1958,59 +2550,48 @@ impl Validate for CavePoly {
             }
         }
 
-        // check duplicates vertices
-        for i in 0..verts_len {
-            for j in (i + 1)..verts_len {
-                if self.verts[i].square_dist(self.verts[j]) < math::EPS_SQR {
+        // check duplicates vertices, holes included
+        let all_verts: Vec<math::Vec2> = self.verts.iter().chain(self.holes.iter().flatten()).copied().collect();
+        for i in 0..all_verts.len() {
+            for j in (i + 1)..all_verts.len() {
+                if all_verts[i].square_dist(all_verts[j]) < math::EPS_SQR {
                     return Err(base::GeometryError::DuplicateVertices);
                 }
             }
         }
 
-        // check no collinear vertices
-        let mut signed_area_sum = 0.0;
-        for i in 0..verts_len {
-            let i1 = (i + 1) % verts_len;
-            let i2 = (i + 2) % verts_len;
-
-            let v0 = self.verts[i];
-            let v1 = self.verts[i1];
-            let v2 = self.verts[i2];
-
-            let area = self.verts[i].signed_area(v1, v2);
-            signed_area_sum += v0.cross(v1);
-
-            if area.abs() < math::EPS {
-                return Err(base::GeometryError::CollinearVertices);
-            }
+        // check no collinear vertices and counterclockwise
+        if check_ring(&self.verts)? >= math::EPS {
+            return Err(base::GeometryError::NotCounterClockWise);
         }
 
-        if signed_area_sum.abs() < math::EPS {
-            return Err(base::GeometryError::CollinearVertices);
+        // holes are clockwise instead
+        for hole in &self.holes {
+            if check_ring(hole)? <= -math::EPS {
+                return Err(base::GeometryError::HoleNotClockWise);
+            }
         }
 
-        // check counterclockwise
-        if signed_area_sum >= math::EPS {
-            return Err(base::GeometryError::NotCounterClockWise);
+        // check no self-intersecting edges, neither in a ring nor between rings
+        let rings: Vec<&[math::Vec2]> = std::iter::once(self.verts.as_slice())
+            .chain(self.holes.iter().map(Vec::as_slice))
+            .collect();
+        for (idx_1, ring_1) in rings.iter().enumerate() {
+            if ring_self_intersects(ring_1) || rings[(idx_1 + 1)..].iter().any(|ring_2| rings_intersect(ring_1, ring_2)) {
+                return Err(base::GeometryError::SelfIntersecting);
+            }
         }
 
-        // check no self-intersecting edges
-        for i in 0..verts_len {
-            let a1 = self.verts[i];
-            let a2 = self.verts[(i + 1) % verts_len];
-
-            for j in (i + 2)..verts_len {
-                // skip adjacent edges, that can only happen between first and last edges
-                if i == 0 && j == verts_len - 1 {
-                    continue;
-                }
-
-                let b1 = self.verts[j];
-                let b2 = self.verts[(j + 1) % verts_len];
-
-                if math::check_segments_intersection(a1, a2, b1, b2) {
-                    return Err(base::GeometryError::SelfIntersecting);
-                }
+        // since rings do not intersect, a hole is inside the polygon or another hole if any of its vertices is
+        for (idx_1, hole_1) in self.holes.iter().enumerate() {
+            if !ring_contains(&self.verts, hole_1[0])
+                || self
+                    .holes
+                    .iter()
+                    .enumerate()
+                    .any(|(idx_2, hole_2)| idx_1 != idx_2 && ring_contains(hole_2, hole_1[0]))
+            {
+                return Err(base::GeometryError::HoleOutside);
             }
         }
 
@@ -2019,39 +2600,20 @@ impl Validate for CavePoly {
 
     #[inline]
     fn normalize(&mut self) -> Result<(), base::GeometryError> {
-        let verts_len = self.verts.len();
-
-        if verts_len < 3 {
-            return Err(base::GeometryError::TooFewVertices(verts_len));
-        }
-
-        let mut signed_area_sum = 0.0;
-        for i in 0..verts_len {
-            let i1 = (i + 1) % verts_len;
-            let i2 = (i + 2) % verts_len;
-
-            let v0 = self.verts[i];
-            let v1 = self.verts[i1];
-            let v2 = self.verts[i2];
-
-            let area = self.verts[i].signed_area(v1, v2);
-            signed_area_sum += v0.cross(v1);
-
-            if area.abs() < math::EPS {
-                return Err(base::GeometryError::CollinearVertices);
-            }
-        }
-
-        if signed_area_sum.abs() < math::EPS {
-            return Err(base::GeometryError::CollinearVertices);
-        }
-
         // check counterclockwise
-        if signed_area_sum >= math::EPS {
+        if check_ring(&self.verts)? >= math::EPS {
             self.verts.reverse();
             self.clear_cache();
         }
 
+        // check clockwise holes
+        for hole in self.holes.iter_mut() {
+            if check_ring(hole)? <= -math::EPS {
+                hole.reverse();
+                self.cache.clear();
+            }
+        }
+
         Ok(())
     }
 }
@@ -2074,6 +2636,14 @@ impl ToHitBox for CavePoly {
     }
 }
 
+/// the support of a concave polygon is the one of its convex hull, use its convex parts to get exact results
+impl Support for CavePoly {
+    #[inline]
+    fn support(&self, dir: math::Vec2) -> math::Vec2 {
+        support_verts(&self.verts, dir)
+    }
+}
+
 impl SatCompatible for CavePoly {
     #[inline]
     fn sides_number(&self) -> usize {
@@ -2091,8 +2661,11 @@ impl SatCompatible for CavePoly {
     }
 
     #[inline]
-    fn split_cave(&self) -> Result<Option<&[CvxPoly]>, base::GeometryError> {
-        self.cvx_polys().map(Some)
+    fn split_cave(&self) -> Result<Option<CaveParts<'_>>, base::GeometryError> {
+        Ok(Some(CaveParts {
+            cvx_polys: self.cvx_polys()?,
+            hitboxes: self.cvx_hitboxes()?,
+        }))
     }
 }
 
@@ -2180,6 +2753,7 @@ impl ApplyTransformationShape for CavePoly {
     #[inline]
     fn apply_vec2_checked(&self, vec: math::Vec2) -> Result<Self, base::GeometryError> {
         let new_verts = self.verts().into_iter().map(|v| vec.add(*v)).collect();
+        let new_holes = self.holes.iter().map(|hole| hole.iter().map(|v| vec.add(*v)).collect()).collect();
 
         let new_cache = self
             .cache
@@ -2187,6 +2761,7 @@ impl ApplyTransformationShape for CavePoly {
 
         let new_cave_poly = Self {
             verts: new_verts,
+            holes: new_holes,
             cache: new_cache,
         };
 
@@ -2198,6 +2773,7 @@ impl ApplyTransformationShape for CavePoly {
     #[inline]
     fn apply_vec2_unchecked(&self, vec: math::Vec2) -> Self {
         let new_verts = self.verts().into_iter().map(|v| vec.add(*v)).collect();
+        let new_holes = self.holes.iter().map(|hole| hole.iter().map(|v| vec.add(*v)).collect()).collect();
 
         let new_cache = self
             .cache
@@ -2205,6 +2781,7 @@ impl ApplyTransformationShape for CavePoly {
 
         Self {
             verts: new_verts,
+            holes: new_holes,
             cache: new_cache,
         }
     }
@@ -2212,6 +2789,11 @@ impl ApplyTransformationShape for CavePoly {
     #[inline]
     fn apply_mat2x3_checked(&self, mat: &math::Mat2x3) -> Result<Self, base::GeometryError> {
         let new_verts = self.verts().into_iter().map(|v| mat.pre_mul_vec2(*v)).collect();
+        let new_holes = self
+            .holes
+            .iter()
+            .map(|hole| hole.iter().map(|v| mat.pre_mul_vec2(*v)).collect())
+            .collect();
 
         let new_cache = self
             .cache
@@ -2219,6 +2801,7 @@ impl ApplyTransformationShape for CavePoly {
 
         let new_cave_poly = Self {
             verts: new_verts,
+            holes: new_holes,
             cache: new_cache,
         };
 
@@ -2230,6 +2813,11 @@ impl ApplyTransformationShape for CavePoly {
     #[inline]
     fn apply_mat2x3_unchecked(&self, mat: &math::Mat2x3) -> Self {
         let new_verts = self.verts().into_iter().map(|v| mat.pre_mul_vec2(*v)).collect();
+        let new_holes = self
+            .holes
+            .iter()
+            .map(|hole| hole.iter().map(|v| mat.pre_mul_vec2(*v)).collect())
+            .collect();
 
         let new_cache = self
             .cache
@@ -2237,6 +2825,7 @@ impl ApplyTransformationShape for CavePoly {
 
         Self {
             verts: new_verts,
+            holes: new_holes,
             cache: new_cache,
         }
     }
@@ -2244,6 +2833,11 @@ impl ApplyTransformationShape for CavePoly {
     #[inline]
     fn apply_mat2x3_then_vec2_checked(&self, vec: math::Vec2, mat: &math::Mat2x3) -> Result<Self, base::GeometryError> {
         let new_verts = self.verts().into_iter().map(|v| mat.pre_mul_vec2(*v).add(vec)).collect();
+        let new_holes = self
+            .holes
+            .iter()
+            .map(|hole| hole.iter().map(|v| mat.pre_mul_vec2(*v).add(vec)).collect())
+            .collect();
 
         let new_cache = self.cache.apply_transformation(
             |t| t.apply_mat2x3_then_vec2_unchecked(vec, mat),
@@ -2252,6 +2846,7 @@ impl ApplyTransformationShape for CavePoly {
 
         let new_cave_poly = Self {
             verts: new_verts,
+            holes: new_holes,
             cache: new_cache,
         };
 
@@ -2263,6 +2858,11 @@ impl ApplyTransformationShape for CavePoly {
     #[inline]
     fn apply_mat2x3_then_vec2_unchecked(&self, vec: math::Vec2, mat: &math::Mat2x3) -> Self {
         let new_verts = self.verts().into_iter().map(|v| mat.pre_mul_vec2(*v).add(vec)).collect();
+        let new_holes = self
+            .holes
+            .iter()
+            .map(|hole| hole.iter().map(|v| mat.pre_mul_vec2(*v).add(vec)).collect())
+            .collect();
 
         let new_cache = self.cache.apply_transformation(
             |t| t.apply_mat2x3_then_vec2_unchecked(vec, mat),
@@ -2271,6 +2871,7 @@ impl ApplyTransformationShape for CavePoly {
 
         Self {
             verts: new_verts,
+            holes: new_holes,
             cache: new_cache,
         }
     }
@@ -2285,32 +2886,46 @@ impl fmt::Display for CavePoly {
             }
             write!(f, "{}", vert)?;
         }
-        write!(f, ")")
+        write!(f, ")")?;
+        for hole in &self.holes {
+            write!(f, " with hole (")?;
+            for (i, vert) in hole.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", vert)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
     }
 }
 
+/// notice that center is a local position, you may need to manually integrate it with a position
 #[derive(Debug, Clone, PartialEq, Encode, Decode, Deserialize)]
 pub struct Circle {
+    pub(crate) center: math::Vec2,
     pub(crate) radius: f32,
 }
 
 impl Circle {
     #[inline]
-    pub fn new_checked(radius: f32) -> Result<Self, base::MathError> {
-        let circle = Self { radius };
+    pub fn new_checked(center: math::Vec2, radius: f32) -> Result<Self, base::GeometryError> {
+        let circle = Self { center, radius };
 
-        circle.validate()?;
+        circle.validate(true)?;
 
         Ok(circle)
     }
 
     #[inline]
-    pub fn validate(&self) -> Result<(), base::MathError> {
-        if self.radius <= 0.0 {
-            return Err(base::MathError::NonPositive("radius"));
-        }
+    pub const fn new_unchecked(center: math::Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
 
-        Ok(())
+    #[inline]
+    pub fn center(&self) -> math::Vec2 {
+        self.center
     }
 
     #[inline]
@@ -2318,22 +2933,138 @@ impl Circle {
         self.radius
     }
 
+    #[inline]
+    pub fn center_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.center
+    }
+
     #[inline]
     pub fn set_radius(&mut self, new_radius: f32) {
         self.radius = new_radius;
     }
+
+    /// scale factor of the radius under the linear part of mat, None if mat does not keep circles round
+    #[inline]
+    fn radius_scale(mat: &math::Mat2x3) -> Option<f32> {
+        let x_len = math::Vec2::new(mat.x.0, mat.x.1).mag();
+        let y_len = math::Vec2::new(mat.y.0, mat.y.1).mag();
+        let skew = mat.x.0 * mat.y.0 + mat.x.1 * mat.y.1;
+
+        // rotations and uniform scales only, compared relatively to the size of the axes
+        if (x_len - y_len).abs() > math::EPS * x_len.max(1.0) || skew.abs() > math::EPS * (x_len * y_len).max(1.0) {
+            return None;
+        }
+
+        Some(x_len)
+    }
+}
+
+impl Validate for Circle {
+    #[inline]
+    fn validate(&self, _warnings: bool) -> Result<(), base::GeometryError> {
+        if !self.radius.is_finite() || self.radius < math::EPS {
+            return Err(base::GeometryError::NonPositiveRadius);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn normalize(&mut self) -> Result<(), base::GeometryError> {
+        Ok(())
+    }
+}
+
+impl Centroid for Circle {
+    #[inline]
+    fn centroid(&self) -> math::Vec2 {
+        self.center
+    }
 }
 
 impl ToHitBox for Circle {
     #[inline]
     fn to_hitbox(&self) -> HitBox {
-        let diameter = self.radius * 2.0;
-        HitBox::new(0.0, 0.0, diameter, diameter)
+        HitBox::new(
+            self.center.x - self.radius,
+            self.center.y - self.radius,
+            self.center.x + self.radius,
+            self.center.y + self.radius,
+        )
+    }
+}
+
+impl Support for Circle {
+    #[inline]
+    fn support(&self, dir: math::Vec2) -> math::Vec2 {
+        if dir.approx_equal_zero() {
+            self.center
+        } else {
+            self.center.add(dir.norm().scale(self.radius))
+        }
+    }
+}
+
+impl SatCompatible for Circle {
+    /// a circle has no sides, so sat alone cannot separate it, see check_sat()
+    #[inline]
+    fn sides_number(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn append_sides(&self, _sides: &mut Vec<math::Vec2>) {}
+
+    #[inline]
+    fn project(&self, axis: math::Vec2) -> (f32, f32) {
+        let center_proj = self.center.dot(axis);
+        let radius_proj = self.radius * axis.mag();
+        (center_proj - radius_proj, center_proj + radius_proj)
+    }
+
+    #[inline]
+    fn split_cave(&self) -> Result<Option<CaveParts<'_>>, base::GeometryError> {
+        Ok(None)
+    }
+}
+
+impl ApplyTransformationShape for Circle {
+    #[inline]
+    fn apply_vec2_checked(&self, vec: math::Vec2) -> Result<Self, base::GeometryError> {
+        Self::new_checked(self.center.add(vec), self.radius)
+    }
+
+    #[inline]
+    fn apply_vec2_unchecked(&self, vec: math::Vec2) -> Self {
+        Self::new_unchecked(self.center.add(vec), self.radius)
+    }
+
+    #[inline]
+    fn apply_mat2x3_checked(&self, mat: &math::Mat2x3) -> Result<Self, base::GeometryError> {
+        let radius_scale = Self::radius_scale(mat).ok_or(base::GeometryError::InvalidScale)?;
+        Self::new_checked(mat.pre_mul_vec2(self.center), self.radius * radius_scale)
+    }
+
+    /// the radius is scaled by the square root of the determinant, which is exact for rotations and uniform scales
+    #[inline]
+    fn apply_mat2x3_unchecked(&self, mat: &math::Mat2x3) -> Self {
+        Self::new_unchecked(mat.pre_mul_vec2(self.center), self.radius * mat.determinant().abs().sqrt())
+    }
+
+    #[inline]
+    fn apply_mat2x3_then_vec2_checked(&self, vec: math::Vec2, mat: &math::Mat2x3) -> Result<Self, base::GeometryError> {
+        let radius_scale = Self::radius_scale(mat).ok_or(base::GeometryError::InvalidScale)?;
+        Self::new_checked(mat.pre_mul_vec2(self.center).add(vec), self.radius * radius_scale)
+    }
+
+    #[inline]
+    fn apply_mat2x3_then_vec2_unchecked(&self, vec: math::Vec2, mat: &math::Mat2x3) -> Self {
+        Self::new_unchecked(mat.pre_mul_vec2(self.center).add(vec), self.radius * mat.determinant().abs().sqrt())
     }
 }
 
 impl fmt::Display for Circle {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "circle ({:.4})", self.radius)
+        write!(f, "circle ({}, {:.4})", self.center, self.radius)
     }
 }
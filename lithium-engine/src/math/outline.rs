@@ -0,0 +1,250 @@
+use crate::{base, math};
+
+/// how offset() fills the gap at the corners where the offset edges part
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OffsetJoin {
+    Miter { limit: f32 },     // sharp corner, beveled when the miter is longer than limit times the distance
+    Round { tolerance: f32 }, // arc, flattened so that it is never further than tolerance from the exact one (at least a thousandth of the distance)
+    Bevel,                    // straight cut
+}
+
+impl Default for OffsetJoin {
+    #[inline]
+    fn default() -> Self {
+        Self::Miter { limit: 4.0 }
+    }
+}
+
+/// smallest tolerance of a round join relative to the offset distance, lower ones would give huge numbers of vertices
+const MIN_ROUND_TOLERANCE: f32 = 1e-3;
+
+/// distance of point from the segment from a to b
+#[inline]
+pub(crate) fn segment_dist(point: math::Vec2, a: math::Vec2, b: math::Vec2) -> f32 {
+    math::closest_on_segment(point, a, b).dist(point)
+}
+
+/// ramer-douglas-peucker on an open chain, pushes every kept vertex except the last one
+fn rdp(chain: &[math::Vec2], tolerance: f32, simplified: &mut Vec<math::Vec2>) {
+    let (first, last) = (chain[0], chain[chain.len() - 1]);
+
+    let farthest = chain[1..chain.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(idx, vert)| (idx + 1, segment_dist(*vert, first, last)))
+        .max_by(|(_, dist_1), (_, dist_2)| dist_1.total_cmp(dist_2));
+
+    match farthest {
+        Some((idx, dist)) if dist > tolerance => {
+            rdp(&chain[..=idx], tolerance, simplified);
+            rdp(&chain[idx..], tolerance, simplified);
+        }
+        _ => simplified.push(first),
+    }
+}
+
+/// removes the collinear vertices of a ring built by the functions below and turns it into a shape
+fn cleaned_shape(mut verts: Vec<math::Vec2>) -> Result<math::Shape, base::GeometryError> {
+    if !math::clean_ring(&mut verts) {
+        return Err(base::GeometryError::TooFewVertices(verts.len()));
+    }
+
+    math::ring_shape(verts, Vec::new())
+}
+
+/// simplifies a closed counterclockwise ring with ramer-douglas-peucker, removing the vertices closer than tolerance to
+/// the outline that is left; fails if nothing is left or if a very high tolerance made a concave ring self intersect
+#[inline]
+pub fn simplify_ring(verts: &[math::Vec2], tolerance: f32) -> Result<math::Shape, base::GeometryError> {
+    cleaned_shape(simplified_verts(verts, tolerance))
+}
+
+/// vertices of simplify_ring(), they keep the winding of the ring but can still be collinear
+pub(crate) fn simplified_verts(verts: &[math::Vec2], tolerance: f32) -> Vec<math::Vec2> {
+    if verts.len() < 3 {
+        return verts.to_vec();
+    }
+
+    // split the ring at the vertex farthest from the first one, both halves are then simplified as chains
+    let (split_idx, _) = verts
+        .iter()
+        .enumerate()
+        .map(|(idx, vert)| (idx, vert.square_dist(verts[0])))
+        .max_by(|(_, dist_1), (_, dist_2)| dist_1.total_cmp(dist_2))
+        .unwrap();
+
+    let mut closed = verts.to_vec();
+    closed.push(verts[0]);
+
+    let mut simplified = Vec::with_capacity(verts.len());
+    rdp(&closed[..=split_idx], tolerance, &mut simplified);
+    rdp(&closed[split_idx..], tolerance, &mut simplified);
+
+    simplified
+}
+
+/// merges the runs of consecutive vertices of a closed counterclockwise ring closer than distance into their average,
+/// then removes the collinear ones; fails if nothing is left or if the welded ring is not valid
+#[inline]
+pub fn weld_ring(verts: &[math::Vec2], distance: f32) -> Result<math::Shape, base::GeometryError> {
+    cleaned_shape(welded_verts(verts, distance))
+}
+
+/// vertices of weld_ring(), they keep the winding of the ring but can still be collinear
+pub(crate) fn welded_verts(verts: &[math::Vec2], distance: f32) -> Vec<math::Vec2> {
+    let square_dist = distance * distance;
+    let mut runs: Vec<(math::Vec2, usize)> = Vec::with_capacity(verts.len()); // (sum of the vertices, number of vertices)
+
+    for vert in verts {
+        match runs.last_mut() {
+            Some((sum, count)) if sum.scale(1.0 / *count as f32).square_dist(*vert) <= square_dist => {
+                sum.add_mut(*vert);
+                *count += 1;
+            }
+            _ => runs.push((*vert, 1)),
+        }
+    }
+
+    // the last run can continue into the first one
+    if runs.len() > 1 {
+        let (last_sum, last_count) = runs[runs.len() - 1];
+        let (first_sum, first_count) = runs[0];
+
+        if last_sum
+            .scale(1.0 / last_count as f32)
+            .square_dist(first_sum.scale(1.0 / first_count as f32))
+            <= square_dist
+        {
+            runs[0] = (first_sum.add(last_sum), first_count + last_count);
+            runs.pop();
+        }
+    }
+
+    runs.into_iter().map(|(sum, count)| sum.scale(1.0 / count as f32)).collect()
+}
+
+/// rounds the corners of a closed counterclockwise ring with chaikin's algorithm, every iteration replaces each edge
+/// with the points at a quarter and at three quarters of it, doubling the vertices; fails if the ring is not valid
+#[inline]
+pub fn smooth_ring(verts: &[math::Vec2], iterations: usize) -> Result<math::Shape, base::GeometryError> {
+    cleaned_shape(smoothed_verts(verts, iterations))
+}
+
+/// vertices of smooth_ring(), they keep the winding of the ring but can still be collinear
+pub(crate) fn smoothed_verts(verts: &[math::Vec2], iterations: usize) -> Vec<math::Vec2> {
+    let mut smoothed = verts.to_vec();
+
+    for _ in 0..iterations {
+        let mut next = Vec::with_capacity(smoothed.len() * 2);
+
+        for (a, b) in math::ring_edges(&smoothed) {
+            let edge = b.sub(a);
+            next.push(a.add(edge.scale(0.25)));
+            next.push(a.add(edge.scale(0.75)));
+        }
+
+        smoothed = next;
+    }
+
+    smoothed
+}
+
+/// outward normal of the edge from a to b of a counterclockwise ring
+#[inline]
+fn outward_normal(a: math::Vec2, b: math::Vec2) -> math::Vec2 {
+    let edge = b.sub(a);
+    math::Vec2::new(-edge.y, edge.x).norm()
+}
+
+/// shape of a convex piece used by offset(), None if it has no area
+fn piece_shape(mut verts: Vec<math::Vec2>) -> Option<math::Shape> {
+    if !math::clean_ring(&mut verts) {
+        return None;
+    }
+
+    if math::ring_cross_sum(&verts) > 0.0 {
+        verts.reverse();
+    }
+
+    math::ring_shape(verts, Vec::new()).ok()
+}
+
+/// pieces filling the gap between the offset edges entering and leaving vert, normal_1 and normal_2 are their normals
+/// already pointing in the direction of the offset
+fn join_piece(vert: math::Vec2, normal_1: math::Vec2, normal_2: math::Vec2, distance: f32, join: OffsetJoin) -> Option<math::Shape> {
+    let mut verts = vec![vert, vert.add(normal_1.scale(distance))];
+
+    match join {
+        OffsetJoin::Miter { limit } => {
+            let bisector = normal_1.add(normal_2).norm();
+            let cos_half = bisector.dot(normal_1);
+
+            if cos_half > math::EPS && 1.0 / cos_half <= limit {
+                verts.push(vert.add(bisector.scale(distance / cos_half)));
+            }
+        }
+        OffsetJoin::Round { tolerance } => {
+            let angle = math::atan2(normal_1.cross(normal_2), normal_1.dot(normal_2));
+            let tolerance = tolerance.max(MIN_ROUND_TOLERANCE * distance);
+            let max_step = 2.0 * math::acos(1.0 - (tolerance / distance).min(1.0));
+            let steps = (angle.abs() / max_step.max(math::EPS)).ceil() as usize;
+
+            for step in 1..steps {
+                let step_angle = angle * step as f32 / steps as f32;
+                let (sin, cos) = (math::sin(step_angle), math::cos(step_angle));
+                let normal = math::Vec2::new(normal_1.x * cos - normal_1.y * sin, normal_1.x * sin + normal_1.y * cos);
+                verts.push(vert.add(normal.scale(distance)));
+            }
+        }
+        OffsetJoin::Bevel => (),
+    }
+
+    verts.push(vert.add(normal_2.scale(distance)));
+
+    piece_shape(verts)
+}
+
+/// inflates the shape by distance, or deflates it when distance is negative, the corners are joined with join; the
+/// result is the union of the shape and the offset edges (or the shape minus them), so it can be made of several
+/// shapes, or none when a shape is deflated away; see union_all() and difference_all() for the supported shapes
+pub fn offset(shape: &math::Shape, distance: f32, join: OffsetJoin) -> Result<Vec<math::Shape>, base::GeometryError> {
+    let rings = math::rings(shape)?;
+
+    if distance.abs() < math::EPS {
+        return Ok(vec![shape.clone()]);
+    }
+
+    let inflate = distance > 0.0;
+    let distance = distance.abs();
+    let mut pieces = Vec::new();
+
+    for ring in &rings {
+        let len = ring.len();
+
+        for idx in 0..len {
+            let (prev, vert, next) = (ring[(idx + len - 1) % len], ring[idx], ring[(idx + 1) % len]);
+
+            let (mut normal_1, mut normal_2) = (outward_normal(prev, vert), outward_normal(vert, next));
+            if !inflate {
+                normal_1 = normal_1.rev();
+                normal_2 = normal_2.rev();
+            }
+
+            // the offset edges part at convex vertices when inflating and at reflex ones when deflating
+            let convex = prev.signed_area(vert, next) < 0.0;
+            if convex == inflate {
+                pieces.extend(join_piece(vert, normal_1, normal_2, distance, join));
+            }
+
+            let offset = normal_2.scale(distance);
+            pieces.extend(piece_shape(vec![vert, next, next.add(offset), vert.add(offset)]));
+        }
+    }
+
+    if inflate {
+        pieces.push(shape.clone());
+        math::union_all(&pieces)
+    } else {
+        math::difference_all(shape, &pieces)
+    }
+}
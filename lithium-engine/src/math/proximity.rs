@@ -0,0 +1,130 @@
+use crate::math;
+
+/// a shape moved to world space and reduced to what distance queries need
+enum Primitive {
+    Segment(math::Vec2, math::Vec2),
+    Rings(Vec<Vec<math::Vec2>>), // outline and holes
+    Circle(math::Vec2, f32),     // center and radius
+}
+
+impl Primitive {
+    fn new(shape: &math::Shape, transform: &math::Transform2D) -> Self {
+        match shape {
+            math::Shape::Segment(segment) => Self::Segment(transform.to_world(segment.a), transform.to_world(segment.b)),
+            math::Shape::Circle(circle) => Self::Circle(transform.to_world(circle.center), circle.radius),
+            _ => Self::Rings(
+                math::rings(shape)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|ring| ring.into_iter().map(|vert| transform.to_world(vert)).collect())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// edges of the boundary, circles have none
+    fn edges(&self) -> Vec<(math::Vec2, math::Vec2)> {
+        match self {
+            Self::Segment(a, b) => vec![(*a, *b)],
+            Self::Rings(rings) => rings.iter().flat_map(|ring| math::ring_edges(ring)).collect(),
+            Self::Circle(..) => Vec::new(),
+        }
+    }
+
+    fn contains(&self, point: math::Vec2) -> bool {
+        match self {
+            Self::Segment(a, b) => closest_on_segment(point, *a, *b).square_dist(point) < math::EPS_SQR,
+            Self::Rings(rings) => math::rings_contain(rings, point),
+            Self::Circle(center, radius) => center.square_dist(point) <= math::pow2(*radius),
+        }
+    }
+
+    fn closest(&self, point: math::Vec2) -> math::Vec2 {
+        match self {
+            _ if self.contains(point) => point,
+            Self::Circle(center, radius) => center.add(point.sub(*center).norm().scale(*radius)),
+            _ => self
+                .edges()
+                .into_iter()
+                .map(|(a, b)| closest_on_segment(point, a, b))
+                .min_by(|closest_1, closest_2| closest_1.square_dist(point).total_cmp(&closest_2.square_dist(point)))
+                .unwrap_or(point),
+        }
+    }
+
+    fn distance(&self, primitive: &Self) -> f32 {
+        match (self, primitive) {
+            (Self::Circle(center_1, radius_1), Self::Circle(center_2, radius_2)) => {
+                (center_1.dist(*center_2) - radius_1 - radius_2).max(0.0)
+            }
+            (Self::Circle(center, radius), other) | (other, Self::Circle(center, radius)) => {
+                (other.closest(*center).dist(*center) - radius).max(0.0)
+            }
+            _ => {
+                let (edges_1, edges_2) = (self.edges(), primitive.edges());
+
+                // a shape completely inside the other one has no crossing edges
+                if edges_1.iter().any(|&(a, _)| primitive.contains(a)) || edges_2.iter().any(|&(a, _)| self.contains(a)) {
+                    return 0.0;
+                }
+
+                edges_1
+                    .iter()
+                    .flat_map(|&(a_1, b_1)| edges_2.iter().map(move |&(a_2, b_2)| segments_dist(a_1, b_1, a_2, b_2)))
+                    .fold(f32::INFINITY, f32::min)
+            }
+        }
+    }
+}
+
+/// point of the segment from a to b that is closest to point
+#[inline]
+pub fn closest_on_segment(point: math::Vec2, a: math::Vec2, b: math::Vec2) -> math::Vec2 {
+    let edge = b.sub(a);
+    let square_len = edge.square_mag();
+
+    if square_len < math::EPS_SQR {
+        return a;
+    }
+
+    let t = (point.sub(a).dot(edge) / square_len).clamp(0.0, 1.0);
+
+    a.add(edge.scale(t))
+}
+
+/// distance between the segments from a_1 to b_1 and from a_2 to b_2
+#[inline]
+pub fn segments_dist(a_1: math::Vec2, b_1: math::Vec2, a_2: math::Vec2, b_2: math::Vec2) -> f32 {
+    if math::check_segments_intersection(a_1, b_1, a_2, b_2) {
+        return 0.0;
+    }
+
+    // without crossings the closest pair always involves an end point
+    [
+        closest_on_segment(a_1, a_2, b_2).dist(a_1),
+        closest_on_segment(b_1, a_2, b_2).dist(b_1),
+        closest_on_segment(a_2, a_1, b_1).dist(a_2),
+        closest_on_segment(b_2, a_1, b_1).dist(b_2),
+    ]
+    .into_iter()
+    .fold(f32::INFINITY, f32::min)
+}
+
+/// checks if a point in world space is inside a shape placed by transform, holes are outside and segments only contain
+/// the points lying on them
+#[inline]
+pub fn contains_point(shape: &math::Shape, transform: &math::Transform2D, point: math::Vec2) -> bool {
+    Primitive::new(shape, transform).contains(point)
+}
+
+/// point of a shape placed by transform that is closest to a point in world space, the point itself if it is inside
+#[inline]
+pub fn closest_point(shape: &math::Shape, transform: &math::Transform2D, point: math::Vec2) -> math::Vec2 {
+    Primitive::new(shape, transform).closest(point)
+}
+
+/// distance between two shapes placed by their transforms, 0 if they touch or overlap
+#[inline]
+pub fn distance(shape_1: &math::Shape, transform_1: &math::Transform2D, shape_2: &math::Shape, transform_2: &math::Transform2D) -> f32 {
+    Primitive::new(shape_1, transform_1).distance(&Primitive::new(shape_2, transform_2))
+}
@@ -1,9 +1,11 @@
+pub mod checksum;
 pub mod client;
 pub mod packets;
 pub mod server;
 pub mod shared;
 pub mod snapshots;
 
+pub use checksum::*;
 pub use client::*;
 pub use packets::*;
 pub use server::*;
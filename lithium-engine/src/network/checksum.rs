@@ -0,0 +1,65 @@
+use crate::{base, ecs};
+
+use bincode::{enc::write::Writer, error::EncodeError};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// 64 bit FNV-1a hash, unlike std's hashers its output is stable across platforms, runs and Rust versions
+struct Fnv1a(u64);
+
+impl Writer for Fnv1a {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! hash_component {
+    ($world:expr, $hasher:expr, $component_field:ident) => {{
+        // hash the name of the set and its length, so that moving components between sets changes the checksum
+        $hasher.write(stringify!($component_field).as_bytes())?;
+        bincode::encode_into_writer(
+            $world.engine.$component_field.get_ents().len() as u64,
+            &mut $hasher,
+            bincode::config::standard(),
+        )?;
+
+        // entities are visited in order, so the checksum doesn't depend on the layout of the storage
+        let mut ents = $world.engine.$component_field.get_ents().to_vec();
+        ents.sort_unstable();
+
+        for entity in ents {
+            if let Some(component) = $world.engine.$component_field.get(entity) {
+                bincode::encode_into_writer((entity, component), &mut $hasher, bincode::config::standard())?;
+            }
+        }
+    }};
+}
+
+/// hash of the engine components of the world, two worlds with the same checksum are bitwise identical with a very high
+/// probability; compare the checksums computed by the server and the clients at the same tick to check that they
+/// simulate deterministically (see the deterministic feature), user components are not included
+pub fn checksum<const N: usize>(world: &ecs::World<N>) -> Result<u64, base::NetworkError> {
+    let mut hasher = Fnv1a(FNV_OFFSET_BASIS);
+
+    hash_component!(world, hasher, transform);
+    hash_component!(world, hasher, rotation_matrix);
+    hash_component!(world, hasher, translation);
+    hash_component!(world, hasher, rotation);
+    hash_component!(world, hasher, surface);
+    hash_component!(world, hasher, body);
+    hash_component!(world, hasher, material);
+    hash_component!(world, hasher, joint);
+    hash_component!(world, hasher, spring);
+    hash_component!(world, hasher, collision_filter);
+    hash_component!(world, hasher, force_field);
+    hash_component!(world, hasher, breakable);
+
+    Ok(hasher.0)
+}
@@ -192,12 +192,16 @@ where
     }
 }
 
+/// number of checksums kept by ClientSession, older ones are dropped
+const MAX_CHECKSUMS: usize = 256;
+
 pub struct ClientSession<I: PartialEq> {
     pub assigned_entity: ecs::Entity,                        // entity assigned by server for this client
     pub input_map: network::InputMap<I>,                     // stores all the recorded inputs for each tick
     pub last_sent_tick: base::Tick,                          // tick of last input sent, to check against ack_tick
     pub last_received_snapshot: (base::Tick, ecs::World<0>), // cache of the last snapshot received from server (todo: use const generic N instead of 0)
     pub last_rewind_snapshot: (base::Tick, ecs::World<0>), // cache of the last snapshot use for rewind (todo: use const generic N instead of 0)
+    pub checksums: base::CappedVec<(base::Tick, u64)>,     // checksums of the world after each recorded tick
 }
 
 impl<I: PartialEq> ClientSession<I> {
@@ -209,6 +213,7 @@ impl<I: PartialEq> ClientSession<I> {
             last_sent_tick: 0,
             last_received_snapshot: (0, ecs::World::default()),
             last_rewind_snapshot: (0, ecs::World::default()),
+            checksums: base::CappedVec::new(MAX_CHECKSUMS),
         }
     }
 
@@ -223,6 +228,28 @@ impl<I: PartialEq> ClientSession<I> {
         self.input_map.record(tick, self.assigned_entity, input);
     }
 
+    /// stores the checksum of the world at the given tick, a tick simulated again after a rewind replaces the old value
+    #[inline]
+    pub fn record_checksum(&mut self, world: &ecs::World<0>, tick: base::Tick) -> Result<(), base::NetworkError> {
+        let checksum = network::checksum(world)?;
+
+        match self.checksums.iter_mut().find(|(t, _)| *t == tick) {
+            Some((_, old_checksum)) => *old_checksum = checksum,
+            None => self.checksums.push_back((tick, checksum)),
+        }
+
+        Ok(())
+    }
+
+    /// compares the checksum sent by the server with the recorded one, None if the tick hasn't been recorded
+    #[inline]
+    pub fn verify_checksum(&self, tick: base::Tick, checksum: u64) -> Option<bool> {
+        self.checksums
+            .iter()
+            .find(|(t, _)| *t == tick)
+            .map(|(_, recorded)| *recorded == checksum)
+    }
+
     #[inline]
     pub fn apply_initial_state(&mut self, world: &mut ecs::World<0>, snapshot: network::Snapshot) {
         let network::Snapshot {
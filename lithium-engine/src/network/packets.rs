@@ -11,6 +11,7 @@ pub enum ServerPacket<S, I> {
     InitialState(Snapshot),
     DeltaState { snapshot: Snapshot, ack_tick: base::Tick },
     InputState(network::InputMap<I>),
+    Checksum { tick: base::Tick, checksum: u64 },
     User(S),
 }
 
@@ -95,6 +96,11 @@ pub enum EmptyNetworkComponent {
     Surface,
     Body,
     Material,
+    Joint,
+    Spring,
+    CollisionFilter,
+    ForceField,
+    Breakable,
 }
 
 impl EmptyNetworkComponent {
@@ -107,6 +113,11 @@ impl EmptyNetworkComponent {
             Self::Surface => _ = world.engine.surface.remove(entity),
             Self::Body => _ = world.engine.body.remove(entity),
             Self::Material => _ = world.engine.material.remove(entity),
+            Self::Joint => _ = world.engine.joint.remove(entity),
+            Self::Spring => _ = world.engine.spring.remove(entity),
+            Self::CollisionFilter => _ = world.engine.collision_filter.remove(entity),
+            Self::ForceField => _ = world.engine.force_field.remove(entity),
+            Self::Breakable => _ = world.engine.breakable.remove(entity),
         }
     }
 }
@@ -120,6 +131,11 @@ pub enum DataNetworkComponent {
     Surface(ecs::Surface),
     Body(ecs::Body),
     Material(ecs::Material),
+    Joint(ecs::Joint),
+    Spring(ecs::Spring),
+    CollisionFilter(ecs::CollisionFilter),
+    ForceField(ecs::ForceField),
+    Breakable(ecs::Breakable),
 }
 
 impl DataNetworkComponent {
@@ -132,6 +148,11 @@ impl DataNetworkComponent {
             Self::Surface(component) => world.engine.surface.upsert(entity, component),
             Self::Body(component) => world.engine.body.upsert(entity, component),
             Self::Material(component) => world.engine.material.upsert(entity, component),
+            Self::Joint(component) => world.engine.joint.upsert(entity, component),
+            Self::Spring(component) => world.engine.spring.upsert(entity, component),
+            Self::CollisionFilter(component) => world.engine.collision_filter.upsert(entity, component),
+            Self::ForceField(component) => world.engine.force_field.upsert(entity, component),
+            Self::Breakable(component) => world.engine.breakable.upsert(entity, component),
         }
     }
 }
@@ -270,6 +270,28 @@ impl<S: Encode, I: Encode> ServerSession<S, I> {
         Ok(())
     }
 
+    /// sends the checksum of the snapshot taken at the given tick, nothing is sent if the snapshot isn't stored anymore
+    #[inline]
+    pub fn send_checksum<F>(&self, tick: base::Tick, clients: &[SocketAddr], mut send_packet: F) -> Result<(), base::NetworkError>
+    where
+        F: FnMut(&network::ServerPacket<S, I>, &SocketAddr) -> Result<(), base::NetworkError>,
+    {
+        let Some((_, snapshot)) = self.world_snapshots.iter().find(|(t, _)| *t == tick) else {
+            return Ok(());
+        };
+
+        let packet = network::ServerPacket::Checksum {
+            tick,
+            checksum: network::checksum(snapshot)?,
+        };
+
+        for client in clients.iter() {
+            send_packet(&packet, client)?;
+        }
+
+        Ok(())
+    }
+
     #[inline]
     pub fn send_delta_state<F>(
         &mut self,
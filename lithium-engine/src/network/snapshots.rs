@@ -231,6 +231,11 @@ pub fn initial_state_packets<const N: usize, S: Encode, I: Encode>(
     load_initial_state!(world, packets, S, I, tick, surface, Surface);
     load_initial_state!(world, packets, S, I, tick, body, Body);
     load_initial_state!(world, packets, S, I, tick, material, Material);
+    load_initial_state!(world, packets, S, I, tick, joint, Joint);
+    load_initial_state!(world, packets, S, I, tick, spring, Spring);
+    load_initial_state!(world, packets, S, I, tick, collision_filter, CollisionFilter);
+    load_initial_state!(world, packets, S, I, tick, force_field, ForceField);
+    load_initial_state!(world, packets, S, I, tick, breakable, Breakable);
 
     Ok(())
 }
@@ -249,6 +254,11 @@ pub fn delta_state_snapshots<const N: usize, S: Encode, I: Encode>(
     load_delta_state!(world, world_cache, snapshots, S, I, tick, surface, Surface);
     load_delta_state!(world, world_cache, snapshots, S, I, tick, body, Body);
     load_delta_state!(world, world_cache, snapshots, S, I, tick, material, Material);
+    load_delta_state!(world, world_cache, snapshots, S, I, tick, joint, Joint);
+    load_delta_state!(world, world_cache, snapshots, S, I, tick, spring, Spring);
+    load_delta_state!(world, world_cache, snapshots, S, I, tick, collision_filter, CollisionFilter);
+    load_delta_state!(world, world_cache, snapshots, S, I, tick, force_field, ForceField);
+    load_delta_state!(world, world_cache, snapshots, S, I, tick, breakable, Breakable);
 
     Ok(())
 }
@@ -1,5 +1,17 @@
+pub mod character;
 pub mod collisions;
+pub mod destruction;
 pub mod dynamics;
+pub mod fields;
+pub mod islands;
+pub mod joints;
+pub mod queries;
 
+pub use character::*;
 pub use collisions::*;
+pub use destruction::*;
 pub use dynamics::*;
+pub use fields::*;
+pub use islands::*;
+pub use joints::*;
+pub use queries::*;
@@ -0,0 +1,409 @@
+use crate::math::ApplyTransformationShape;
+use crate::{base, ecs, math, physics};
+
+pub const CHARACTER_MAX_SPEED: f32 = 12.0;
+pub const CHARACTER_GROUND_ACC: f32 = 1.5;
+pub const CHARACTER_AIR_ACC: f32 = 0.75;
+pub const CHARACTER_JUMP_SPEED: f32 = 12.0;
+pub const CHARACTER_MAX_SLOPE_DEGREES: f32 = 50.0;
+pub const CHARACTER_STEP_HEIGHT: f32 = 10.0;
+pub const CHARACTER_SKIN: f32 = 0.05;
+pub const CHARACTER_COYOTE_TIME: f32 = 6.0;
+pub const CHARACTER_JUMP_BUFFER: f32 = 6.0;
+
+/// max number of surfaces the character can slide along in a single update
+const MAX_SLIDES: usize = 4;
+
+/// inputs of a single tick
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CharacterInput {
+    pub move_x: f32, // horizontal input between -1 (left) and 1 (right)
+    pub jump: bool,  // jump pressed during this tick
+}
+
+/// moves a kinematic entity like a platformer character, owned by the user and updated once per tick between
+/// resolve_collisions() and integrate_all_pos(); the entity should have a kinematic translation and no rotation
+#[derive(Debug, Clone)]
+pub struct CharacterController {
+    pub gravity: math::Vec2,          // defines both the acceleration and the down direction of the character
+    pub max_speed: f32,               // max horizontal speed given by the input
+    pub ground_acc: f32,              // horizontal acceleration (and deceleration) while grounded
+    pub air_acc: f32,                 // horizontal acceleration (and deceleration) while in the air
+    pub jump_speed: f32,              // vertical speed given by a jump
+    pub max_slope: f32,               // steepest walkable slope in radians, steeper slopes behave like walls
+    pub step_height: f32,             // max height of the steps the character climbs without jumping
+    pub skin: f32,                    // gap kept between the character and the surfaces around it
+    pub coyote_time: f32,             // time after leaving the ground during which the character can still jump
+    pub jump_buffer: f32,             // time a jump pressed in the air is remembered before landing
+    pub filter: physics::QueryFilter, // entities the character collides with, the character itself is always excluded
+    lin_vel: math::Vec2,              // velocity given by the inputs and gravity, without the velocity of the platform
+    grounded: bool,
+    ground_normal: math::Vec2,
+    ground_entity: Option<ecs::Entity>,
+    air_time: f32,     // time since the character was last grounded
+    jump_timer: f32,   // time left before a buffered jump expires
+    coyote_used: bool, // a jump already consumed the current coyote time
+}
+
+impl CharacterController {
+    #[inline]
+    pub fn new(gravity: math::Vec2) -> Self {
+        Self {
+            gravity,
+            max_speed: CHARACTER_MAX_SPEED,
+            ground_acc: CHARACTER_GROUND_ACC,
+            air_acc: CHARACTER_AIR_ACC,
+            jump_speed: CHARACTER_JUMP_SPEED,
+            max_slope: CHARACTER_MAX_SLOPE_DEGREES.to_radians(),
+            step_height: CHARACTER_STEP_HEIGHT,
+            skin: CHARACTER_SKIN,
+            coyote_time: CHARACTER_COYOTE_TIME,
+            jump_buffer: CHARACTER_JUMP_BUFFER,
+            filter: physics::QueryFilter::default(),
+            lin_vel: math::Vec2::ZERO,
+            grounded: false,
+            ground_normal: math::Vec2::ZERO,
+            ground_entity: None,
+            air_time: 0.0,
+            jump_timer: 0.0,
+            coyote_used: false,
+        }
+    }
+
+    #[inline]
+    pub fn lin_vel(&self) -> math::Vec2 {
+        self.lin_vel
+    }
+
+    #[inline]
+    pub fn grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// normal of the ground below the character, zero if it is in the air
+    #[inline]
+    pub fn ground_normal(&self) -> math::Vec2 {
+        self.ground_normal
+    }
+
+    #[inline]
+    pub fn ground_entity(&self) -> Option<ecs::Entity> {
+        self.ground_entity
+    }
+
+    #[inline]
+    pub fn lin_vel_mut(&mut self) -> &mut math::Vec2 {
+        &mut self.lin_vel
+    }
+
+    /// forgets velocity, ground and timers, e.g. after teleporting the character
+    #[inline]
+    pub fn reset(&mut self) {
+        self.lin_vel = math::Vec2::ZERO;
+        self.grounded = false;
+        self.ground_normal = math::Vec2::ZERO;
+        self.ground_entity = None;
+        self.air_time = 0.0;
+        self.jump_timer = 0.0;
+        self.coyote_used = false;
+    }
+
+    #[inline]
+    fn up(&self) -> math::Vec2 {
+        if self.gravity.approx_equal_zero() {
+            // screen coordinates are y-down
+            math::Vec2::new(0.0, -1.0)
+        } else {
+            self.gravity.norm().rev()
+        }
+    }
+
+    #[inline]
+    fn walkable(&self, normal: math::Vec2) -> bool {
+        normal.dot(self.up()) >= math::cos(self.max_slope)
+    }
+
+    /// casts the shape moved by offset along dir, the distance of the hit already accounts for the skin
+    fn cast<const N: usize>(
+        &self,
+        world: &ecs::World<N>,
+        shape: &math::Shape,
+        filter: &physics::QueryFilter,
+        offset: math::Vec2,
+        dir: math::Vec2,
+        max_distance: f32,
+    ) -> Result<Option<physics::ShapeCastHit>, base::GeometryError> {
+        let hit = physics::shape_cast(world, &shape.apply_vec2_unchecked(offset), dir, max_distance + self.skin, filter)?;
+
+        Ok(hit.map(|hit| physics::ShapeCastHit {
+            distance: (hit.distance - self.skin).max(0.0),
+            ..hit
+        }))
+    }
+
+    /// looks for walkable ground right below the character
+    fn probe_ground<const N: usize>(
+        &mut self,
+        world: &ecs::World<N>,
+        shape: &math::Shape,
+        filter: &physics::QueryFilter,
+    ) -> Result<(), base::GeometryError> {
+        match self.cast(world, shape, filter, math::Vec2::ZERO, self.up().rev(), self.skin)? {
+            Some(hit) if self.walkable(hit.normal) => {
+                self.grounded = true;
+                self.ground_normal = hit.normal;
+                self.ground_entity = Some(hit.entity);
+            }
+            _ => {
+                self.grounded = false;
+                self.ground_normal = math::Vec2::ZERO;
+                self.ground_entity = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// tries to climb a step by moving up, forward and then down, returns the new offset if the character lands on walkable
+    /// ground
+    fn step_up<const N: usize>(
+        &self,
+        world: &ecs::World<N>,
+        shape: &math::Shape,
+        filter: &physics::QueryFilter,
+        offset: math::Vec2,
+        remaining: math::Vec2,
+    ) -> Result<Option<math::Vec2>, base::GeometryError> {
+        let up = self.up();
+        let forward = remaining.sub(up.scale(remaining.dot(up)));
+        let forward_distance = forward.mag();
+
+        if forward_distance < math::EPS || self.step_height <= 0.0 {
+            return Ok(None);
+        }
+
+        let forward_dir = forward.scale(1.0 / forward_distance);
+
+        // move up as much as the ceiling allows
+        let raise = self
+            .cast(world, shape, filter, offset, up, self.step_height)?
+            .map_or(self.step_height, |hit| hit.distance);
+        let raised = offset.add(up.scale(raise));
+
+        // move forward at the raised height
+        let advance = self
+            .cast(world, shape, filter, raised, forward_dir, forward_distance)?
+            .map_or(forward_distance, |hit| hit.distance);
+
+        if advance < math::EPS {
+            return Ok(None);
+        }
+
+        let advanced = raised.add(forward_dir.scale(advance));
+
+        // land on top of the step
+        match self.cast(world, shape, filter, advanced, up.rev(), raise)? {
+            Some(hit) if self.walkable(hit.normal) => Ok(Some(advanced.add(up.rev().scale(hit.distance)))),
+            _ => Ok(None),
+        }
+    }
+
+    /// moves the shape by displacement sliding along the surfaces it hits, returns the final offset and the normals of the
+    /// surfaces hit
+    fn slide<const N: usize>(
+        &self,
+        world: &ecs::World<N>,
+        shape: &math::Shape,
+        filter: &physics::QueryFilter,
+        displacement: math::Vec2,
+    ) -> Result<(math::Vec2, Vec<math::Vec2>), base::GeometryError> {
+        let up = self.up();
+        let mut offset = math::Vec2::ZERO;
+        let mut remaining = displacement;
+        let mut normals = Vec::new();
+
+        for _ in 0..MAX_SLIDES {
+            let distance = remaining.mag();
+
+            if distance < math::EPS {
+                break;
+            }
+
+            let dir = remaining.scale(1.0 / distance);
+
+            let Some(hit) = self.cast(world, shape, filter, offset, dir, distance)? else {
+                // nothing in the way
+                offset.add_mut(remaining);
+                break;
+            };
+
+            offset.add_mut(dir.scale(hit.distance));
+            remaining = dir.scale(distance - hit.distance);
+
+            let mut normal = hit.normal;
+
+            if self.grounded && !self.walkable(normal) {
+                if let Some(stepped) = self.step_up(world, shape, filter, offset, remaining)? {
+                    offset = stepped;
+                    break;
+                }
+
+                if normal.dot(up) > 0.0 {
+                    // steep slopes behave like walls, otherwise the character could climb them by sliding
+                    let side = normal.sub(up.scale(normal.dot(up)));
+
+                    if !side.approx_equal_zero() {
+                        normal = side.norm();
+                    }
+                }
+            }
+
+            // remove the part of the movement going into the surface
+            let into = remaining.dot(normal);
+            if into < 0.0 {
+                remaining.sub_mut(normal.scale(into));
+            }
+
+            normals.push(normal);
+        }
+
+        Ok((offset, normals))
+    }
+
+    /// updates the velocity of the entity so that integrate_all_pos() moves it to the position computed by the controller;
+    /// does nothing if step is not positive, since the velocity is the displacement divided by step
+    pub fn update<const N: usize>(
+        &mut self,
+        world: &mut ecs::World<N>,
+        entity: ecs::Entity,
+        input: &CharacterInput,
+        step: f32,
+    ) -> Result<(), base::GeometryError> {
+        if step <= 0.0 {
+            return Ok(());
+        }
+
+        let Some(shape) = physics::apply_all_trans(world, entity) else {
+            return Ok(());
+        };
+
+        let mut filter = self.filter.clone();
+        filter.exclude.push(entity);
+
+        let up = self.up();
+        let right = up.perp_ccw();
+
+        // jump buffer
+        if input.jump {
+            self.jump_timer = self.jump_buffer;
+        } else {
+            self.jump_timer = (self.jump_timer - step).max(0.0);
+        }
+
+        // ground detection and coyote time
+        self.probe_ground(world, &shape, &filter)?;
+
+        if self.grounded {
+            self.air_time = 0.0;
+            self.coyote_used = false;
+        } else {
+            self.air_time += step;
+        }
+
+        // the character is carried by moving platforms and by moving surfaces (e.g. conveyor belts) along the ground
+        let platform_lin_vel = match self.ground_entity {
+            Some(ground_entity) => {
+                let lin_vel = world
+                    .engine
+                    .translation
+                    .get(ground_entity)
+                    .map_or(math::Vec2::ZERO, |translation| translation.lin_vel);
+
+                let surface_vel = world.engine.surface.get(ground_entity).map_or(math::Vec2::ZERO, |surface| {
+                    physics::global_surface_vel(surface, world.engine.rotation_matrix.get(ground_entity))
+                });
+                let tangent = self.ground_normal.perp_ccw();
+
+                lin_vel.add(tangent.scale(surface_vel.dot(tangent)))
+            }
+            None => math::Vec2::ZERO,
+        };
+
+        // horizontal velocity approaches the one requested by the input
+        let mut lin_vel_x = self.lin_vel.dot(right);
+        let mut lin_vel_y = self.lin_vel.dot(up);
+
+        let acc = if self.grounded { self.ground_acc } else { self.air_acc } * step;
+        let target_lin_vel_x = input.move_x.clamp(-1.0, 1.0) * self.max_speed;
+        lin_vel_x += (target_lin_vel_x - lin_vel_x).clamp(-acc, acc);
+
+        // vertical velocity
+        if self.grounded && lin_vel_y <= 0.0 {
+            lin_vel_y = 0.0;
+        } else {
+            lin_vel_y -= self.gravity.mag() * step;
+        }
+
+        let mut on_ground = self.grounded && lin_vel_y <= 0.0;
+
+        if self.jump_timer > 0.0 && !self.coyote_used && self.air_time <= self.coyote_time {
+            lin_vel_y = self.jump_speed;
+            self.jump_timer = 0.0;
+            self.coyote_used = true;
+            on_ground = false;
+        }
+
+        self.lin_vel = right.scale(lin_vel_x).add(up.scale(lin_vel_y));
+
+        if on_ground {
+            // walk along the ground instead of moving horizontally, so slopes don't slow the character down
+            let tangent = self.ground_normal.perp_ccw();
+            let (offset, normals) = self.slide(world, &shape, &filter, tangent.scale(lin_vel_x * step))?;
+            let mut offset = offset;
+
+            // stick to the ground while walking down slopes and steps
+            if let Some(hit) = self.cast(world, &shape, &filter, offset, up.rev(), self.step_height)?
+                && self.walkable(hit.normal)
+            {
+                offset.add_mut(up.rev().scale(hit.distance));
+            }
+
+            self.stop_into(&normals);
+
+            // the platform moves the character along with it, its own collisions are not checked again
+            offset.add_mut(platform_lin_vel.scale(step));
+            self.set_displacement(world, entity, offset, step);
+        } else {
+            if self.grounded {
+                // jumping off a platform keeps its velocity
+                self.lin_vel.add_mut(platform_lin_vel);
+            }
+
+            let (offset, normals) = self.slide(world, &shape, &filter, self.lin_vel.scale(step))?;
+
+            self.stop_into(&normals);
+            self.set_displacement(world, entity, offset, step);
+        }
+
+        Ok(())
+    }
+
+    /// removes the velocity going into the surfaces hit during the last move
+    #[inline]
+    fn stop_into(&mut self, normals: &[math::Vec2]) {
+        for &normal in normals {
+            let into = self.lin_vel.dot(normal);
+            if into < 0.0 {
+                self.lin_vel.sub_mut(normal.scale(into));
+            }
+        }
+    }
+
+    /// integrate_all_pos() moves the entity by lin_vel * step
+    #[inline]
+    fn set_displacement<const N: usize>(&self, world: &mut ecs::World<N>, entity: ecs::Entity, displacement: math::Vec2, step: f32) {
+        if let Some(translation) = world.engine.translation.get_mut(entity) {
+            translation.lin_vel = displacement.scale(1.0 / step);
+        }
+    }
+}
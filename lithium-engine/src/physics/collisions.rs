@@ -1,7 +1,11 @@
 pub mod contact;
 pub mod detection;
+pub mod gjk;
+pub mod manager;
 pub mod resolution;
 
 pub use contact::*;
 pub use detection::*;
+pub use gjk::*;
+pub use manager::*;
 pub(crate) use resolution::*;
@@ -1,4 +1,3 @@
-use crate::math::ApplyTransformationVerts;
 use crate::{base, ecs, math};
 
 enum Feature {
@@ -18,7 +17,6 @@ impl Feature {
         }
     }
 
-    #[allow(dead_code)]
     #[inline]
     fn apply_mat2x3_mut(&mut self, mat: &math::Mat2x3) {
         match self {
@@ -29,6 +27,39 @@ impl Feature {
             }
         }
     }
+
+    /// projection of the feature on an axis, taking the furthest point along the axis
+    #[inline]
+    fn support_dot(&self, axis: math::Vec2) -> f32 {
+        match self {
+            Self::Vertex(vertex) => vertex.dot(axis),
+            Self::Edge(segment) => segment.a.dot(axis).max(segment.b.dot(axis)),
+        }
+    }
+}
+
+/// position and rotation matrix of an entity after a fraction of the step
+fn config_at(
+    pos: math::Vec2,
+    rot_mat: Option<&ecs::RotationMatrix>,
+    lin_vel: Option<math::Vec2>,
+    ang_vel: Option<f32>,
+    body: &ecs::Body,
+    step: f32,
+) -> (math::Vec2, Option<math::Mat2x3>) {
+    let pos = match lin_vel {
+        Some(v) => pos.add(v.scale(step)),
+        None => pos,
+    };
+
+    let rot_mat = match (rot_mat, ang_vel) {
+        (Some(rm), Some(av)) => Some(rm.update(math::Radians(av * step), rm.rot_mat.pre_mul_vec2(body.centroid)).rot_mat),
+        (Some(rm), None) => Some(rm.rot_mat.clone()),
+        (None, Some(_)) => panic!("ang_vel exists but rot_mat does not"),
+        (None, None) => None,
+    };
+
+    (pos, rot_mat)
 }
 
 /// extimates the contact point of a collision
@@ -49,11 +80,22 @@ pub fn compute_contact_point(
     step: f32,
 ) -> Result<math::Vec2, base::GeometryError> {
     const FEATURE_MARGIN: f32 = 0.05;
+    const FEATURE_SLOPE: f32 = 0.02; // edges tilted less than this from the contact plane are treated as flat
     const PARALLEL_EPS: f32 = 0.02;
 
+    /// 2 vertices are part of the same support edge if their projections are close, either in absolute terms or compared
+    /// to the length of the edge, so that long edges are not reduced to a single vertex by a tiny tilt
+    #[inline]
+    fn is_support_edge(vert_1: math::Vec2, vert_2: math::Vec2, dot_1: f32, dot_2: f32) -> bool {
+        let margin = FEATURE_MARGIN.max(FEATURE_SLOPE * vert_1.dist(vert_2));
+        (dot_1 - dot_2).abs() <= margin
+    }
+
+    /// support feature of a shape along normal, the search is done in local space by rotating the normal backwards, and
+    /// only the resulting feature is moved to world space
     fn find_support_feature(
         normal: math::Vec2,
-        rot_mat: Option<&ecs::RotationMatrix>,
+        rot_mat: Option<&math::Mat2x3>,
         shape: &math::Shape,
         cave_part_idx: usize,
     ) -> Result<Feature, base::GeometryError> {
@@ -76,18 +118,15 @@ pub fn compute_contact_point(
                 next_idx
             };
 
-            if pairs[best_idx].1 - pairs[second_idx].1 <= FEATURE_MARGIN {
+            if is_support_edge(pairs[best_idx].0, pairs[second_idx].0, pairs[best_idx].1, pairs[second_idx].1) {
                 Feature::Edge(math::Segment::new_unchecked(pairs[best_idx].0, pairs[second_idx].0))
             } else {
                 Feature::Vertex(pairs[best_idx].0)
             }
         }
 
-        fn find_support_feature_cvx_poly(normal: math::Vec2, rot_mat: Option<&ecs::RotationMatrix>, cvx_poly: &math::CvxPoly) -> Feature {
-            let verts = match rot_mat {
-                Some(ecs::RotationMatrix { rot_mat: rm }) => &cvx_poly.apply_mat2x3(rm),
-                None => &cvx_poly.verts,
-            };
+        fn find_support_feature_cvx_poly(normal: math::Vec2, cvx_poly: &math::CvxPoly) -> Feature {
+            let verts = &cvx_poly.verts;
 
             let n_sides = verts.len();
 
@@ -114,24 +153,27 @@ pub fn compute_contact_point(
                 (next_idx, next_dot)
             };
 
-            if best_dot - second_dot <= FEATURE_MARGIN {
+            if is_support_edge(verts[best_idx], verts[second_idx], best_dot, second_dot) {
                 Feature::Edge(math::Segment::new_unchecked(verts[best_idx], verts[second_idx]))
             } else {
                 Feature::Vertex(verts[best_idx])
             }
         }
 
-        Ok(match shape {
+        // rotation matrices are orthonormal, so the transpose of the linear part rotates the normal back to local space
+        let normal = match rot_mat {
+            Some(rm) => math::Vec2::new(normal.x * rm.x.0 + normal.y * rm.x.1, normal.x * rm.y.0 + normal.y * rm.y.1),
+            None => normal,
+        };
+
+        let mut feature = match shape {
             math::Shape::Segment(segment) => {
-                let [a, b] = match rot_mat {
-                    Some(ecs::RotationMatrix { rot_mat: rm }) => segment.apply_mat2x3(rm),
-                    None => [segment.a, segment.b],
-                };
+                let [a, b] = [segment.a, segment.b];
 
                 let a_dot = a.dot(normal);
                 let b_dot = b.dot(normal);
 
-                if (a_dot - b_dot).abs() <= FEATURE_MARGIN {
+                if is_support_edge(a, b, a_dot, b_dot) {
                     Feature::Edge(math::Segment::new_unchecked(a, b))
                 } else if a_dot >= b_dot {
                     Feature::Vertex(a)
@@ -140,32 +182,33 @@ pub fn compute_contact_point(
                 }
             }
             math::Shape::Triangle(triangle) => {
-                let [a, b, c] = match rot_mat {
-                    Some(ecs::RotationMatrix { rot_mat: rm }) => triangle.apply_mat2x3(rm),
-                    None => [triangle.a, triangle.b, triangle.c],
-                };
+                let [a, b, c] = [triangle.a, triangle.b, triangle.c];
 
                 let pairs = [(a, a.dot(normal)), (b, b.dot(normal)), (c, c.dot(normal))];
 
                 find_support_feature_from_pairs(&pairs)
             }
             math::Shape::Quad(quad) => {
-                let [a, b, c, d] = match rot_mat {
-                    Some(ecs::RotationMatrix { rot_mat: rm }) => quad.apply_mat2x3(rm),
-                    None => [quad.a, quad.b, quad.c, quad.d],
-                };
+                let [a, b, c, d] = [quad.a, quad.b, quad.c, quad.d];
 
                 let pairs = [(a, a.dot(normal)), (b, b.dot(normal)), (c, c.dot(normal)), (d, d.dot(normal))];
 
                 find_support_feature_from_pairs(&pairs)
             }
-            math::Shape::CvxPoly(cvx_poly) => find_support_feature_cvx_poly(normal, rot_mat, cvx_poly),
+            math::Shape::CvxPoly(cvx_poly) => find_support_feature_cvx_poly(normal, cvx_poly),
             math::Shape::CavePoly(cave_poly) => {
                 let cvx_polys = cave_poly.cvx_polys()?;
-                find_support_feature_cvx_poly(normal, rot_mat, &cvx_polys[cave_part_idx])
+                find_support_feature_cvx_poly(normal, &cvx_polys[cave_part_idx])
             }
-            math::Shape::Circle(_) => unimplemented!(),
-        })
+            // a circle touches with the single point of its boundary facing the normal
+            math::Shape::Circle(circle) => Feature::Vertex(math::Support::support(circle, normal)),
+        };
+
+        if let Some(rm) = rot_mat {
+            feature.apply_mat2x3_mut(rm);
+        }
+
+        Ok(feature)
     }
 
     fn project_vertex_on_segment(vertex: math::Vec2, segment: &math::Segment, to_clamp: bool) -> math::Vec2 {
@@ -249,15 +292,22 @@ pub fn compute_contact_point(
         best_on_1.midpoint(best_on_2)
     }
 
-    fn check_overlap_and_find_midpoint(segment_1: &math::Segment, segment_2: &math::Segment) -> Option<math::Vec2> {
+    /// center of the overlap between 2 parallel segments, weighted by how deep each end of the overlap is along the normal,
+    /// so that a slightly tilted edge pushes more on its lower end and is levelled instead of being tipped over
+    fn check_overlap_and_find_center(normal: math::Vec2, segment_1: &math::Segment, segment_2: &math::Segment) -> Option<math::Vec2> {
         let (a, b) = (segment_1.a, segment_1.b);
         let (c, d) = (segment_2.a, segment_2.b);
         let (c_proj, d_proj) = (
             project_vertex_on_segment(c, segment_1, false),
             project_vertex_on_segment(d, segment_1, false),
         );
+        let (a_proj, b_proj) = (
+            project_vertex_on_segment(a, segment_2, false),
+            project_vertex_on_segment(b, segment_2, false),
+        );
 
-        let mut pairs = [(a, a), (b, b), (c, c_proj), (d, d_proj)];
+        // (point on the line of segment_1, point on the line of segment_2)
+        let mut pairs = [(a, a_proj), (b, b_proj), (c_proj, c), (d_proj, d)];
 
         if (a.x - b.x).abs() >= math::EPS {
             // non-vertical lines
@@ -270,7 +320,7 @@ pub fn compute_contact_point(
             }
 
             // sort points by x
-            pairs.sort_by(|p1, p2| p1.1.x.partial_cmp(&p2.1.x).unwrap());
+            pairs.sort_by(|p1, p2| p1.0.x.partial_cmp(&p2.0.x).unwrap());
         } else {
             // vertical lines
             let overlap_start_y = (a.y.min(b.y)).max(c_proj.y.min(d_proj.y));
@@ -282,44 +332,61 @@ pub fn compute_contact_point(
             }
 
             // sort points by y
-            pairs.sort_by(|p1, p2| p1.1.y.partial_cmp(&p2.1.y).unwrap());
+            pairs.sort_by(|p1, p2| p1.0.y.partial_cmp(&p2.0.y).unwrap());
         };
 
         let overlap_start_midpoint = pairs[1].0.midpoint(pairs[1].1);
         let overlap_end_midpoint = pairs[2].0.midpoint(pairs[2].1);
-        Some(overlap_start_midpoint.midpoint(overlap_end_midpoint))
+
+        // the margin keeps both ends relevant when the edges barely touch
+        let start_weight = pairs[1].0.sub(pairs[1].1).dot(normal).max(0.0) + FEATURE_MARGIN;
+        let end_weight = pairs[2].0.sub(pairs[2].1).dot(normal).max(0.0) + FEATURE_MARGIN;
+
+        Some(
+            overlap_start_midpoint.add(
+                overlap_end_midpoint
+                    .sub(overlap_start_midpoint)
+                    .scale(end_weight / (start_weight + end_weight)),
+            ),
+        )
     }
 
-    let global_pos_1 = match lin_vel_1 {
-        Some(v) => pos_1.add(v.scale(step)),
-        None => pos_1,
-    };
+    // support features of both entities after a fraction of the step
+    let find_supports = |fraction: f32| -> Result<(Feature, Feature), base::GeometryError> {
+        let (global_pos_1, global_rot_mat_1) = config_at(pos_1, rot_mat_1, lin_vel_1, ang_vel_1, body_1, fraction * step);
+        let (global_pos_2, global_rot_mat_2) = config_at(pos_2, rot_mat_2, lin_vel_2, ang_vel_2, body_2, fraction * step);
 
-    let global_pos_2 = match lin_vel_2 {
-        Some(v) => pos_2.add(v.scale(step)),
-        None => pos_2,
-    };
+        let mut support_1 = find_support_feature(normal, global_rot_mat_1.as_ref(), &body_1.shape, cave_part_idx_1)?;
+        let mut support_2 = find_support_feature(normal.rev(), global_rot_mat_2.as_ref(), &body_2.shape, cave_part_idx_2)?;
 
-    let global_rot_mat_1: Option<&ecs::RotationMatrix> = match (rot_mat_1, ang_vel_1) {
-        (Some(rm), Some(av)) => Some(&rm.update(math::Radians(av * step), rm.rot_mat.pre_mul_vec2(body_1.centroid))),
-        (Some(rm), None) => Some(rm),
-        (None, Some(_)) => panic!("ang_vel exists but rot_mat does not"),
-        (None, None) => None,
-    };
+        support_1.apply_vec2_mut(global_pos_1);
+        support_2.apply_vec2_mut(global_pos_2);
 
-    let global_rot_mat_2: Option<&ecs::RotationMatrix> = match (rot_mat_2, ang_vel_2) {
-        (Some(rm), Some(av)) => Some(&rm.update(math::Radians(av * step), rm.rot_mat.pre_mul_vec2(body_2.centroid))),
-        (Some(rm), None) => Some(rm),
-        (None, Some(_)) => panic!("ang_vel exists but rot_mat does not"),
-        (None, None) => None,
+        Ok((support_1, support_2))
     };
 
-    // find support vertices
-    let mut support_1 = find_support_feature(normal, global_rot_mat_1, &body_1.shape, cave_part_idx_1)?;
-    let mut support_2 = find_support_feature(normal.rev(), global_rot_mat_2, &body_2.shape, cave_part_idx_2)?;
+    // gap between the entities along the normal, it is negative when they overlap
+    let gap = |(support_1, support_2): &(Feature, Feature)| -support_2.support_dot(normal.rev()) - support_1.support_dot(normal);
+
+    // the collision detection works on the shapes swept across the whole step, so the entities may only touch somewhere
+    // in the middle of it; since rotations can make a different vertex lead, the contact is searched where the gap
+    // closes, estimated by interpolating the gap between the start and the end of the step
+    let supports_end = find_supports(1.0)?;
+    let gap_end = gap(&supports_end);
 
-    support_1.apply_vec2_mut(global_pos_1);
-    support_2.apply_vec2_mut(global_pos_2);
+    let (support_1, support_2) = if gap_end < 0.0 {
+        let supports_start = find_supports(0.0)?;
+        let gap_start = gap(&supports_start);
+
+        if gap_start <= 0.0 {
+            // already touching at the start of the step
+            supports_start
+        } else {
+            find_supports(gap_start / (gap_start - gap_end))?
+        }
+    } else {
+        supports_end
+    };
 
     Ok(match (support_1, support_2) {
         (Feature::Vertex(vertex_1), Feature::Vertex(vertex_2)) => vertex_1.midpoint(vertex_2),
@@ -335,7 +402,8 @@ pub fn compute_contact_point(
 
             let denominator = edge_1.cross(edge_2);
 
-            if denominator.abs() >= PARALLEL_EPS {
+            // compare the sine of the angle between the edges, so that the check does not depend on their length
+            if denominator.abs() >= PARALLEL_EPS * edge_1.mag() * edge_2.mag() {
                 // edges are not parallel
 
                 let delta = segment_2.a.sub(segment_1.a);
@@ -378,10 +446,10 @@ pub fn compute_contact_point(
                 }
             } else {
                 // edges are parallel
-                let overlap_midpoint = check_overlap_and_find_midpoint(&segment_1, &segment_2);
+                let overlap_center = check_overlap_and_find_center(normal, &segment_1, &segment_2);
 
-                match overlap_midpoint {
-                    Some(midpoint) => midpoint,
+                match overlap_center {
+                    Some(center) => center,
                     None => midpoint_of_min_dist_between_parallel_non_overlapping_segments(&segment_1, &segment_2),
                 }
             }
@@ -1,7 +1,7 @@
 use crate::math::{ApplyTransformationShape, Centroid, SatCompatible, ToHitBox};
 use crate::{base, ecs, math, physics};
 
-use std::mem;
+use std::{collections::HashSet, mem};
 
 /// checks if 2 hitboxes are colliding using EPS to prevent false negatives
 #[inline]
@@ -29,6 +29,20 @@ pub enum SatCollision {
     Cave(Vec<CaveCollision>),
 }
 
+/// algorithm used by resolve_collisions() to check if the global shapes of 2 entities are colliding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NarrowPhase {
+    #[default]
+    Sat, // separating axis theorem with check_sat(), polygons only, pairs involving a circle fall back to check_gjk()
+    Gjk, // gjk and epa with check_gjk(), works with every shape through its support function
+}
+
+/// true for shapes with a curved boundary, which check_sat() cannot separate
+#[inline]
+pub(crate) fn is_round(shape: &math::Shape) -> bool {
+    matches!(shape, math::Shape::Circle(_))
+}
+
 /// checks if 2 convex geometries are colliding using SAT algorithm, returns the contact overlap and normal
 fn check_sat_cvx<T, U>(geometry_1: &T, geometry_2: &U) -> Option<CvxCollision>
 where
@@ -95,22 +109,28 @@ where
     })
 }
 
-/// checks if 2 geometries are colliding using SAT algorithm with check_sat_cvx()
+/// checks if 2 geometries are colliding using SAT algorithm with check_sat_cvx(), the convex pieces of concave geometries
+/// are first culled with their hitboxes, circles have no sides to build axes from and must be checked with check_gjk()
 pub fn check_sat<T, U>(geometry_1: &T, geometry_2: &U) -> Result<Option<SatCollision>, base::GeometryError>
 where
-    T: SatCompatible + Centroid,
-    U: SatCompatible + Centroid,
+    T: SatCompatible + Centroid + ToHitBox,
+    U: SatCompatible + Centroid + ToHitBox,
 {
     let cave_parts_1 = geometry_1.split_cave()?;
     let cave_parts_2 = geometry_2.split_cave()?;
 
-    fn test_against_cave<V>(cvx_geometry: &V, cave_parts: &[math::CvxPoly]) -> Option<Vec<(CvxCollision, usize)>>
+    fn test_against_cave<V>(cvx_geometry: &V, cave_parts: math::CaveParts) -> Option<Vec<(CvxCollision, usize)>>
     where
-        V: SatCompatible + Centroid,
+        V: SatCompatible + Centroid + ToHitBox,
     {
-        let mut collisions = Vec::with_capacity(cave_parts.len());
+        let hitbox = cvx_geometry.to_hitbox();
+        let mut collisions = Vec::with_capacity(cave_parts.cvx_polys.len());
+
+        for (idx, (cvx_poly, cvx_hitbox)) in cave_parts.cvx_polys.iter().zip(cave_parts.hitboxes).enumerate() {
+            if !check_hitboxes(&hitbox, cvx_hitbox) {
+                continue;
+            }
 
-        for (idx, cvx_poly) in cave_parts.iter().enumerate() {
             if let Some(cvx_collision) = check_sat_cvx(cvx_geometry, cvx_poly) {
                 collisions.push((cvx_collision, idx));
             };
@@ -148,8 +168,12 @@ where
         (Some(cave_parts_1), Some(cave_parts_2)) => {
             let mut collisions = Vec::new();
 
-            for (idx_1, cvx_poly_1) in cave_parts_1.iter().enumerate() {
-                for (idx_2, cvx_poly_2) in cave_parts_2.iter().enumerate() {
+            for (idx_1, (cvx_poly_1, hitbox_1)) in cave_parts_1.cvx_polys.iter().zip(cave_parts_1.hitboxes).enumerate() {
+                for (idx_2, (cvx_poly_2, hitbox_2)) in cave_parts_2.cvx_polys.iter().zip(cave_parts_2.hitboxes).enumerate() {
+                    if !check_hitboxes(hitbox_1, hitbox_2) {
+                        continue;
+                    }
+
                     if let Some(CvxCollision { normal }) = check_sat_cvx(cvx_poly_1, cvx_poly_2) {
                         collisions.push(CaveCollision {
                             normal,
@@ -169,6 +193,29 @@ where
     })
 }
 
+/// world space pass direction of a one-way surface, it rotates with its entity
+#[inline]
+pub(crate) fn global_pass_dir(surface: &ecs::Surface, rot_mat: Option<&ecs::RotationMatrix>) -> Option<math::Vec2> {
+    let pass_dir = surface.one_way?;
+
+    Some(match rot_mat {
+        Some(ecs::RotationMatrix { rot_mat: rm }) => rm.pre_mul_vec2_linear(pass_dir),
+        None => pass_dir,
+    })
+}
+
+/// true if a one-way surface lets the other entity through, normal points from the one-way entity to the other one and
+/// rel_vel is the linear velocity of the other entity relative to the one-way entity
+#[inline]
+fn passes_one_way(surface: &ecs::Surface, rot_mat: Option<&ecs::RotationMatrix>, normal: math::Vec2, rel_vel: math::Vec2) -> bool {
+    let Some(pass_dir) = global_pass_dir(surface, rot_mat) else {
+        return false;
+    };
+
+    // the other entity is blocked only if it is on the side entities exit from and it is moving back against pass_dir
+    normal.dot(pass_dir) <= math::EPS || rel_vel.dot(pass_dir) >= 0.0
+}
+
 pub fn compute_global_shape(
     state: State,
     mut pos: math::Vec2,
@@ -178,22 +225,26 @@ pub fn compute_global_shape(
     body: &ecs::Body,
     step: f32,
 ) -> math::Shape {
-    let rot_mat = if matches!(state, State::Static | State::Still) {
-        None
-    } else {
-        pos = match lin_vel {
-            Some(lv) => pos.add(lv.scale(step)),
-            None => pos,
-        };
-
-        match (rot_mat, ang_vel) {
-            (Some(rm), Some(av)) => Some(&rm.update(math::Radians(av * step), rm.rot_mat.pre_mul_vec2(body.centroid))),
-            (Some(rm), None) => Some(rm),
-            (None, Some(_)) => panic!("ang_vel exists but rot_mat does not"), // <- thanks Lyla for fixing the error message
-            (None, None) => None,
-        }
+    // entities that are not moving keep their current orientation, they are not advanced across the step
+    let moving = !matches!(state, State::Static | State::Still | State::Sleeping);
+
+    if moving && let Some(lv) = lin_vel {
+        pos = pos.add(lv.scale(step));
+    }
+
+    let rot_mat = match (rot_mat, ang_vel) {
+        (Some(rm), Some(av)) if moving => Some(&rm.update(math::Radians(av * step), rm.rot_mat.pre_mul_vec2(body.centroid))),
+        (Some(rm), _) => Some(rm),
+        (None, Some(_)) if moving => panic!("ang_vel exists but rot_mat does not"), // <- thanks Lyla for fixing the error message
+        (None, _) => None,
     };
 
+    // decompose concave shapes once in local space, the transformations below carry the pieces along; errors are ignored
+    // here since they are returned again when the global shape is split
+    if let math::Shape::CavePoly(cave_poly) = &body.shape {
+        let _ = cave_poly.cvx_polys();
+    }
+
     match rot_mat {
         Some(ecs::RotationMatrix { rot_mat: rm }) => body.shape.apply_mat2x3_then_vec2_unchecked(pos, rm),
         None => body.shape.apply_vec2_unchecked(pos),
@@ -203,21 +254,33 @@ pub fn compute_global_shape(
 #[repr(u8)]
 #[derive(Debug, Clone, Copy)]
 pub enum State {
-    Active, // entity can collide
-    Far,    // entity has not been involved in a collision for an entire iteration
-    Still,  // entity has translation or rotation components, but they are currently zero
-    Static, // entity does not have translation or rotation components
+    Active,   // entity can collide
+    Far,      // entity has not been involved in a collision for an entire iteration
+    Still,    // entity has translation or rotation components, but they are currently zero
+    Static,   // entity does not have translation or rotation components
+    Sleeping, // entity belongs to a sleeping island, it is woken up when an active entity hits it
     Invalid,
 }
 
-/// detects collisions and computes reactions for every object
-pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usize, step: f32) -> Result<(), base::GeometryError> {
+/// detects collisions and computes reactions for every object, touching pairs are stored in contacts and used to put
+/// islands to sleep
+pub fn resolve_collisions<const N: usize>(
+    world: &mut ecs::World<N>,
+    contacts: &mut physics::ContactManager,
+    iters: usize,
+    step: f32,
+) -> Result<(), base::GeometryError> {
     #[inline]
     fn get_state<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity) -> State {
         let mut translation_is_zero = false; // true -> exists but is 0; false -> does not exist
         let mut rotation_is_zero = false;
 
-        if let Some(&ecs::Translation { lin_vel, .. }) = world.engine.translation.get(entity) {
+        if let Some(&ecs::Translation { lin_vel, sleeping, .. }) = world.engine.translation.get(entity) {
+            if sleeping {
+                // entity is part of a sleeping island
+                return State::Sleeping;
+            }
+
             // entity can move
             if lin_vel.approx_equal_zero() {
                 // entity is not moving
@@ -248,7 +311,19 @@ pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usiz
         }
     }
 
-    let ents = world.engine.transform.get_ents();
+    /// true if nothing can push the entity, either because it is static or kinematic
+    #[inline]
+    fn is_immovable<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity) -> bool {
+        match world.engine.translation.get(entity) {
+            Some(translation) => translation.kinematic,
+            None => world.engine.rotation.get(entity).is_none(),
+        }
+    }
+
+    // riders must inherit the motion of kinematic entities before the new velocities are used to predict the shapes
+    contacts.carry_riders(world);
+
+    let ents = world.engine.transform.get_ents().to_vec(); // cloned since the joint solver needs the whole world
     let len = ents.len();
     let mut states: Vec<State> = Vec::with_capacity(len);
     let mut next_states = vec![State::Invalid; len];
@@ -271,6 +346,18 @@ pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usiz
         }
     }
 
+    // pairs where neither entity is active are never checked, so their previous contacts and overlaps must be kept
+    // as long as both entities are still valid objects
+    let resting_ents: HashSet<ecs::Entity> = ents
+        .iter()
+        .zip(states.iter())
+        .filter(|(_, state)| !matches!(state, State::Active | State::Invalid))
+        .map(|(&entity, _)| entity)
+        .collect();
+
+    contacts.clear();
+    physics::reset_joint_impulses(world);
+
     for i in 0..iters {
         let mut solved = true;
 
@@ -373,6 +460,27 @@ pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usiz
                     continue 'loop_2;
                 };
 
+                // filtering, entities without a filter collide with everything
+                let filter_1 = world
+                    .engine
+                    .collision_filter
+                    .get(entity_1)
+                    .unwrap_or(&ecs::CollisionFilter::DEFAULT);
+                let filter_2 = world
+                    .engine
+                    .collision_filter
+                    .get(entity_2)
+                    .unwrap_or(&ecs::CollisionFilter::DEFAULT);
+
+                if !filter_1.should_collide(filter_2) {
+                    continue 'loop_2;
+                }
+
+                if !surface_1.sensor && !surface_2.sensor && is_immovable(world, entity_1) && is_immovable(world, entity_2) {
+                    // kinematic entities pass through static and other kinematic entities, but they still trigger sensors
+                    continue 'loop_2;
+                }
+
                 // compute global shapes
                 if global_shape_1.is_none() {
                     // println!("recomputing global_shape_1 cache");
@@ -398,16 +506,66 @@ pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usiz
 
                 // narrow phase
                 // println!("{entity_1}-{entity_2} checking global shapes...");
-                let Some(sat_collision) = check_sat(global_shape_1.as_ref().unwrap(), &global_shape_2)? else {
+                let sat_collision = match contacts.narrow_phase {
+                    NarrowPhase::Sat if !is_round(global_shape_1.as_ref().unwrap()) && !is_round(&global_shape_2) => {
+                        check_sat(global_shape_1.as_ref().unwrap(), &global_shape_2)?
+                    }
+                    // sat has no axes for curved shapes, so circles always go through gjk
+                    _ => physics::check_gjk(global_shape_1.as_ref().unwrap(), &global_shape_2)?.map(SatCollision::from),
+                };
+
+                let Some(sat_collision) = sat_collision else {
                     // global shapes are not colliding, no need to compute reaction or invalidate cache
                     // println!("  -> global shapes NOT colliding");
                     continue 'loop_2;
                 };
                 // println!("  -> global shapes ARE colliding");
 
+                if surface_1.sensor || surface_2.sensor {
+                    // sensors only record the overlap, no reaction and no need to iterate again
+                    if surface_1.sensor {
+                        contacts.add_overlap(entity_1, entity_2);
+                    }
+
+                    if surface_2.sensor {
+                        contacts.add_overlap(entity_2, entity_1);
+                    }
+
+                    continue 'loop_2;
+                }
+
+                // one-way surfaces, only the normals that block the entities are kept
+                let rel_vel = lin_vel_2.unwrap_or(math::Vec2::ZERO).sub(lin_vel_1.unwrap_or(math::Vec2::ZERO));
+                let blocks = |normal: math::Vec2| {
+                    !passes_one_way(surface_1, rot_mat_1, normal, rel_vel)
+                        && !passes_one_way(surface_2, rot_mat_2, normal.rev(), rel_vel.rev())
+                };
+
+                let sat_collision = match sat_collision {
+                    SatCollision::Cvx(cvx_collision) if blocks(cvx_collision.normal) => SatCollision::Cvx(cvx_collision),
+                    SatCollision::Cave(mut cave_collisions) => {
+                        cave_collisions.retain(|cave_collision| blocks(cave_collision.normal));
+
+                        if cave_collisions.is_empty() {
+                            continue 'loop_2;
+                        }
+
+                        SatCollision::Cave(cave_collisions)
+                    }
+                    _ => continue 'loop_2,
+                };
+
                 // collision detected
                 solved = false;
                 entity_1_is_far = false;
+                contacts.add_pair(entity_1, entity_2);
+
+                if matches!(state_2, State::Sleeping)
+                    && let Some(translation_2) = world.engine.translation.get_mut(entity_2)
+                {
+                    // wake on contact, the rest of its island will follow in update_sleeping()
+                    translation_2.wake();
+                }
 
                 // compute centers of mass
                 let mass_center_1 = global_shape_1.as_ref().unwrap().centroid();
@@ -418,6 +576,8 @@ pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usiz
                 global_shape_1 = None;
 
                 let (mut translation_1, mut translation_2) = world.engine.translation.get2_mut(entity_1, entity_2);
+                let kinematic_1 = translation_1.as_deref().is_some_and(|translation_1| translation_1.kinematic);
+                let kinematic_2 = translation_2.as_deref().is_some_and(|translation_2| translation_2.kinematic);
                 let (mut rotation_1, mut rotation_2) = world.engine.rotation.get2_mut(entity_1, entity_2);
 
                 // println!("{entity_1}-{entity_2} computing reaction");
@@ -440,7 +600,7 @@ pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usiz
                             step,
                         )?;
 
-                        physics::compute_reaction(
+                        let impulse = physics::compute_reaction(
                             normal,
                             contact_point,
                             mass_center_1,
@@ -451,8 +611,26 @@ pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usiz
                             rotation_2.as_deref_mut(),
                             surface_1,
                             surface_2,
+                            rot_mat_1,
+                            rot_mat_2,
                         );
 
+                        if impulse > 0.0 {
+                            contacts.add_impact(entity_1, impulse, contact_point);
+                            contacts.add_impact(entity_2, impulse, contact_point);
+                        }
+
+                        // a dynamic entity above a kinematic one rides it
+                        if normal.x.abs() <= 0.5 {
+                            if kinematic_1 && normal.y < 0.0 && translation_2.as_deref().is_some_and(|t| !t.kinematic) {
+                                contacts.add_rider(entity_1, entity_2, lin_vel_1.unwrap_or(math::Vec2::ZERO));
+                            }
+
+                            if kinematic_2 && normal.y > 0.0 && translation_1.as_deref().is_some_and(|t| !t.kinematic) {
+                                contacts.add_rider(entity_2, entity_1, lin_vel_2.unwrap_or(math::Vec2::ZERO));
+                            }
+                        }
+
                         Ok(())
                     };
 
@@ -491,7 +669,10 @@ pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usiz
             }
         }
 
-        if solved {
+        // joints are solved after the contacts of the same iteration, so each one can correct the other
+        let joints_solved = physics::solve_joints_iter(world, step) < physics::JOINT_TOLERANCE;
+
+        if solved && joints_solved {
             break;
         } else {
             // compute next_states
@@ -502,13 +683,23 @@ pub fn resolve_collisions<const N: usize>(world: &mut ecs::World<N>, iters: usiz
                     continue;
                 }
 
+                if matches!(states[idx], State::Sleeping) && matches!(next_states[idx], State::Invalid) {
+                    // nothing hit the sleeping entity
+                    next_states[idx] = State::Sleeping;
+                    continue;
+                }
+
                 next_states[idx] = match (next_states[idx], get_state(world, ents[idx])) {
-                    (State::Far, State::Active) => State::Far, // if it's far but it's not static or still, keep far
+                    (State::Far, State::Active) if joints_solved => State::Far, // if it's far but it's not static or still, keep far
+                    // joints may have moved a far entity towards something else
                     (_, state) => state,
                 };
             }
         }
     }
 
+    contacts.finalize(|entity_1, entity_2| !(resting_ents.contains(&entity_1) && resting_ents.contains(&entity_2)));
+    physics::update_sleeping(world, contacts, step);
+
     Ok(())
 }
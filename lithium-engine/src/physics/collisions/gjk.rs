@@ -0,0 +1,381 @@
+use crate::math::{Support, ToHitBox};
+use crate::{base, math, physics};
+
+const GJK_MAX_ITERATIONS: usize = 32;
+const EPA_MAX_ITERATIONS: usize = 32;
+const GJK_TOLERANCE: f32 = 1e-5; // relative improvement of the distance below which gjk stops
+const EPA_TOLERANCE: f32 = 1e-3; // improvement of the depth below which epa stops
+
+/// result of gjk and epa on 2 convex geometries
+#[derive(Debug, Clone, Copy)]
+pub struct GjkCollision {
+    pub normal: math::Vec2,  // points from geometry_1 to geometry_2
+    pub depth: f32,          // penetration depth, when the geometries are apart it is minus their distance
+    pub point_1: math::Vec2, // point of geometry_1 closest to geometry_2, or deepest inside it
+    pub point_2: math::Vec2, // point of geometry_2 closest to geometry_1, or deepest inside it
+}
+
+/// vertex of the minkowski difference geometry_1 - geometry_2, keeping the points it comes from
+#[derive(Debug, Clone, Copy)]
+struct MinkowskiVert {
+    point: math::Vec2,
+    point_1: math::Vec2,
+    point_2: math::Vec2,
+}
+
+impl MinkowskiVert {
+    #[inline]
+    fn support<T, U>(geometry_1: &T, geometry_2: &U, dir: math::Vec2) -> Self
+    where
+        T: Support + ?Sized,
+        U: Support + ?Sized,
+    {
+        let point_1 = geometry_1.support(dir);
+        let point_2 = geometry_2.support(dir.rev());
+
+        Self {
+            point: point_1.sub(point_2),
+            point_1,
+            point_2,
+        }
+    }
+
+    /// vertex between self and other, t goes from 0 (self) to 1 (other)
+    #[inline]
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            point: self.point.add(other.point.sub(self.point).scale(t)),
+            point_1: self.point_1.add(other.point_1.sub(self.point_1).scale(t)),
+            point_2: self.point_2.add(other.point_2.sub(self.point_2).scale(t)),
+        }
+    }
+}
+
+/// simplex of gjk, made of up to 3 vertices of the minkowski difference
+struct Simplex {
+    verts: [MinkowskiVert; 3],
+    len: usize,
+}
+
+impl Simplex {
+    #[inline]
+    fn new(vert: MinkowskiVert) -> Self {
+        Self { verts: [vert; 3], len: 1 }
+    }
+
+    #[inline]
+    fn push(&mut self, vert: MinkowskiVert) {
+        self.verts[self.len] = vert;
+        self.len += 1;
+    }
+
+    /// closest point of a segment to the origin, the simplex is reduced to the vertices needed to express it
+    fn closest_on_segment(a: MinkowskiVert, b: MinkowskiVert) -> (MinkowskiVert, Self) {
+        let edge = b.point.sub(a.point);
+        let square_len = edge.square_mag();
+
+        if square_len < math::EPS_SQR {
+            return (a, Self::new(a));
+        }
+
+        let t = -a.point.dot(edge) / square_len;
+
+        if t <= 0.0 {
+            (a, Self::new(a))
+        } else if t >= 1.0 {
+            (b, Self::new(b))
+        } else {
+            (a.lerp(b, t), Self { verts: [a, b, b], len: 2 })
+        }
+    }
+
+    /// closest point of the simplex to the origin, returns None if the origin is inside the simplex, otherwise the
+    /// simplex is reduced to the vertices needed to express the closest point
+    fn reduce(&mut self) -> Option<MinkowskiVert> {
+        match self.len {
+            1 => Some(self.verts[0]),
+            2 => {
+                let (closest, reduced) = Self::closest_on_segment(self.verts[0], self.verts[1]);
+                *self = reduced;
+                Some(closest)
+            }
+            _ => {
+                let [a, b, c] = self.verts;
+                let area = a.point.signed_area(b.point, c.point);
+
+                if area.abs() >= math::EPS {
+                    // the origin is inside if it is on the same side of every edge
+                    let side_ab = a.point.signed_area(b.point, math::Vec2::ZERO) * area;
+                    let side_bc = b.point.signed_area(c.point, math::Vec2::ZERO) * area;
+                    let side_ca = c.point.signed_area(a.point, math::Vec2::ZERO) * area;
+
+                    if side_ab >= 0.0 && side_bc >= 0.0 && side_ca >= 0.0 {
+                        return None;
+                    }
+                }
+
+                // the closest point is on one of the edges
+                let mut best: Option<(MinkowskiVert, Self)> = None;
+
+                for (p, q) in [(a, b), (b, c), (c, a)] {
+                    let (closest, reduced) = Self::closest_on_segment(p, q);
+
+                    if best
+                        .as_ref()
+                        .is_none_or(|(best_closest, _)| closest.point.square_mag() < best_closest.point.square_mag())
+                    {
+                        best = Some((closest, reduced));
+                    }
+                }
+
+                let (closest, reduced) = best.unwrap();
+                *self = reduced;
+                Some(closest)
+            }
+        }
+    }
+}
+
+/// checks the distance or the penetration of 2 convex geometries only through their support functions, using gjk when
+/// they are apart and epa when they overlap
+pub fn gjk_collision<T, U>(geometry_1: &T, geometry_2: &U) -> GjkCollision
+where
+    T: Support + ?Sized,
+    U: Support + ?Sized,
+{
+    let first = MinkowskiVert::support(geometry_1, geometry_2, math::Vec2::new(1.0, 0.0));
+    let mut simplex = Simplex::new(first);
+    let mut closest = first;
+
+    for _ in 0..GJK_MAX_ITERATIONS {
+        let square_dist = closest.point.square_mag();
+
+        if square_dist < math::EPS_SQR {
+            // the origin is on the simplex, the geometries are touching
+            break;
+        }
+
+        let vert = MinkowskiVert::support(geometry_1, geometry_2, closest.point.rev());
+
+        if square_dist - closest.point.dot(vert.point) <= GJK_TOLERANCE * square_dist {
+            // no vertex gets closer to the origin, the geometries are apart
+            let dist = square_dist.sqrt();
+
+            return GjkCollision {
+                normal: closest.point.rev().scale(1.0 / dist),
+                depth: -dist,
+                point_1: closest.point_1,
+                point_2: closest.point_2,
+            };
+        }
+
+        simplex.push(vert);
+
+        match simplex.reduce() {
+            Some(new_closest) => closest = new_closest,
+            None => break, // the origin is inside the simplex, the geometries overlap
+        }
+    }
+
+    epa(geometry_1, geometry_2, simplex)
+}
+
+/// expands the simplex containing the origin towards the boundary of the minkowski difference to find the penetration
+fn epa<T, U>(geometry_1: &T, geometry_2: &U, simplex: Simplex) -> GjkCollision
+where
+    T: Support + ?Sized,
+    U: Support + ?Sized,
+{
+    let mut polytope: Vec<MinkowskiVert> = simplex.verts[..simplex.len].to_vec();
+
+    // the geometries only touch, grow the simplex to a triangle
+    if polytope.len() == 1 {
+        let vert = MinkowskiVert::support(geometry_1, geometry_2, math::Vec2::new(1.0, 0.0));
+
+        polytope.push(if vert.point.square_dist(polytope[0].point) >= math::EPS_SQR {
+            vert
+        } else {
+            MinkowskiVert::support(geometry_1, geometry_2, math::Vec2::new(-1.0, 0.0))
+        });
+    }
+
+    if polytope.len() == 2 {
+        let edge = polytope[1].point.sub(polytope[0].point);
+        let normal = math::Vec2::new(edge.y, -edge.x);
+        let offset = polytope[0].point.dot(normal);
+
+        let vert = MinkowskiVert::support(geometry_1, geometry_2, normal);
+        let vert = if vert.point.dot(normal) - offset > math::EPS {
+            vert
+        } else {
+            MinkowskiVert::support(geometry_1, geometry_2, normal.rev())
+        };
+
+        if (vert.point.dot(normal) - offset).abs() <= math::EPS {
+            // the minkowski difference is flat, so there is no penetration
+            let normal = if normal.approx_equal_zero() {
+                math::Vec2::new(1.0, 0.0)
+            } else {
+                normal.norm()
+            };
+            let (closest, _) = Simplex::closest_on_segment(polytope[0], polytope[1]);
+
+            return GjkCollision {
+                normal,
+                depth: 0.0,
+                point_1: closest.point_1,
+                point_2: closest.point_2,
+            };
+        }
+
+        polytope.push(vert);
+    }
+
+    // keep the polytope counterclockwise (positive signed area) so that the outward normal of an edge is on its right
+    if polytope[0].point.signed_area(polytope[1].point, polytope[2].point) < 0.0 {
+        polytope.swap(1, 2);
+    }
+
+    let mut best_idx = 0;
+    let mut best_normal = math::Vec2::new(1.0, 0.0);
+    let mut best_dist = f32::INFINITY;
+
+    for _ in 0..EPA_MAX_ITERATIONS {
+        // find the edge closest to the origin
+        best_dist = f32::INFINITY;
+
+        for idx in 0..polytope.len() {
+            let edge = polytope[(idx + 1) % polytope.len()].point.sub(polytope[idx].point);
+
+            if edge.square_mag() < math::EPS_SQR {
+                continue;
+            }
+
+            let normal = math::Vec2::new(edge.y, -edge.x).norm();
+            let dist = normal.dot(polytope[idx].point);
+
+            if dist < best_dist {
+                best_idx = idx;
+                best_normal = normal;
+                best_dist = dist;
+            }
+        }
+
+        let vert = MinkowskiVert::support(geometry_1, geometry_2, best_normal);
+
+        if vert.point.dot(best_normal) - best_dist <= EPA_TOLERANCE {
+            // the edge is on the boundary of the minkowski difference
+            break;
+        }
+
+        polytope.insert(best_idx + 1, vert);
+    }
+
+    // project the origin on the closest edge to find the points of the geometries
+    let a = polytope[best_idx];
+    let b = polytope[(best_idx + 1) % polytope.len()];
+    let edge = b.point.sub(a.point);
+    let t = (best_normal.scale(best_dist).sub(a.point).dot(edge) / edge.square_mag()).clamp(0.0, 1.0);
+    let closest = a.lerp(b, t);
+
+    GjkCollision {
+        normal: best_normal,
+        depth: best_dist.max(0.0),
+        point_1: closest.point_1,
+        point_2: closest.point_2,
+    }
+}
+
+/// convex parts of a shape with their hitboxes, a convex shape is a single part
+#[inline]
+fn cvx_parts(shape: &math::Shape) -> Result<Vec<(&dyn Support, math::HitBox)>, base::GeometryError> {
+    Ok(match shape {
+        math::Shape::CavePoly(cave_poly) => cave_poly
+            .cvx_polys()?
+            .iter()
+            .zip(cave_poly.cvx_hitboxes()?)
+            .map(|(cvx_poly, hitbox)| (cvx_poly as &dyn Support, hitbox.clone()))
+            .collect(),
+        _ => vec![(shape as &dyn Support, shape.to_hitbox())],
+    })
+}
+
+/// gjk result between a convex part of shape_1 and a convex part of shape_2, convex shapes are a single part with index 0
+#[derive(Debug, Clone, Copy)]
+pub struct GjkCaveCollision {
+    pub collision: GjkCollision,
+    pub cave_part_idx_1: usize,
+    pub cave_part_idx_2: usize,
+}
+
+/// collision between 2 shapes found by check_gjk(), like physics::SatCollision but keeping depth and closest points
+#[derive(Debug, Clone)]
+pub enum GjkShapeCollision {
+    Cvx(GjkCollision),
+    Cave(Vec<GjkCaveCollision>),
+}
+
+impl From<GjkShapeCollision> for physics::SatCollision {
+    /// keeps only the normals, so that the rest of the pipeline does not depend on the narrow phase
+    #[inline]
+    fn from(gjk_collision: GjkShapeCollision) -> Self {
+        match gjk_collision {
+            GjkShapeCollision::Cvx(collision) => physics::SatCollision::Cvx(physics::CvxCollision { normal: collision.normal }),
+            GjkShapeCollision::Cave(collisions) => physics::SatCollision::Cave(
+                collisions
+                    .into_iter()
+                    .map(|cave_collision| physics::CaveCollision {
+                        normal: cave_collision.collision.normal,
+                        cave_part_idx_1: cave_collision.cave_part_idx_1,
+                        cave_part_idx_2: cave_collision.cave_part_idx_2,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// checks if 2 shapes are colliding using gjk_collision(), concave polygons are tested through their convex parts;
+/// every collision keeps its normal, depth and closest points
+pub fn check_gjk(shape_1: &math::Shape, shape_2: &math::Shape) -> Result<Option<GjkShapeCollision>, base::GeometryError> {
+    #[inline]
+    fn colliding<T, U>(geometry_1: &T, geometry_2: &U) -> Option<GjkCollision>
+    where
+        T: Support + ?Sized,
+        U: Support + ?Sized,
+    {
+        let collision = gjk_collision(geometry_1, geometry_2);
+        if collision.depth >= -math::EPS { Some(collision) } else { None }
+    }
+
+    if !matches!(shape_1, math::Shape::CavePoly(_)) && !matches!(shape_2, math::Shape::CavePoly(_)) {
+        return Ok(colliding(shape_1, shape_2).map(GjkShapeCollision::Cvx));
+    }
+
+    let parts_1 = cvx_parts(shape_1)?;
+    let parts_2 = cvx_parts(shape_2)?;
+
+    let mut collisions = Vec::new();
+
+    for (idx_1, (part_1, hitbox_1)) in parts_1.iter().enumerate() {
+        for (idx_2, (part_2, hitbox_2)) in parts_2.iter().enumerate() {
+            if !physics::check_hitboxes(hitbox_1, hitbox_2) {
+                continue;
+            }
+
+            if let Some(collision) = colliding(*part_1, *part_2) {
+                collisions.push(GjkCaveCollision {
+                    collision,
+                    cave_part_idx_1: idx_1,
+                    cave_part_idx_2: idx_2,
+                });
+            }
+        }
+    }
+
+    Ok(if collisions.is_empty() {
+        None
+    } else {
+        Some(GjkShapeCollision::Cave(collisions))
+    })
+}
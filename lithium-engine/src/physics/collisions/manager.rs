@@ -0,0 +1,248 @@
+use crate::{ecs, math, physics};
+
+use std::mem;
+
+pub const SLEEP_LIN_VEL: f32 = 0.05;
+pub const SLEEP_ANG_VEL: f32 = 0.005;
+pub const TIME_TO_SLEEP: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorEventKind {
+    Enter, // the entity started overlapping the sensor during the last call
+    Stay,  // the entity was already overlapping the sensor
+    Exit,  // the entity stopped overlapping the sensor during the last call
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensorEvent {
+    pub sensor: ecs::Entity,
+    pub other: ecs::Entity,
+    pub kind: SensorEventKind,
+}
+
+/// keeps track of the contacts found by resolve_collisions(), owned by the user and passed to every call
+#[derive(Debug, Clone)]
+pub struct ContactManager {
+    pub sleep: bool,                        // whether islands are allowed to fall asleep
+    pub sleep_lin_vel: f32,                 // linear velocity below which a body is considered still
+    pub sleep_ang_vel: f32,                 // angular velocity below which a body is considered still
+    pub time_to_sleep: f32,                 // time an entire island must stay still before falling asleep
+    pub narrow_phase: physics::NarrowPhase, // algorithm used to check if 2 shapes are colliding
+    pairs: Vec<(ecs::Entity, ecs::Entity)>, // touching pairs of the last call, sorted and without duplicates
+    prev_pairs: Vec<(ecs::Entity, ecs::Entity)>,
+    overlaps: Vec<(ecs::Entity, ecs::Entity)>, // (sensor, other) pairs of the last call, sorted and without duplicates
+    prev_overlaps: Vec<(ecs::Entity, ecs::Entity)>,
+    sensor_events: Vec<SensorEvent>,
+    riders: Vec<(ecs::Entity, ecs::Entity, math::Vec2)>, // (kinematic, rider, kinematic lin_vel) of the last call
+    impacts: Vec<(ecs::Entity, f32, math::Vec2)>,        // (entity, impulse, contact point) of the last call
+}
+
+impl ContactManager {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            sleep: true,
+            sleep_lin_vel: SLEEP_LIN_VEL,
+            sleep_ang_vel: SLEEP_ANG_VEL,
+            time_to_sleep: TIME_TO_SLEEP,
+            narrow_phase: physics::NarrowPhase::Sat,
+            pairs: Vec::new(),
+            prev_pairs: Vec::new(),
+            overlaps: Vec::new(),
+            prev_overlaps: Vec::new(),
+            sensor_events: Vec::new(),
+            riders: Vec::new(),
+            impacts: Vec::new(),
+        }
+    }
+
+    /// touching pairs, pairs of resting entities that were not checked during the last call are kept from the previous
+    /// ones so that islands stay connected while they sleep
+    #[inline]
+    pub fn pairs(&self) -> &[(ecs::Entity, ecs::Entity)] {
+        &self.pairs
+    }
+
+    #[inline]
+    pub fn touching(&self, entity_1: ecs::Entity, entity_2: ecs::Entity) -> bool {
+        self.pairs.binary_search(&Self::ordered(entity_1, entity_2)).is_ok()
+    }
+
+    /// (sensor, other) pairs overlapping during the last call
+    #[inline]
+    pub fn overlaps(&self) -> &[(ecs::Entity, ecs::Entity)] {
+        &self.overlaps
+    }
+
+    /// enter, stay and exit events of the last call, sorted by sensor
+    #[inline]
+    pub fn sensor_events(&self) -> &[SensorEvent] {
+        &self.sensor_events
+    }
+
+    /// total normal impulse received by each entity during the last call and the contact point of its strongest
+    /// contact, sorted by entity
+    #[inline]
+    pub fn impacts(&self) -> &[(ecs::Entity, f32, math::Vec2)] {
+        &self.impacts
+    }
+
+    /// total normal impulse received by an entity during the last call
+    #[inline]
+    pub fn impulse(&self, entity: ecs::Entity) -> f32 {
+        self.impacts
+            .binary_search_by_key(&entity, |&(other, _, _)| other)
+            .map_or(0.0, |idx| self.impacts[idx].1)
+    }
+
+    /// kinematic entity the rider was resting on during the last call
+    #[inline]
+    pub fn platform(&self, rider: ecs::Entity) -> Option<ecs::Entity> {
+        self.riders
+            .iter()
+            .find(|&&(_, other, _)| other == rider)
+            .map(|&(kinematic, _, _)| kinematic)
+    }
+
+    #[inline]
+    fn ordered(entity_1: ecs::Entity, entity_2: ecs::Entity) -> (ecs::Entity, ecs::Entity) {
+        if entity_1 <= entity_2 {
+            (entity_1, entity_2)
+        } else {
+            (entity_2, entity_1)
+        }
+    }
+
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.sensor_events.clear();
+        self.impacts.clear();
+
+        mem::swap(&mut self.pairs, &mut self.prev_pairs);
+        self.pairs.clear();
+
+        mem::swap(&mut self.overlaps, &mut self.prev_overlaps);
+        self.overlaps.clear();
+    }
+
+    #[inline]
+    pub(crate) fn add_overlap(&mut self, sensor: ecs::Entity, other: ecs::Entity) {
+        self.overlaps.push((sensor, other));
+    }
+
+    #[inline]
+    pub(crate) fn add_rider(&mut self, kinematic: ecs::Entity, rider: ecs::Entity, lin_vel: math::Vec2) {
+        if !self.riders.iter().any(|&(k, r, _)| k == kinematic && r == rider) {
+            self.riders.push((kinematic, rider, lin_vel));
+        }
+    }
+
+    /// gives every rider the change in velocity of the kinematic entity it was resting on, so that riders inherit
+    /// its motion even when it suddenly changes direction; sleeping riders are kept since they are never checked
+    pub(crate) fn carry_riders<const N: usize>(&mut self, world: &mut ecs::World<N>) {
+        for (kinematic, rider, lin_vel) in self.riders.iter_mut() {
+            let Some(kinematic_lin_vel) = world.engine.translation.get(*kinematic).map(|translation| translation.lin_vel) else {
+                continue;
+            };
+
+            let delta = kinematic_lin_vel.sub(*lin_vel);
+            *lin_vel = kinematic_lin_vel;
+
+            if !delta.approx_equal_zero()
+                && let Some(translation) = world.engine.translation.get_mut(*rider)
+            {
+                translation.wake();
+                translation.lin_vel.add_mut(delta);
+            }
+        }
+
+        self.riders
+            .retain(|&(_, rider, _)| world.engine.translation.get(rider).is_some_and(|translation| translation.sleeping));
+    }
+
+    #[inline]
+    pub(crate) fn add_impact(&mut self, entity: ecs::Entity, impulse: f32, point: math::Vec2) {
+        self.impacts.push((entity, impulse, point));
+    }
+
+    #[inline]
+    pub(crate) fn add_pair(&mut self, entity_1: ecs::Entity, entity_2: ecs::Entity) {
+        self.pairs.push(Self::ordered(entity_1, entity_2));
+    }
+
+    /// sorts and removes duplicated pairs and computes sensor events, must be called once all the pairs have been added;
+    /// previous pairs and overlaps between entities that were never checked against each other (checked returns false)
+    /// are kept
+    pub(crate) fn finalize<F>(&mut self, checked: F)
+    where
+        F: Fn(ecs::Entity, ecs::Entity) -> bool,
+    {
+        for &(entity_1, entity_2) in self.prev_pairs.iter() {
+            if !checked(entity_1, entity_2) {
+                self.pairs.push((entity_1, entity_2));
+            }
+        }
+
+        self.pairs.sort_unstable();
+        self.pairs.dedup();
+
+        // impacts of the same entity are summed, the strongest one comes first and gives the contact point
+        self.impacts.sort_unstable_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)));
+        self.impacts.dedup_by(|impact, first| {
+            if impact.0 != first.0 {
+                return false;
+            }
+
+            first.1 += impact.1;
+            true
+        });
+
+        for &(sensor, other) in self.prev_overlaps.iter() {
+            if !checked(sensor, other) {
+                self.overlaps.push((sensor, other));
+            }
+        }
+
+        self.overlaps.sort_unstable();
+        self.overlaps.dedup();
+
+        // both vectors are sorted, so they can be merged in a single pass
+        let (mut idx, mut prev_idx) = (0, 0);
+
+        while idx < self.overlaps.len() || prev_idx < self.prev_overlaps.len() {
+            let (pair, kind) = match (self.overlaps.get(idx), self.prev_overlaps.get(prev_idx)) {
+                (Some(&pair), Some(&prev_pair)) if pair == prev_pair => {
+                    idx += 1;
+                    prev_idx += 1;
+                    (pair, SensorEventKind::Stay)
+                }
+                (Some(&pair), Some(&prev_pair)) if pair < prev_pair => {
+                    idx += 1;
+                    (pair, SensorEventKind::Enter)
+                }
+                (Some(&pair), None) => {
+                    idx += 1;
+                    (pair, SensorEventKind::Enter)
+                }
+                (_, Some(&prev_pair)) => {
+                    prev_idx += 1;
+                    (prev_pair, SensorEventKind::Exit)
+                }
+                (None, None) => break,
+            };
+
+            self.sensor_events.push(SensorEvent {
+                sensor: pair.0,
+                other: pair.1,
+                kind,
+            });
+        }
+    }
+}
+
+impl Default for ContactManager {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,6 +1,15 @@
 use crate::{ecs, math};
 
-/// updates 2 entities' linear velocity vector after they collide
+/// world space velocity of a surface, it rotates with its entity
+#[inline]
+pub(crate) fn global_surface_vel(surface: &ecs::Surface, rot_mat: Option<&ecs::RotationMatrix>) -> math::Vec2 {
+    match rot_mat {
+        Some(rot_mat) => rot_mat.rot_mat.pre_mul_vec2_linear(surface.surface_vel),
+        None => surface.surface_vel,
+    }
+}
+
+/// updates 2 entities' linear velocity vector after they collide, returns the magnitude of the normal impulse
 pub(crate) fn compute_reaction(
     normal: math::Vec2,
     contact_point: math::Vec2,
@@ -12,7 +21,9 @@ pub(crate) fn compute_reaction(
     mut rotation_2: Option<&mut ecs::Rotation>,
     surface_1: &ecs::Surface,
     surface_2: &ecs::Surface,
-) {
+    rot_mat_1: Option<&ecs::RotationMatrix>,
+    rot_mat_2: Option<&ecs::RotationMatrix>,
+) -> f32 {
     let compute_translation_reaction = translation_1.is_some() || translation_2.is_some();
     let compute_rotation_reaction = rotation_1.is_some() || rotation_2.is_some();
 
@@ -32,10 +43,20 @@ pub(crate) fn compute_reaction(
         }
     }
 
-    // compute elast and friction
-    let elast = surface_1.elast.min(surface_2.elast);
-    let static_friction = (surface_1.static_friction * surface_2.static_friction).sqrt();
-    let kinetic_friction = (surface_1.kinetic_friction * surface_2.kinetic_friction).sqrt();
+    // compute elast, friction and rolling resistance
+    let (combine_1, combine_2) = (surface_1.combine, surface_2.combine);
+    let elast = combine_1.elast.combine(combine_2.elast, surface_1.elast, surface_2.elast);
+    let static_friction = combine_1
+        .friction
+        .combine(combine_2.friction, surface_1.static_friction, surface_2.static_friction);
+    let kinetic_friction = combine_1
+        .friction
+        .combine(combine_2.friction, surface_1.kinetic_friction, surface_2.kinetic_friction);
+    let rolling_resistance = combine_1.rolling_resistance.combine(
+        combine_2.rolling_resistance,
+        surface_1.rolling_resistance,
+        surface_2.rolling_resistance,
+    );
 
     if compute_translation_reaction || compute_rotation_reaction {
         // extract lin_vel and inv_mass
@@ -59,20 +80,28 @@ pub(crate) fn compute_reaction(
         let arm_1 = contact_point.sub(mass_center_1);
         let arm_2 = contact_point.sub(mass_center_2);
 
+        // kinematic entities are not rotated by impulses either
+        let kinematic_1 = translation_1.as_deref().is_some_and(|translation_1| translation_1.kinematic);
+        let kinematic_2 = translation_2.as_deref().is_some_and(|translation_2| translation_2.kinematic);
+
         // extract ang_vel and inv_inertia
         let (ang_vel_1, inv_inertia_1) = {
-            if let Some(rotation_1) = rotation_1.as_deref() {
+            if let Some(rotation_1) = rotation_1.as_deref()
+                && !kinematic_1
+            {
                 (rotation_1.ang_vel, rotation_1.inv_inertia())
             } else {
-                (0.0, 0.0)
+                (rotation_1.as_deref().map_or(0.0, |rotation_1| rotation_1.ang_vel), 0.0)
             }
         };
 
         let (ang_vel_2, inv_inertia_2) = {
-            if let Some(rotation_2) = rotation_2.as_deref() {
+            if let Some(rotation_2) = rotation_2.as_deref()
+                && !kinematic_2
+            {
                 (rotation_2.ang_vel, rotation_2.inv_inertia())
             } else {
-                (0.0, 0.0)
+                (rotation_2.as_deref().map_or(0.0, |rotation_2| rotation_2.ang_vel), 0.0)
             }
         };
 
@@ -82,6 +111,11 @@ pub(crate) fn compute_reaction(
         let normal_inv_mass_inertia =
             inv_mass_1 + inv_mass_2 + inv_inertia_1 * math::pow2(arm_1.cross(normal)) + inv_inertia_2 * math::pow2(arm_2.cross(normal));
 
+        if normal_inv_mass_inertia < math::EPS {
+            // both entities have infinite mass (e.g. 2 kinematic entities), nothing can be pushed
+            return 0.0;
+        }
+
         // relative velocity from shape_1 to shape_2, vector from vel_1 to vel_2
         let rel_vel = vel_2.sub(vel_1);
         // normal_rel_vel_mag is basically rel_vel projected on the normal axis
@@ -91,7 +125,7 @@ pub(crate) fn compute_reaction(
         if normal_rel_vel_mag >= math::EPS {
             // object are not getting closer
             // careful here, since objects resting on other objects have a negative normal_rel_vel_mag very close to 0
-            return;
+            return 0.0;
         };
 
         // so here are the steps to compute impulse (not yet updated for angular velocity):
@@ -151,8 +185,8 @@ pub(crate) fn compute_reaction(
         let lin_vel_1 = if let Some(translation_1) = translation_1.as_deref_mut() {
             translation_1.lin_vel.sub_mut(impulse_vector.scale(inv_mass_1)); // here we subtract the delta_lin_vel (see above why)
 
-            // round linear velocity to 0 for object 1
-            if translation_1.rest {
+            // round linear velocity to 0 for object 1, kinematic entities keep the velocity they were given
+            if translation_1.rest && !translation_1.kinematic {
                 if translation_1.lin_vel.x.abs() <= 0.1 {
                     translation_1.lin_vel.x = 0.0;
                 }
@@ -170,8 +204,8 @@ pub(crate) fn compute_reaction(
         let lin_vel_2 = if let Some(translation_2) = translation_2.as_deref_mut() {
             translation_2.lin_vel.add_mut(impulse_vector.scale(inv_mass_2)); // here we add the delta_lin_vel (see above why)
 
-            // round linear velocity to 0 for object 2
-            if translation_2.rest {
+            // round linear velocity to 0 for object 2, kinematic entities keep the velocity they were given
+            if translation_2.rest && !translation_2.kinematic {
                 if translation_2.lin_vel.x.abs() <= 0.1 {
                     translation_2.lin_vel.x = 0.0;
                 }
@@ -207,11 +241,41 @@ pub(crate) fn compute_reaction(
             0.0
         };
 
+        // rolling resistance is an angular impulse slowing down the relative rotation, bounded by the normal impulse like
+        // friction; its lever arm is the distance of the contact point from the center of mass of the rolling entity
+        let ang_inv_inertia = inv_inertia_1 + inv_inertia_2;
+
+        let (ang_vel_1, ang_vel_2) = if rolling_resistance > 0.0 && ang_inv_inertia > math::EPS {
+            let radius_1 = if inv_inertia_1 > 0.0 { arm_1.mag() } else { 0.0 };
+            let radius_2 = if inv_inertia_2 > 0.0 { arm_2.mag() } else { 0.0 };
+            let max_rolling = rolling_resistance * impulse.abs() * radius_1.max(radius_2);
+
+            let rolling_impulse = (-(ang_vel_2 - ang_vel_1) / ang_inv_inertia).clamp(-max_rolling, max_rolling);
+
+            if let Some(rotation_1) = rotation_1.as_deref_mut() {
+                rotation_1.ang_vel -= inv_inertia_1 * rolling_impulse;
+            }
+
+            if let Some(rotation_2) = rotation_2.as_deref_mut() {
+                rotation_2.ang_vel += inv_inertia_2 * rolling_impulse;
+            }
+
+            (
+                ang_vel_1 - inv_inertia_1 * rolling_impulse,
+                ang_vel_2 + inv_inertia_2 * rolling_impulse,
+            )
+        } else {
+            (ang_vel_1, ang_vel_2)
+        };
+
         // recompute rel_vel and normal_rel_vel_mag
         let vel_1 = lin_vel_1.add(arm_1.cross_scalar(ang_vel_1));
         let vel_2 = lin_vel_2.add(arm_2.cross_scalar(ang_vel_2));
 
-        let rel_vel = vel_2.sub(vel_1);
+        // moving surfaces (e.g. conveyor belts) drag the other entity as if their material was moving
+        let surface_rel_vel = global_surface_vel(surface_2, rot_mat_2).sub(global_surface_vel(surface_1, rot_mat_1));
+
+        let rel_vel = vel_2.sub(vel_1).add(surface_rel_vel);
 
         let normal_rel_vel_mag = rel_vel.dot(normal);
 
@@ -222,7 +286,7 @@ pub(crate) fn compute_reaction(
 
         if tangent_rel_vel_mag < math::EPS {
             // no tangential slip, so nothing to correct
-            return;
+            return impulse.abs();
         }
 
         // tangent_unit is tangent_rel_lin_vel normalized
@@ -260,5 +324,9 @@ pub(crate) fn compute_reaction(
         if let Some(rotation_2) = rotation_2.as_deref_mut() {
             rotation_2.ang_vel += inv_inertia_2 * arm_2.cross(friction_impulse_vector);
         };
+
+        impulse.abs()
+    } else {
+        0.0
     }
 }
@@ -0,0 +1,270 @@
+use crate::math::{ApplyTransformationShape, Centroid, SatCompatible};
+use crate::{base, ecs, math, physics};
+
+/// an entity broken by apply_all_destruction() and the entities spawned in its place
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fracture {
+    pub entity: ecs::Entity,
+    pub fragments: Vec<ecs::Entity>,
+}
+
+/// second moment of area of a polygon around the origin, signed like its area
+fn second_moment(verts: &[math::Vec2]) -> f32 {
+    let mut moment = 0.0;
+
+    for (a, b) in physics::edges(verts) {
+        moment += a.cross(b) * (a.dot(a) + a.dot(b) + b.dot(b));
+    }
+
+    moment / 12.0
+}
+
+/// area and second moment of area around the centroid of a shape, None for shapes without an area
+pub(crate) fn area_moment(shape: &math::Shape) -> Option<(f32, f32)> {
+    if let math::Shape::Circle(circle) = shape {
+        let area = std::f32::consts::PI * math::pow2(circle.radius());
+        return Some((area, 0.5 * area * math::pow2(circle.radius())));
+    }
+
+    let (mut area, mut moment, mut weighted_centroid) = (0.0, 0.0, math::Vec2::ZERO);
+
+    // holes are wound the other way, so their area and moment are subtracted
+    for ring in math::rings(shape).ok()? {
+        let (ring_area, ring_centroid) = physics::area_centroid(&ring);
+        area += ring_area;
+        moment += second_moment(&ring);
+        weighted_centroid.add_mut(ring_centroid.scale(ring_area));
+    }
+
+    if area.abs() < math::EPS {
+        return None;
+    }
+
+    let centroid = weighted_centroid.scale(1.0 / area);
+
+    Some((area.abs(), (moment - area * centroid.dot(centroid)).abs()))
+}
+
+/// splits a convex polygon with the line through point along dir, returns the pieces on the left and on the right of the
+/// line (either one can be empty), both keep the winding of the polygon
+fn split(verts: &[math::Vec2], point: math::Vec2, dir: math::Vec2) -> (Vec<math::Vec2>, Vec<math::Vec2>) {
+    let side = |vert: math::Vec2| dir.cross(vert.sub(point));
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+
+    for (prev, curr) in physics::edges(verts) {
+        let (side_prev, side_curr) = (side(prev), side(curr));
+
+        if (side_prev > math::EPS && side_curr < -math::EPS) || (side_prev < -math::EPS && side_curr > math::EPS) {
+            // the edge crosses the line, both pieces get the intersection
+            let intersection = prev.add(curr.sub(prev).scale(side_prev / (side_prev - side_curr)));
+            left.push(intersection);
+            right.push(intersection);
+        }
+
+        if side_curr >= -math::EPS {
+            left.push(curr);
+        }
+
+        if side_curr <= math::EPS {
+            right.push(curr);
+        }
+    }
+
+    (left, right)
+}
+
+/// turns the vertices of a convex fragment into the simplest shape describing it
+fn fragment_shape(verts: &mut [math::Vec2]) -> Option<math::Shape> {
+    let cvx_poly = math::CvxPoly::from_hull(verts).ok()?;
+
+    match *cvx_poly.verts() {
+        [a, b, c] => math::Triangle::new_checked(a, b, c).ok().map(math::Shape::Triangle),
+        [a, b, c, d] => math::Quad::new_checked(a, b, c, d).ok().map(math::Shape::Quad),
+        _ => Some(math::Shape::CvxPoly(cvx_poly)),
+    }
+}
+
+/// adds the impulses received during the last call to resolve_collisions() to the damage of every breakable entity, then
+/// splits the entities whose damage reached the threshold, must be called right after resolve_collisions()
+///
+/// the body is split into its convex pieces (see CavePoly::cvx_polys()) which are then cut by lines running from the
+/// strongest impact toward the mass center; every fragment becomes a new entity with the same surface, material and
+/// collision filter, a mass and inertia proportional to its area, and the velocity of the broken entity at its position,
+/// while the broken entity loses all its engine components; segments and circles never break
+pub fn apply_all_destruction<const N: usize>(
+    world: &mut ecs::World<N>,
+    entity_manager: &mut ecs::EntityManager,
+    contacts: &physics::ContactManager,
+) -> Result<Vec<Fracture>, base::GeometryError> {
+    let mut broken = Vec::new();
+
+    for (entity, breakable) in world.engine.breakable.iter_mut() {
+        let impulse = contacts.impulse(entity);
+
+        if impulse > 0.0 && impulse >= breakable.min_impulse {
+            breakable.damage += impulse;
+        }
+
+        if breakable.damage >= breakable.threshold {
+            broken.push(entity);
+        }
+    }
+
+    let mut fractures = Vec::with_capacity(broken.len());
+
+    for entity in broken {
+        let Some(breakable) = world.engine.breakable.get(entity).cloned() else {
+            continue;
+        };
+
+        let Some(shape) = physics::apply_all_trans(world, entity) else {
+            continue;
+        };
+
+        let pieces: Vec<Vec<math::Vec2>> = match &shape {
+            math::Shape::Triangle(triangle) => vec![vec![triangle.a(), triangle.b(), triangle.c()]],
+            math::Shape::Quad(quad) => vec![vec![quad.a(), quad.b(), quad.c(), quad.d()]],
+            math::Shape::CvxPoly(cvx_poly) => vec![cvx_poly.verts().to_vec()],
+            math::Shape::CavePoly(_) => match shape.split_cave()? {
+                Some(cave_parts) => cave_parts.cvx_polys.iter().map(|cvx_poly| cvx_poly.verts().to_vec()).collect(),
+                None => continue,
+            },
+            math::Shape::Segment(_) | math::Shape::Circle(_) => continue,
+        };
+
+        let mass_center = shape.centroid();
+
+        // cut lines are evenly spaced in angle and cross halfway between the strongest impact and the mass center, so the
+        // cracks start from the impact without only chipping the corners off
+        let origin = contacts
+            .impacts()
+            .binary_search_by_key(&entity, |&(other, _, _)| other)
+            .map_or(mass_center, |idx| contacts.impacts()[idx].2.add(mass_center).scale(0.5));
+
+        let mut frags = pieces;
+
+        for cut in 0..breakable.cuts {
+            let angle = (cut as f32 + 0.5) * std::f32::consts::PI / breakable.cuts as f32;
+            let dir = math::Vec2::new(math::cos(angle), math::sin(angle));
+
+            frags = frags
+                .iter()
+                .flat_map(|frag| {
+                    let (left, right) = split(frag, origin, dir);
+                    [left, right]
+                })
+                .filter(|frag| frag.len() >= 3)
+                .collect();
+        }
+
+        // area and second moment of the whole body around its own centroid, used to share mass and inertia
+        let (mut total_area, mut total_moment, mut weighted_centroid) = (0.0, 0.0, math::Vec2::ZERO);
+
+        for frag in frags.iter() {
+            let (area, centroid) = physics::area_centroid(frag);
+            total_area += area;
+            total_moment += second_moment(frag);
+            weighted_centroid.add_mut(centroid.scale(area));
+        }
+
+        if total_area.abs() < math::EPS {
+            continue;
+        }
+
+        let total_centroid = weighted_centroid.scale(1.0 / total_area);
+        let total_moment = (total_moment - total_area * total_centroid.dot(total_centroid)).abs();
+        let total_area = total_area.abs();
+
+        let translation = world.engine.translation.get(entity).cloned();
+        let rotation = world.engine.rotation.get(entity).cloned();
+        let has_rot_mat = world.engine.rotation_matrix.get(entity).is_some();
+        let surface = world.engine.surface.get(entity).cloned();
+        let material = world.engine.material.get(entity).cloned();
+        let collision_filter = world.engine.collision_filter.get(entity).cloned();
+
+        let (lin_vel, ang_vel) = (
+            translation.as_ref().map_or(math::Vec2::ZERO, |translation| translation.lin_vel),
+            rotation.as_ref().map_or(0.0, |rotation| rotation.ang_vel),
+        );
+
+        let mut fragments = Vec::with_capacity(frags.len());
+
+        for mut frag in frags {
+            let (area, centroid) = physics::area_centroid(&frag);
+            let moment = (second_moment(&frag) - area * centroid.dot(centroid)).abs();
+            let area = area.abs();
+
+            if area < breakable.min_area.max(math::EPS) {
+                // debris is discarded
+                continue;
+            }
+
+            let Some(global_shape) = fragment_shape(&mut frag) else {
+                continue;
+            };
+
+            // fragments are stored around their own centroid, so they rotate around it
+            let pos = global_shape.centroid();
+            let Ok(local_shape) = global_shape.apply_vec2_checked(pos.rev()) else {
+                continue;
+            };
+
+            let fragment = entity_manager.create();
+
+            world.engine.transform.upsert(fragment, ecs::Transform::new(pos));
+            world.engine.body.upsert(fragment, ecs::Body::new(local_shape));
+
+            if has_rot_mat {
+                world
+                    .engine
+                    .rotation_matrix
+                    .upsert(fragment, ecs::RotationMatrix::new(math::Mat2x3::IDENTITY));
+            }
+
+            if let Some(mut translation) = translation.clone() {
+                translation.set_mass(translation.mass() * area / total_area);
+                translation.lin_vel = lin_vel.add(pos.sub(mass_center).cross_scalar(ang_vel));
+                translation.force = math::Vec2::ZERO;
+                translation.wake();
+                world.engine.translation.upsert(fragment, translation);
+            }
+
+            if let Some(mut rotation) = rotation.clone()
+                && total_moment > math::EPS
+                && moment > math::EPS
+            {
+                rotation.set_inertia(rotation.inertia() * moment / total_moment);
+                rotation.torque = 0.0;
+                world.engine.rotation.upsert(fragment, rotation);
+            }
+
+            if let Some(surface) = surface.clone() {
+                world.engine.surface.upsert(fragment, surface);
+            }
+
+            if let Some(material) = material.clone() {
+                world.engine.material.upsert(fragment, material);
+            }
+
+            if let Some(collision_filter) = collision_filter.clone() {
+                world.engine.collision_filter.upsert(fragment, collision_filter);
+            }
+
+            fragments.push(fragment);
+        }
+
+        world.engine.transform.remove(entity);
+        world.engine.rotation_matrix.remove(entity);
+        world.engine.translation.remove(entity);
+        world.engine.rotation.remove(entity);
+        world.engine.surface.remove(entity);
+        world.engine.body.remove(entity);
+        world.engine.material.remove(entity);
+        world.engine.collision_filter.remove(entity);
+        world.engine.breakable.remove(entity);
+
+        fractures.push(Fracture { entity, fragments });
+    }
+
+    Ok(fractures)
+}
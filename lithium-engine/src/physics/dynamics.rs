@@ -1,12 +1,15 @@
 use crate::math::geometry::ApplyTransformationShape;
-use crate::{ecs, math};
+use crate::{base, ecs, math, physics};
 
 // reset
 
 #[inline]
 pub fn reset_all_rest<const N: usize>(world: &mut ecs::World<N>) {
     for (_, translation) in world.engine.translation.iter_mut() {
-        translation.rest = false;
+        if !translation.sleeping {
+            // sleeping entities keep the rest state they had when they fell asleep
+            translation.rest = false;
+        }
     }
 }
 
@@ -17,10 +20,11 @@ pub fn set_all_force<const N: usize>(world: &mut ecs::World<N>, force: math::Vec
     }
 }
 
+/// sets the force of every entity so that it accelerates by lin_acc scaled by its gravity scale
 #[inline]
 pub fn set_all_lin_acc<const N: usize>(world: &mut ecs::World<N>, lin_acc: math::Vec2) {
     for (_, translation) in world.engine.translation.iter_mut() {
-        translation.force = lin_acc.scale(translation.mass());
+        translation.force = lin_acc.scale(translation.mass() * translation.gravity_scale);
     }
 }
 
@@ -40,10 +44,18 @@ pub fn set_all_ang_acc<const N: usize>(world: &mut ecs::World<N>, ang_acc: f32)
 
 // integration
 
+/// factor applied to a velocity each step by a damping, implicit so that it never reverses the velocity
+#[inline]
+fn damping_factor(damping: f32, step: f32) -> f32 {
+    1.0 / (1.0 + damping * step)
+}
+
 #[inline]
 pub fn integrate_all_pos<const N: usize>(world: &mut ecs::World<N>, step: f32) {
     for (entity, transform) in world.engine.transform.iter_mut() {
-        if let Some(ecs::Translation { lin_vel, .. }) = world.engine.translation.get(entity) {
+        if let Some(ecs::Translation { lin_vel, sleeping, .. }) = world.engine.translation.get(entity)
+            && !sleeping
+        {
             transform.pos.add_mut(lin_vel.scale(step));
         }
     }
@@ -54,6 +66,7 @@ pub fn integrate_all_rot_mat<const N: usize>(world: &mut ecs::World<N>, step: f3
     for (entity, rot_mat) in world.engine.rotation_matrix.iter_mut() {
         if let Some(ecs::Rotation { ang_vel, .. }) = world.engine.rotation.get(entity)
             && let Some(ecs::Body { centroid, .. }) = world.engine.body.get(entity)
+            && !world.engine.translation.get(entity).is_some_and(|translation| translation.sleeping)
         {
             _ = rot_mat.update_mut(math::Radians(ang_vel * step), rot_mat.rot_mat.pre_mul_vec2(*centroid));
         }
@@ -63,49 +76,205 @@ pub fn integrate_all_rot_mat<const N: usize>(world: &mut ecs::World<N>, step: f3
 #[inline]
 pub fn integrate_all_lin_vel<const N: usize>(world: &mut ecs::World<N>, step: f32) {
     for (_, translation) in world.engine.translation.iter_mut() {
-        translation.lin_vel.add_mut(translation.force.scale(translation.inv_mass() * step));
+        if !translation.sleeping {
+            translation.lin_vel.add_mut(translation.force.scale(translation.inv_mass() * step));
+
+            if !translation.kinematic {
+                translation.lin_vel = translation.lin_vel.scale(damping_factor(translation.lin_damping, step));
+            }
+        }
     }
 }
 
 #[inline]
 pub fn integrate_all_ang_vel<const N: usize>(world: &mut ecs::World<N>, step: f32) {
-    for (_, rotation) in world.engine.rotation.iter_mut() {
-        rotation.ang_vel += rotation.torque() * rotation.inv_inertia() * step;
+    for (entity, rotation) in world.engine.rotation.iter_mut() {
+        // kinematic entities keep the angular velocity they were given
+        if !world
+            .engine
+            .translation
+            .get(entity)
+            .is_some_and(|translation| translation.sleeping || translation.kinematic)
+        {
+            rotation.ang_vel += rotation.torque() * rotation.inv_inertia() * step;
+            rotation.ang_vel *= damping_factor(rotation.ang_damping, step);
+        }
+    }
+}
+
+/// numerical scheme used by integrate_all()
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Integrator {
+    #[default]
+    SymplecticEuler, // velocity first, then position with the new velocity, same as the integrate_all_* functions above
+    VelocityVerlet, // second order, forces are evaluated twice per step
+    Rk4,            // fourth order Runge-Kutta, forces are evaluated four times per step
+}
+
+/// advances in one call the velocities and positions of every entity with the given integrator
+///
+/// force_field(entity, pos, lin_vel) returns a force depending on the state of the entity and it is evaluated as many
+/// times as the integrator needs, while the force stored in the translation is considered constant during the step;
+/// kinematic entities simply move by their velocity, and torques are always considered constant
+///
+/// damping is applied to the velocity before moving with symplectic Euler (like the integrate_all_* functions) and at
+/// the end of the step with the other schemes
+pub fn integrate_all<const N: usize, F>(world: &mut ecs::World<N>, integrator: Integrator, step: f32, mut force_field: F)
+where
+    F: FnMut(ecs::Entity, math::Vec2, math::Vec2) -> math::Vec2,
+{
+    for (entity, transform) in world.engine.transform.iter_mut() {
+        let Some(translation) = world.engine.translation.get_mut(entity) else {
+            continue;
+        };
+
+        if translation.sleeping {
+            continue;
+        }
+
+        if translation.kinematic {
+            transform.pos.add_mut(translation.lin_vel.scale(step));
+            continue;
+        }
+
+        let pos = transform.pos;
+        let lin_vel = translation.lin_vel;
+        let force = translation.force;
+        let inv_mass = translation.inv_mass();
+        let damping = damping_factor(translation.lin_damping, step);
+
+        let mut lin_acc = |pos: math::Vec2, lin_vel: math::Vec2| force.add(force_field(entity, pos, lin_vel)).scale(inv_mass);
+
+        let (new_pos, new_lin_vel) = match integrator {
+            Integrator::SymplecticEuler => {
+                let new_lin_vel = lin_vel.add(lin_acc(pos, lin_vel).scale(step)).scale(damping);
+
+                (pos.add(new_lin_vel.scale(step)), new_lin_vel)
+            }
+            Integrator::VelocityVerlet => {
+                let lin_acc_1 = lin_acc(pos, lin_vel);
+                let new_pos = pos.add(lin_vel.scale(step)).add(lin_acc_1.scale(0.5 * step * step));
+
+                // velocity dependent forces are evaluated with the explicit Euler prediction of the velocity
+                let lin_acc_2 = lin_acc(new_pos, lin_vel.add(lin_acc_1.scale(step)));
+
+                (new_pos, lin_vel.add(lin_acc_1.add(lin_acc_2).scale(0.5 * step)).scale(damping))
+            }
+            Integrator::Rk4 => {
+                let half_step = 0.5 * step;
+
+                let k1_vel = lin_vel;
+                let k1_acc = lin_acc(pos, k1_vel);
+
+                let k2_vel = lin_vel.add(k1_acc.scale(half_step));
+                let k2_acc = lin_acc(pos.add(k1_vel.scale(half_step)), k2_vel);
+
+                let k3_vel = lin_vel.add(k2_acc.scale(half_step));
+                let k3_acc = lin_acc(pos.add(k2_vel.scale(half_step)), k3_vel);
+
+                let k4_vel = lin_vel.add(k3_acc.scale(step));
+                let k4_acc = lin_acc(pos.add(k3_vel.scale(step)), k4_vel);
+
+                let sixth_step = step / 6.0;
+
+                (
+                    pos.add(k1_vel.add(k2_vel.scale(2.0)).add(k3_vel.scale(2.0)).add(k4_vel).scale(sixth_step)),
+                    lin_vel
+                        .add(k1_acc.add(k2_acc.scale(2.0)).add(k3_acc.scale(2.0)).add(k4_acc).scale(sixth_step))
+                        .scale(damping),
+                )
+            }
+        };
+
+        transform.pos = new_pos;
+        translation.lin_vel = new_lin_vel;
+    }
+
+    for (entity, rotation) in world.engine.rotation.iter_mut() {
+        let (sleeping, kinematic) = world
+            .engine
+            .translation
+            .get(entity)
+            .map_or((false, false), |translation| (translation.sleeping, translation.kinematic));
+
+        if sleeping {
+            continue;
+        }
+
+        let (ang_acc, damping) = if kinematic {
+            (0.0, 1.0)
+        } else {
+            (rotation.torque * rotation.inv_inertia(), damping_factor(rotation.ang_damping, step))
+        };
+
+        // with a constant torque every scheme except symplectic Euler gives the exact solution
+        let (angle, new_ang_vel) = match integrator {
+            Integrator::SymplecticEuler => {
+                let new_ang_vel = (rotation.ang_vel + ang_acc * step) * damping;
+
+                (new_ang_vel * step, new_ang_vel)
+            }
+            Integrator::VelocityVerlet | Integrator::Rk4 => (
+                (rotation.ang_vel + 0.5 * ang_acc * step) * step,
+                (rotation.ang_vel + ang_acc * step) * damping,
+            ),
+        };
+
+        rotation.ang_vel = new_ang_vel;
+
+        if let Some(rot_mat) = world.engine.rotation_matrix.get_mut(entity)
+            && let Some(ecs::Body { centroid, .. }) = world.engine.body.get(entity)
+        {
+            _ = rot_mat.update_mut(math::Radians(angle), rot_mat.rot_mat.pre_mul_vec2(*centroid));
+        }
     }
 }
 
-// helpers wrappers
+// helpers wrappers, they also wake the entity up unless the force or torque is negligible
 
 #[inline]
 pub fn apply_lin_vel_axis<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, lin_vel: f32, axis: math::Axis) -> Option<()> {
-    world.engine.translation.get_mut(entity)?.apply_lin_vel_axis(lin_vel, axis);
+    let translation = world.engine.translation.get_mut(entity)?;
+    translation.wake();
+    translation.apply_lin_vel_axis(lin_vel, axis);
 
     Some(())
 }
 
 #[inline]
 pub fn apply_lin_vel<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, lin_vel: math::Vec2) -> Option<()> {
-    world.engine.translation.get_mut(entity)?.apply_lin_vel(lin_vel);
+    let translation = world.engine.translation.get_mut(entity)?;
+    translation.wake();
+    translation.apply_lin_vel(lin_vel);
 
     Some(())
 }
 
 #[inline]
 pub fn apply_force_axis<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, force: f32, axis: math::Axis) -> Option<()> {
-    world.engine.translation.get_mut(entity)?.apply_force_axis(force, axis);
+    let translation = world.engine.translation.get_mut(entity)?;
+    if force.abs() >= math::EPS {
+        translation.wake();
+    }
+    translation.apply_force_axis(force, axis);
 
     Some(())
 }
 
 #[inline]
 pub fn apply_force<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, force: math::Vec2) -> Option<()> {
-    world.engine.translation.get_mut(entity)?.apply_force(force);
+    let translation = world.engine.translation.get_mut(entity)?;
+    if !force.approx_equal_zero() {
+        translation.wake();
+    }
+    translation.apply_force(force);
 
     Some(())
 }
 
 #[inline]
 pub fn apply_ang_vel<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, ang_vel: f32) -> Option<()> {
+    _ = physics::wake(world, entity);
     world.engine.rotation.get_mut(entity)?.apply_ang_vel(ang_vel);
 
     Some(())
@@ -113,6 +282,9 @@ pub fn apply_ang_vel<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Ent
 
 #[inline]
 pub fn apply_torque<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, torque: f32) -> Option<()> {
+    if torque.abs() >= math::EPS {
+        _ = physics::wake(world, entity);
+    }
     world.engine.rotation.get_mut(entity)?.apply_torque(torque);
 
     Some(())
@@ -120,6 +292,76 @@ pub fn apply_torque<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Enti
 
 // extra helpers
 
+/// placement of an entity in world space, missing components count as the identity
+#[inline]
+pub fn transform_2d<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity) -> math::Transform2D {
+    let rotation_matrix = world.engine.rotation_matrix.get(entity);
+
+    match world.engine.transform.get(entity) {
+        Some(transform) => transform.to_transform_2d(rotation_matrix),
+        None => math::Transform2D::new(
+            rotation_matrix.map_or(math::Mat2x3::IDENTITY, |rotation_matrix| rotation_matrix.rot_mat.clone()),
+            math::Vec2::ZERO,
+        ),
+    }
+}
+
+/// rescales the body of an entity, mass and inertia are rescaled too so that the density does not change
+pub fn set_scale<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, scale: math::Vec2) -> Result<(), base::EngineError> {
+    let body = world
+        .engine
+        .body
+        .get_mut(entity)
+        .ok_or(base::ComponentError::ComponentNotFound(entity))?;
+
+    let old_area_moment = physics::area_moment(&body.shape);
+    body.set_scale(scale)?;
+    let new_area_moment = physics::area_moment(&body.shape);
+
+    if let (Some((old_area, old_moment)), Some((new_area, new_moment))) = (old_area_moment, new_area_moment) {
+        if let Some(translation) = world.engine.translation.get_mut(entity) {
+            translation.set_mass(translation.mass() * new_area / old_area);
+        }
+
+        if let Some(rotation) = world.engine.rotation.get_mut(entity) {
+            rotation.set_inertia(rotation.inertia() * new_moment / old_moment);
+        }
+    }
+
+    _ = physics::wake(world, entity);
+
+    Ok(())
+}
+
+/// world space position of the center of mass of an entity
+#[inline]
+pub fn mass_center<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity) -> Option<math::Vec2> {
+    let centroid = world.engine.body.get(entity)?.centroid();
+
+    Some(transform_2d(world, entity).to_world(centroid))
+}
+
+/// world space position and velocity of a point given in the local space of an entity
+#[inline]
+fn local_point_state<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity, point: math::Vec2) -> Option<(math::Vec2, math::Vec2)> {
+    let transform = world.engine.transform.get(entity)?;
+    let global_point = transform.to_transform_2d(world.engine.rotation_matrix.get(entity)).to_world(point);
+
+    let mut vel = world
+        .engine
+        .translation
+        .get(entity)
+        .map_or(math::Vec2::ZERO, |translation| translation.lin_vel);
+
+    if let Some(rotation) = world.engine.rotation.get(entity)
+        && let Some(mass_center) = mass_center(world, entity)
+    {
+        vel.add_mut(global_point.sub(mass_center).cross_scalar(rotation.ang_vel));
+    }
+
+    Some((global_point, vel))
+}
+
 #[inline]
 pub fn apply_force_at_point_axis<const N: usize>(
     world: &mut ecs::World<N>,
@@ -166,8 +408,91 @@ pub fn apply_force_at_point<const N: usize>(
     Some(())
 }
 
+/// applies a force at a point in world space, unlike apply_force_at_point() the torque is computed around the world
+/// space center of mass, so the rotation and position of the entity are taken into account
+#[inline]
+pub fn apply_force_at_world_point<const N: usize>(
+    world: &mut ecs::World<N>,
+    entity: ecs::Entity,
+    force: math::Vec2,
+    point: math::Vec2,
+) -> Option<()> {
+    let mass_center = mass_center(world, entity)?;
+    let arm = point.sub(mass_center);
+
+    apply_force(world, entity, force)?;
+
+    let torque = arm.cross(force);
+    apply_torque(world, entity, torque)?;
+
+    Some(())
+}
+
+/// adds a force at a point in world space without waking the entity up, entities without translation or rotation
+/// simply don't react
+#[inline]
+fn add_force_at_world_point<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, force: math::Vec2, point: math::Vec2) {
+    let Some(mass_center) = mass_center(world, entity) else {
+        return;
+    };
+
+    if let Some(translation) = world.engine.translation.get_mut(entity) {
+        translation.apply_force(force);
+    }
+
+    if let Some(rotation) = world.engine.rotation.get_mut(entity) {
+        rotation.apply_torque(point.sub(mass_center).cross(force));
+    }
+}
+
+/// applies the force of every spring to its entities, must be called after forces are reset and before integrating velocities
+pub fn apply_all_springs<const N: usize>(world: &mut ecs::World<N>) {
+    for idx in 0..world.engine.spring.get_ents().len() {
+        let spring_entity = world.engine.spring.get_ents()[idx];
+        let Some(spring) = world.engine.spring.get(spring_entity).cloned() else {
+            continue;
+        };
+
+        let Some((point_1, vel_1)) = local_point_state(world, spring.entity, spring.attach) else {
+            continue;
+        };
+
+        let (point_2, vel_2) = match spring.anchor {
+            ecs::SpringAnchor::Entity { entity, attach } => {
+                let Some(state) = local_point_state(world, entity, attach) else {
+                    continue;
+                };
+                state
+            }
+            ecs::SpringAnchor::World { point } => (point, math::Vec2::ZERO),
+        };
+
+        let delta = point_2.sub(point_1);
+        let length = delta.mag();
+
+        if length < math::EPS {
+            // direction is undefined
+            continue;
+        }
+
+        let dir = delta.scale(1.0 / length);
+
+        // hooke's law plus a damper acting along the spring, positive magnitude pulls the ends together
+        let magnitude = spring.stiffness * (length - spring.rest_length) + spring.damping * vel_2.sub(vel_1).dot(dir);
+        let force = dir.scale(magnitude);
+
+        // springs don't wake their entities, otherwise a spring holding a weight would keep it awake forever; sleeping
+        // entities ignore forces and their island is woken up as soon as one of its ends moves
+        add_force_at_world_point(world, spring.entity, force, point_1);
+
+        if let ecs::SpringAnchor::Entity { entity, .. } = spring.anchor {
+            add_force_at_world_point(world, entity, force.rev(), point_2);
+        }
+    }
+}
+
 #[inline]
-pub fn apply_all_trans<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity) -> Option<math::Shape> {
+pub fn apply_all_trans<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity) -> Option<math::Shape> {
     let shape = &world.engine.body.get(entity)?.shape;
 
     let transform = world.engine.transform.get(entity);
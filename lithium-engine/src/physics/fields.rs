@@ -0,0 +1,243 @@
+use crate::math::{Centroid, ToHitBox};
+use crate::{ecs, math, physics};
+
+/// signed area and centroid of a polygon, the area is positive if the vertices are counterclockwise in a y-up frame
+pub(crate) fn area_centroid(verts: &[math::Vec2]) -> (f32, math::Vec2) {
+    let mut area = 0.0;
+    let mut centroid = math::Vec2::ZERO;
+
+    for (a, b) in physics::edges(verts) {
+        let cross = a.cross(b);
+        area += cross;
+        centroid.add_mut(a.add(b).scale(cross));
+    }
+
+    if area.abs() < math::EPS {
+        return (0.0, math::Vec2::ZERO);
+    }
+
+    (0.5 * area, centroid.scale(1.0 / (3.0 * area)))
+}
+
+/// clips a polygon against a convex one (Sutherland-Hodgman), the polygon being clipped can be concave
+fn clip(verts: &[math::Vec2], cvx_verts: &[math::Vec2]) -> Vec<math::Vec2> {
+    let orientation = area_centroid(cvx_verts).0.signum();
+    let mut output = verts.to_vec();
+
+    for (a, b) in physics::edges(cvx_verts) {
+        if output.len() < 3 {
+            return Vec::new();
+        }
+
+        let edge = b.sub(a);
+        let inside = |point: math::Vec2| edge.cross(point.sub(a)) * orientation >= 0.0;
+        let intersection = |p: math::Vec2, q: math::Vec2| p.add(q.sub(p).scale(edge.cross(a.sub(p)) / edge.cross(q.sub(p))));
+
+        let input = std::mem::take(&mut output);
+
+        for (prev, curr) in physics::edges(&input) {
+            match (inside(prev), inside(curr)) {
+                (true, true) => output.push(curr),
+                (true, false) => output.push(intersection(prev, curr)),
+                (false, true) => {
+                    output.push(intersection(prev, curr));
+                    output.push(curr);
+                }
+                (false, false) => (),
+            }
+        }
+    }
+
+    output
+}
+
+/// area and centroid of a shape given as rings, the area of the holes that follow the outline is subtracted
+fn rings_area_centroid(rings: &[Vec<math::Vec2>]) -> (f32, math::Vec2) {
+    let mut area = 0.0;
+    let mut centroid = math::Vec2::ZERO;
+
+    for (idx, ring) in rings.iter().enumerate() {
+        let (ring_area, ring_centroid) = area_centroid(ring);
+        let ring_area = if idx == 0 { ring_area.abs() } else { -ring_area.abs() };
+
+        area += ring_area;
+        centroid.add_mut(ring_centroid.scale(ring_area));
+    }
+
+    if area < math::EPS {
+        return (0.0, math::Vec2::ZERO);
+    }
+
+    (area, centroid.scale(1.0 / area))
+}
+
+/// submerged area of an entity and its centroid, the region is given as convex pieces
+fn submerged<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity, pieces: &[Vec<math::Vec2>]) -> Option<(f32, f32, math::Vec2)> {
+    let shape = physics::apply_all_trans(world, entity)?;
+    let rings = physics::outline_rings(&shape);
+
+    if rings.first().is_none_or(|outline| outline.len() < 3) {
+        // segments and circles don't float
+        return None;
+    }
+
+    let total_area = rings_area_centroid(&rings).0;
+    let mut area = 0.0;
+    let mut centroid = math::Vec2::ZERO;
+
+    for piece in pieces {
+        // the holes are clipped as well, so the water inside them doesn't count
+        let clipped: Vec<Vec<math::Vec2>> = rings.iter().map(|ring| clip(ring, piece)).collect();
+        let (piece_area, piece_centroid) = rings_area_centroid(&clipped);
+
+        area += piece_area;
+        centroid.add_mut(piece_centroid.scale(piece_area));
+    }
+
+    if area < math::EPS || total_area < math::EPS {
+        return None;
+    }
+
+    Some((area, total_area, centroid.scale(1.0 / area)))
+}
+
+/// adds a force at a point in world space, unlike physics::apply_force_at_world_point() it doesn't wake the entity up
+fn add_force_at_point<const N: usize>(
+    world: &mut ecs::World<N>,
+    entity: ecs::Entity,
+    force: math::Vec2,
+    point: math::Vec2,
+    mass_center: math::Vec2,
+) {
+    add_force(world, entity, force);
+    add_torque(world, entity, point.sub(mass_center).cross(force));
+}
+
+#[inline]
+fn add_force<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, force: math::Vec2) {
+    if let Some(translation) = world.engine.translation.get_mut(entity) {
+        translation.apply_force(force);
+    }
+}
+
+#[inline]
+fn add_torque<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity, torque: f32) {
+    if let Some(rotation) = world.engine.rotation.get_mut(entity) {
+        rotation.apply_torque(torque);
+    }
+}
+
+/// applies the force of every force field to the entities inside its region, must be called after forces are reset and
+/// before integrating velocities
+///
+/// an entity is inside a region if its center of mass is, except for buoyancy which acts on the submerged part of the
+/// entity; sleeping and kinematic entities are left untouched, and regions shaped as segments or circles are ignored
+pub fn apply_all_force_fields<const N: usize>(world: &mut ecs::World<N>) {
+    for field_idx in 0..world.engine.force_field.get_ents().len() {
+        let field_entity = world.engine.force_field.get_ents()[field_idx];
+        let Some(field) = world.engine.force_field.get(field_entity).cloned() else {
+            continue;
+        };
+
+        let Some(region) = physics::apply_all_trans(world, field_entity) else {
+            continue;
+        };
+
+        let region_rings = physics::outline_rings(&region);
+        if region_rings.first().is_none_or(|outline| outline.len() < 3) {
+            continue;
+        }
+
+        let region_hitbox = region.to_hitbox();
+        let region_centroid = region.centroid();
+
+        // buoyancy clips the entities against the convex pieces of the region
+        let pieces = match (&field.kind, &region) {
+            (ecs::ForceFieldKind::Buoyancy { .. }, math::Shape::CavePoly(cave_poly)) => match cave_poly.cvx_polys() {
+                Ok(cvx_polys) => cvx_polys.iter().map(|cvx_poly| cvx_poly.verts().to_vec()).collect(),
+                Err(_) => continue,
+            },
+            (ecs::ForceFieldKind::Buoyancy { .. }, _) => vec![region_rings[0].clone()],
+            _ => Vec::new(),
+        };
+
+        for idx in 0..world.engine.translation.get_ents().len() {
+            let entity = world.engine.translation.get_ents()[idx];
+            if entity == field_entity {
+                continue;
+            }
+
+            let Some(translation) = world.engine.translation.get(entity) else {
+                continue;
+            };
+
+            if translation.sleeping || translation.kinematic {
+                continue;
+            }
+
+            let Some(mass_center) = physics::mass_center(world, entity) else {
+                continue;
+            };
+
+            let (lin_vel, mass, gravity_scale) = (translation.lin_vel, translation.mass(), translation.gravity_scale);
+            let ang_vel = world.engine.rotation.get(entity).map_or(0.0, |rotation| rotation.ang_vel);
+
+            // every kind except buoyancy acts on the entities whose center of mass is inside the region, holes excluded
+            let inside = physics::hitbox_contains(&region_hitbox, mass_center) && math::rings_contain(&region_rings, mass_center);
+
+            match field.kind {
+                ecs::ForceFieldKind::Uniform { lin_acc } if inside => {
+                    add_force(world, entity, lin_acc.scale(mass * gravity_scale));
+                }
+                ecs::ForceFieldKind::Radial { strength, min_distance } if inside => {
+                    let delta = region_centroid.sub(mass_center);
+                    let distance = delta.mag();
+
+                    if distance < math::EPS {
+                        // direction is undefined
+                        continue;
+                    }
+
+                    let clamped = distance.max(min_distance);
+                    let lin_acc = strength / (clamped * clamped);
+
+                    add_force(world, entity, delta.scale(lin_acc * mass * gravity_scale / distance));
+                }
+                ecs::ForceFieldKind::Wind {
+                    lin_vel: wind_lin_vel,
+                    drag,
+                } if inside => {
+                    add_force(world, entity, wind_lin_vel.sub(lin_vel).scale(drag));
+                }
+                ecs::ForceFieldKind::Drag {
+                    linear,
+                    quadratic,
+                    angular,
+                } if inside => {
+                    let force = lin_vel.scale(-(linear + quadratic * lin_vel.mag()));
+
+                    add_force(world, entity, force);
+                    add_torque(world, entity, -angular * ang_vel);
+                }
+                ecs::ForceFieldKind::Buoyancy {
+                    density,
+                    gravity,
+                    lin_drag,
+                    ang_drag,
+                } => {
+                    let Some((area, total_area, centroid)) = submerged(world, entity, &pieces) else {
+                        continue;
+                    };
+
+                    let fraction = area / total_area;
+
+                    // the fluid pushes against gravity at the centroid of the submerged part, so floating entities right themselves
+                    add_force_at_point(world, entity, gravity.scale(-density * area), centroid, mass_center);
+                    add_force(world, entity, lin_vel.scale(-lin_drag * fraction));
+                    add_torque(world, entity, -ang_drag * fraction * ang_vel);
+                }
+                _ => (),
+            }
+        }
+    }
+}
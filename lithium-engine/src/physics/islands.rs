@@ -0,0 +1,173 @@
+use crate::{ecs, physics};
+
+use std::collections::HashMap;
+
+/// disjoint set used to group connected bodies
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    #[inline]
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    #[inline]
+    fn find(&mut self, mut idx: usize) -> usize {
+        while self.parents[idx] != idx {
+            // path halving
+            self.parents[idx] = self.parents[self.parents[idx]];
+            idx = self.parents[idx];
+        }
+
+        idx
+    }
+
+    #[inline]
+    fn union(&mut self, idx_1: usize, idx_2: usize) {
+        let root_1 = self.find(idx_1);
+        let root_2 = self.find(idx_2);
+
+        if root_1 != root_2 {
+            // keep the smallest index as root so the result doesn't depend on the order of the unions
+            let (root, child) = if root_1 < root_2 { (root_1, root_2) } else { (root_2, root_1) };
+            self.parents[child] = root;
+        }
+    }
+}
+
+/// groups movable bodies touching each other or connected by joints and springs, static and kinematic bodies don't
+/// join islands since they would merge the whole level in a single island, and kinematic bodies never sleep
+pub fn compute_islands<const N: usize>(world: &ecs::World<N>, contacts: &physics::ContactManager) -> Vec<Vec<ecs::Entity>> {
+    let ents: Vec<ecs::Entity> = world
+        .engine
+        .translation
+        .iter()
+        .filter(|(_, translation)| !translation.kinematic)
+        .map(|(entity, _)| entity)
+        .collect();
+    let idx_map: HashMap<ecs::Entity, usize> = ents.iter().enumerate().map(|(idx, &entity)| (entity, idx)).collect();
+    let mut union_find = UnionFind::new(ents.len());
+
+    let mut connect = |entity_1: ecs::Entity, entity_2: ecs::Entity| {
+        if let (Some(&idx_1), Some(&idx_2)) = (idx_map.get(&entity_1), idx_map.get(&entity_2)) {
+            union_find.union(idx_1, idx_2);
+        }
+    };
+
+    for &(entity_1, entity_2) in contacts.pairs() {
+        connect(entity_1, entity_2);
+    }
+
+    for (_, joint) in world.engine.joint.iter() {
+        connect(joint.entity_1, joint.entity_2);
+    }
+
+    for (_, spring) in world.engine.spring.iter() {
+        if let ecs::SpringAnchor::Entity { entity, .. } = spring.anchor {
+            connect(spring.entity, entity);
+        }
+    }
+
+    let mut islands: Vec<Vec<ecs::Entity>> = Vec::new();
+    let mut root_to_island: HashMap<usize, usize> = HashMap::new();
+
+    for (idx, &entity) in ents.iter().enumerate() {
+        let root = union_find.find(idx);
+        let island_idx = *root_to_island.entry(root).or_insert_with(|| {
+            islands.push(Vec::new());
+            islands.len() - 1
+        });
+
+        islands[island_idx].push(entity);
+    }
+
+    islands
+}
+
+/// puts to sleep islands that have been still for long enough and wakes islands where at least one body moves
+pub fn update_sleeping<const N: usize>(world: &mut ecs::World<N>, contacts: &physics::ContactManager, step: f32) {
+    if !contacts.sleep {
+        return;
+    }
+
+    for island in compute_islands(world, contacts) {
+        let mut island_is_still = true;
+        let mut island_is_sleeping = true;
+
+        for &entity in island.iter() {
+            let Some(translation) = world.engine.translation.get(entity) else {
+                continue;
+            };
+
+            if translation.sleeping {
+                continue;
+            }
+
+            island_is_sleeping = false;
+
+            let ang_vel = world.engine.rotation.get(entity).map_or(0.0, |rotation| rotation.ang_vel);
+
+            if translation.lin_vel.mag() > contacts.sleep_lin_vel || ang_vel.abs() > contacts.sleep_ang_vel {
+                island_is_still = false;
+                break;
+            }
+        }
+
+        if island_is_sleeping {
+            // nothing woke the island up
+            continue;
+        }
+
+        if !island_is_still {
+            // a single moving body keeps the whole island awake
+            for &entity in island.iter() {
+                if let Some(translation) = world.engine.translation.get_mut(entity) {
+                    translation.wake();
+                }
+            }
+
+            continue;
+        }
+
+        let mut min_sleep_time = f32::INFINITY;
+
+        for &entity in island.iter() {
+            if let Some(translation) = world.engine.translation.get_mut(entity)
+                && !translation.sleeping
+            {
+                translation.sleep_time += step;
+                min_sleep_time = min_sleep_time.min(translation.sleep_time);
+            }
+        }
+
+        if min_sleep_time >= contacts.time_to_sleep {
+            for &entity in island.iter() {
+                if let Some(translation) = world.engine.translation.get_mut(entity) {
+                    translation.sleep();
+                }
+
+                if let Some(rotation) = world.engine.rotation.get_mut(entity) {
+                    rotation.ang_vel = 0.0;
+                }
+            }
+        }
+    }
+}
+
+/// true if the entity can move but is currently sleeping
+#[inline]
+pub fn is_sleeping<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity) -> bool {
+    world.engine.translation.get(entity).is_some_and(|translation| translation.sleeping)
+}
+
+/// wakes an entity, its island will be woken up by update_sleeping() if the entity starts moving
+#[inline]
+pub fn wake<const N: usize>(world: &mut ecs::World<N>, entity: ecs::Entity) -> Option<()> {
+    world.engine.translation.get_mut(entity)?.wake();
+
+    Some(())
+}
@@ -0,0 +1,343 @@
+use crate::{ecs, math};
+
+/// fraction of the position error corrected every iteration
+const BIAS: f32 = 0.2;
+
+/// impulses smaller than this are considered converged
+pub const JOINT_TOLERANCE: f32 = 1e-3;
+
+/// snapshot of the state of a body that the joint solver needs
+struct JointBody {
+    mass_center: math::Vec2,
+    transform: math::Transform2D,
+    angle: f32,
+    lin_vel: math::Vec2,
+    ang_vel: f32,
+    inv_mass: f32,
+    inv_inertia: f32,
+}
+
+impl JointBody {
+    fn new<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity) -> Option<Self> {
+        let transform = world
+            .engine
+            .transform
+            .get(entity)?
+            .to_transform_2d(world.engine.rotation_matrix.get(entity));
+        let centroid = world.engine.body.get(entity).map_or(math::Vec2::ZERO, |body| body.centroid);
+        let (lin_vel, inv_mass) = world
+            .engine
+            .translation
+            .get(entity)
+            .map_or((math::Vec2::ZERO, 0.0), |t| (t.lin_vel, t.inv_mass()));
+        let kinematic = world.engine.translation.get(entity).is_some_and(|t| t.kinematic);
+        let (ang_vel, inv_inertia) = world
+            .engine
+            .rotation
+            .get(entity)
+            .map_or((0.0, 0.0), |r| (r.ang_vel, if kinematic { 0.0 } else { r.inv_inertia() }));
+
+        Some(Self {
+            mass_center: transform.to_world(centroid),
+            angle: transform.angle().0,
+            transform,
+            lin_vel,
+            ang_vel,
+            inv_mass,
+            inv_inertia,
+        })
+    }
+
+    /// moves a point from the body local space to world space
+    #[inline]
+    fn to_world(&self, point: math::Vec2) -> math::Vec2 {
+        self.transform.to_world(point)
+    }
+
+    #[inline]
+    fn point_vel(&self, arm: math::Vec2) -> math::Vec2 {
+        self.lin_vel.add(arm.cross_scalar(self.ang_vel))
+    }
+
+    fn write_back<const N: usize>(&self, world: &mut ecs::World<N>, entity: ecs::Entity) {
+        if let Some(translation) = world.engine.translation.get_mut(entity) {
+            translation.lin_vel = self.lin_vel;
+        }
+
+        if let Some(rotation) = world.engine.rotation.get_mut(entity) {
+            rotation.ang_vel = self.ang_vel;
+        }
+    }
+}
+
+/// a single constrained direction, either linear (along dir, applied at the arms) or angular (with sign 1 or -1)
+#[derive(Clone, Copy)]
+enum Row {
+    Linear {
+        dir: math::Vec2,
+        arm_1: math::Vec2,
+        arm_2: math::Vec2,
+    },
+    Angular(f32),
+}
+
+impl Row {
+    #[inline]
+    fn rel_vel(&self, body_1: &JointBody, body_2: &JointBody) -> f32 {
+        match *self {
+            Self::Linear { dir, arm_1, arm_2 } => body_2.point_vel(arm_2).sub(body_1.point_vel(arm_1)).dot(dir),
+            Self::Angular(sign) => sign * (body_2.ang_vel - body_1.ang_vel),
+        }
+    }
+
+    #[inline]
+    fn eff_inv_mass(&self, body_1: &JointBody, body_2: &JointBody) -> f32 {
+        match *self {
+            Self::Linear { dir, arm_1, arm_2 } => {
+                body_1.inv_mass
+                    + body_2.inv_mass
+                    + body_1.inv_inertia * math::pow2(arm_1.cross(dir))
+                    + body_2.inv_inertia * math::pow2(arm_2.cross(dir))
+            }
+            Self::Angular(_) => body_1.inv_inertia + body_2.inv_inertia,
+        }
+    }
+
+    #[inline]
+    fn apply(&self, body_1: &mut JointBody, body_2: &mut JointBody, impulse: f32) {
+        match *self {
+            Self::Linear { dir, arm_1, arm_2 } => {
+                let impulse_vector = dir.scale(impulse);
+
+                body_1.lin_vel.sub_mut(impulse_vector.scale(body_1.inv_mass));
+                body_2.lin_vel.add_mut(impulse_vector.scale(body_2.inv_mass));
+                body_1.ang_vel -= body_1.inv_inertia * arm_1.cross(impulse_vector);
+                body_2.ang_vel += body_2.inv_inertia * arm_2.cross(impulse_vector);
+            }
+            Self::Angular(sign) => {
+                body_1.ang_vel -= body_1.inv_inertia * sign * impulse;
+                body_2.ang_vel += body_2.inv_inertia * sign * impulse;
+            }
+        }
+    }
+
+    /// computes the impulse that brings the relative velocity to target_vel, or None if the row can't be solved
+    #[inline]
+    fn impulse(&self, body_1: &JointBody, body_2: &JointBody, target_vel: f32) -> Option<f32> {
+        let eff_inv_mass = self.eff_inv_mass(body_1, body_2);
+
+        if eff_inv_mass < math::EPS {
+            // both bodies are static along this row
+            return None;
+        }
+
+        Some((target_vel - self.rel_vel(body_1, body_2)) / eff_inv_mass)
+    }
+
+    /// keeps error at 0, where error is the current value of the constraint
+    fn solve_equality(&self, body_1: &mut JointBody, body_2: &mut JointBody, error: f32, step: f32) -> f32 {
+        let Some(impulse) = self.impulse(body_1, body_2, -BIAS * error / step) else {
+            return 0.0;
+        };
+
+        self.apply(body_1, body_2, impulse);
+        impulse.abs()
+    }
+
+    /// keeps slack >= 0; while there is slack left the bodies are allowed to close it in a single step (speculative limit)
+    fn solve_inequality(&self, body_1: &mut JointBody, body_2: &mut JointBody, slack: f32, step: f32) -> f32 {
+        let target_vel = if slack > 0.0 { -slack / step } else { -BIAS * slack / step };
+
+        let Some(impulse) = self.impulse(body_1, body_2, target_vel) else {
+            return 0.0;
+        };
+
+        // the limit can only push
+        let impulse = impulse.max(0.0);
+
+        self.apply(body_1, body_2, impulse);
+        impulse
+    }
+
+    /// drives the relative velocity to speed without exceeding the motor force over the step
+    fn solve_motor(&self, body_1: &mut JointBody, body_2: &mut JointBody, motor: ecs::JointMotor, accumulated: &mut f32, step: f32) -> f32 {
+        let Some(impulse) = self.impulse(body_1, body_2, motor.speed) else {
+            return 0.0;
+        };
+
+        let max_impulse = motor.max_force * step;
+        let old_accumulated = *accumulated;
+        *accumulated = (old_accumulated + impulse).clamp(-max_impulse, max_impulse);
+        let impulse = *accumulated - old_accumulated;
+
+        self.apply(body_1, body_2, impulse);
+        impulse.abs()
+    }
+
+    #[inline]
+    fn rev(self) -> Self {
+        match self {
+            Self::Linear { dir, arm_1, arm_2 } => Self::Linear {
+                dir: dir.rev(),
+                arm_1,
+                arm_2,
+            },
+            Self::Angular(sign) => Self::Angular(-sign),
+        }
+    }
+}
+
+/// solves lower <= value <= upper as 2 inequality rows
+#[inline]
+fn solve_limits(row: Row, body_1: &mut JointBody, body_2: &mut JointBody, value: f32, limits: ecs::JointLimits, step: f32) -> f32 {
+    let lower_impulse = row.solve_inequality(body_1, body_2, value - limits.lower, step);
+    let upper_impulse = row.rev().solve_inequality(body_1, body_2, limits.upper - value, step);
+
+    lower_impulse.max(upper_impulse)
+}
+
+/// solves a single joint once, returns the largest impulse applied
+fn solve_joint(joint: &mut ecs::Joint, body_1: &mut JointBody, body_2: &mut JointBody, step: f32) -> f32 {
+    let anchor_1 = body_1.to_world(joint.anchor_1);
+    let anchor_2 = body_2.to_world(joint.anchor_2);
+    let arm_1 = anchor_1.sub(body_1.mass_center);
+    let arm_2 = anchor_2.sub(body_2.mass_center);
+    let delta = anchor_2.sub(anchor_1);
+    let rel_angle = math::Radians::new(body_2.angle - body_1.angle - joint.ref_angle).wrap().0;
+
+    // pins the anchors on both axes
+    let solve_point = |body_1: &mut JointBody, body_2: &mut JointBody| -> f32 {
+        let row_x = Row::Linear {
+            dir: math::Vec2::new(1.0, 0.0),
+            arm_1,
+            arm_2,
+        };
+        let row_y = Row::Linear {
+            dir: math::Vec2::new(0.0, 1.0),
+            arm_1,
+            arm_2,
+        };
+
+        row_x
+            .solve_equality(body_1, body_2, delta.x, step)
+            .max(row_y.solve_equality(body_1, body_2, delta.y, step))
+    };
+
+    match joint.kind {
+        ecs::JointKind::Distance { min_length, max_length } => {
+            let length = delta.mag();
+
+            if length < math::EPS {
+                // direction is undefined, wait for the bodies to move
+                return 0.0;
+            }
+
+            let row = Row::Linear {
+                dir: delta.scale(1.0 / length),
+                arm_1,
+                arm_2,
+            };
+
+            if max_length - min_length < math::EPS {
+                row.solve_equality(body_1, body_2, length - max_length, step)
+            } else {
+                let limits = ecs::JointLimits {
+                    lower: min_length,
+                    upper: max_length,
+                };
+
+                solve_limits(row, body_1, body_2, length, limits, step)
+            }
+        }
+        ecs::JointKind::Revolute { limits, motor } => {
+            let mut max_impulse = 0.0f32;
+
+            if let Some(motor) = motor {
+                max_impulse = max_impulse.max(Row::Angular(1.0).solve_motor(body_1, body_2, motor, &mut joint.motor_impulse, step));
+            }
+
+            if let Some(limits) = limits {
+                max_impulse = max_impulse.max(solve_limits(Row::Angular(1.0), body_1, body_2, rel_angle, limits, step));
+            }
+
+            max_impulse.max(solve_point(body_1, body_2))
+        }
+        ecs::JointKind::Prismatic { axis, limits, motor } => {
+            let axis = body_1.transform.to_world_linear(axis);
+            let normal = axis.perp_ccw();
+
+            // body_1 is constrained at the point of body_2, so the arm of body_1 follows the slider
+            let arm_1 = anchor_2.sub(body_1.mass_center);
+            let axis_row = Row::Linear { dir: axis, arm_1, arm_2 };
+            let normal_row = Row::Linear { dir: normal, arm_1, arm_2 };
+
+            let mut max_impulse = 0.0f32;
+
+            if let Some(motor) = motor {
+                max_impulse = max_impulse.max(axis_row.solve_motor(body_1, body_2, motor, &mut joint.motor_impulse, step));
+            }
+
+            if let Some(limits) = limits {
+                max_impulse = max_impulse.max(solve_limits(axis_row, body_1, body_2, delta.dot(axis), limits, step));
+            }
+
+            max_impulse
+                .max(Row::Angular(1.0).solve_equality(body_1, body_2, rel_angle, step))
+                .max(normal_row.solve_equality(body_1, body_2, delta.dot(normal), step))
+        }
+        ecs::JointKind::Weld => Row::Angular(1.0)
+            .solve_equality(body_1, body_2, rel_angle, step)
+            .max(solve_point(body_1, body_2)),
+    }
+}
+
+/// resets the impulses accumulated by the motors, must be called once per step before solving
+pub(crate) fn reset_joint_impulses<const N: usize>(world: &mut ecs::World<N>) {
+    for (_, joint) in world.engine.joint.iter_mut() {
+        joint.motor_impulse = 0.0;
+    }
+}
+
+/// runs a single iteration over every joint, returns the largest impulse applied
+pub(crate) fn solve_joints_iter<const N: usize>(world: &mut ecs::World<N>, step: f32) -> f32 {
+    let mut max_impulse = 0.0f32;
+
+    for idx in 0..world.engine.joint.get_ents().len() {
+        let joint_entity = world.engine.joint.get_ents()[idx];
+        let Some(mut joint) = world.engine.joint.get(joint_entity).cloned() else {
+            continue;
+        };
+
+        if joint.entity_1 == joint.entity_2 {
+            // a body can't be jointed to itself
+            continue;
+        }
+
+        let (Some(mut body_1), Some(mut body_2)) = (JointBody::new(world, joint.entity_1), JointBody::new(world, joint.entity_2)) else {
+            // one of the bodies does not exist (yet)
+            continue;
+        };
+
+        max_impulse = max_impulse.max(solve_joint(&mut joint, &mut body_1, &mut body_2, step));
+
+        body_1.write_back(world, joint.entity_1);
+        body_2.write_back(world, joint.entity_2);
+
+        if let Some(stored_joint) = world.engine.joint.get_mut(joint_entity) {
+            stored_joint.motor_impulse = joint.motor_impulse;
+        }
+    }
+
+    max_impulse
+}
+
+/// solves only the joints, useful when collisions are not needed; resolve_collisions() already calls this internally
+pub fn solve_joints<const N: usize>(world: &mut ecs::World<N>, iters: usize, step: f32) {
+    reset_joint_impulses(world);
+
+    for _ in 0..iters {
+        if solve_joints_iter(world, step) < JOINT_TOLERANCE {
+            break;
+        }
+    }
+}
@@ -0,0 +1,511 @@
+use crate::math::{ApplyTransformationShape, ToHitBox};
+use crate::{base, ecs, math, physics};
+
+/// number of bisection steps used by shape_cast() to refine the time of impact
+const CAST_REFINE_ITERS: usize = 16;
+
+/// selects which entities a query can return
+#[derive(Debug, Clone)]
+pub struct QueryFilter {
+    pub mask: u32,                 // categories of the collision filter that can be returned
+    pub exclude: Vec<ecs::Entity>, // entities that are never returned (e.g. the entity casting the ray)
+    pub sensors: bool,             // whether sensors and force fields can be returned
+}
+
+impl QueryFilter {
+    #[inline]
+    pub fn new(mask: u32, exclude: Vec<ecs::Entity>, sensors: bool) -> Self {
+        Self { mask, exclude, sensors }
+    }
+
+    /// every entity except the given one
+    #[inline]
+    pub fn excluding(entity: ecs::Entity) -> Self {
+        Self {
+            exclude: vec![entity],
+            ..Self::default()
+        }
+    }
+
+    #[inline]
+    fn accepts<const N: usize>(&self, world: &ecs::World<N>, entity: ecs::Entity) -> bool {
+        if self.exclude.contains(&entity) {
+            return false;
+        }
+
+        if !self.sensors
+            && (world.engine.surface.get(entity).is_some_and(|surface| surface.sensor) || world.engine.force_field.get(entity).is_some())
+        {
+            return false;
+        }
+
+        let filter = world.engine.collision_filter.get(entity).unwrap_or(&ecs::CollisionFilter::DEFAULT);
+        (filter.category & self.mask) != 0
+    }
+}
+
+impl Default for QueryFilter {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            mask: u32::MAX,
+            exclude: Vec::new(),
+            sensors: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: ecs::Entity,
+    pub point: math::Vec2,
+    pub normal: math::Vec2, // surface normal at point, it faces the ray origin
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointHit {
+    pub entity: ecs::Entity,
+    pub point: math::Vec2, // closest point of the entity, the query point itself if it is inside the entity
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeCastHit {
+    pub entity: ecs::Entity,
+    pub normal: math::Vec2, // points from the hit entity to the cast shape
+    pub distance: f32,      // distance travelled by the cast shape before touching the entity
+}
+
+/// rings of the outline of a shape, the counterclockwise outline comes first and the clockwise holes follow; segments
+/// have a single ring of 2 vertices, circles have no rings and are handled separately
+pub(crate) fn outline_rings(shape: &math::Shape) -> Vec<Vec<math::Vec2>> {
+    match shape {
+        math::Shape::Segment(segment) => vec![vec![segment.a, segment.b]],
+        math::Shape::Circle(_) => Vec::new(),
+        _ => math::rings(shape).unwrap_or_default(),
+    }
+}
+
+/// iterates over the edges of an outline, segments only have a single edge
+#[inline]
+pub(crate) fn edges(verts: &[math::Vec2]) -> impl Iterator<Item = (math::Vec2, math::Vec2)> + '_ {
+    let len = verts.len();
+    let edges_len = if len == 2 { 1 } else { len };
+
+    (0..edges_len).map(move |idx| (verts[idx], verts[(idx + 1) % len]))
+}
+
+#[inline]
+pub(crate) fn hitbox_contains(hitbox: &math::HitBox, point: math::Vec2) -> bool {
+    hitbox.min_x <= point.x && point.x <= hitbox.max_x && hitbox.min_y <= point.y && point.y <= hitbox.max_y
+}
+
+/// hitbox of the global shape of an entity, computed by moving the corners of its local hitbox instead of the whole
+/// shape, so it can be larger than the exact one when the entity is rotated
+#[inline]
+fn global_hitbox<const N: usize>(world: &ecs::World<N>, entity: ecs::Entity, body: &ecs::Body) -> math::HitBox {
+    let local_hitbox = body.shape.to_hitbox();
+    let transform = physics::transform_2d(world, entity);
+
+    math::HitBox::from_verts_array(&[
+        transform.to_world(math::Vec2::new(local_hitbox.min_x, local_hitbox.min_y)),
+        transform.to_world(math::Vec2::new(local_hitbox.min_x, local_hitbox.max_y)),
+        transform.to_world(math::Vec2::new(local_hitbox.max_x, local_hitbox.max_y)),
+        transform.to_world(math::Vec2::new(local_hitbox.max_x, local_hitbox.min_y)),
+    ])
+}
+
+/// returns the entities that can be queried and whose hitbox overlaps bounds, along with their global shape and hitbox;
+/// entities are culled with global_hitbox() before their shape is moved to world space
+fn candidates<'a, const N: usize>(
+    world: &'a ecs::World<N>,
+    filter: &'a QueryFilter,
+    bounds: &'a math::HitBox,
+) -> impl Iterator<Item = (ecs::Entity, math::Shape, math::HitBox)> + 'a {
+    world.engine.body.iter().filter_map(move |(entity, body)| {
+        if !physics::check_hitboxes(bounds, &global_hitbox(world, entity, body)) || !filter.accepts(world, entity) {
+            return None;
+        }
+
+        let shape = physics::apply_all_trans(world, entity)?;
+        let hitbox = shape.to_hitbox();
+
+        Some((entity, shape, hitbox))
+    })
+}
+
+/// intersects a ray with a circle, returns the distance along the ray and the normal at the hit point
+fn ray_circle(origin: math::Vec2, dir: math::Vec2, max_distance: f32, circle: &math::Circle) -> Option<(f32, math::Vec2)> {
+    let delta = origin.sub(circle.center);
+    let b = delta.dot(dir);
+    let c = delta.square_mag() - math::pow2(circle.radius);
+
+    if c <= 0.0 {
+        // the ray starts inside the circle
+        return Some((0.0, dir.rev()));
+    }
+
+    let discriminant = b * b - c;
+
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let distance = -b - discriminant.sqrt();
+
+    if distance < 0.0 || distance > max_distance {
+        return None;
+    }
+
+    Some((distance, origin.add(dir.scale(distance)).sub(circle.center).norm()))
+}
+
+/// intersects a ray with a shape, returns the distance along the ray and the normal at the hit point
+fn ray_shape(origin: math::Vec2, dir: math::Vec2, max_distance: f32, shape: &math::Shape) -> Option<(f32, math::Vec2)> {
+    if let math::Shape::Circle(circle) = shape {
+        return ray_circle(origin, dir, max_distance, circle);
+    }
+
+    let rings = outline_rings(shape);
+
+    if math::rings_contain(&rings, origin) {
+        // the ray starts inside the shape, holes excluded
+        return Some((0.0, dir.rev()));
+    }
+
+    let mut best: Option<(f32, math::Vec2)> = None;
+
+    for (a, b) in rings.iter().flat_map(|ring| edges(ring)) {
+        let edge = b.sub(a);
+        let denom = dir.cross(edge);
+
+        if denom.abs() < math::EPS {
+            // parallel
+            continue;
+        }
+
+        let delta = a.sub(origin);
+        let distance = delta.cross(edge) / denom; // along the ray
+        let edge_t = delta.cross(dir) / denom; // along the edge
+
+        if distance < 0.0 || distance > max_distance || !(-math::EPS..=1.0 + math::EPS).contains(&edge_t) {
+            continue;
+        }
+
+        if best.is_none_or(|(best_distance, _)| distance < best_distance) {
+            let normal = edge.perp_ccw().norm();
+            let normal = if normal.dot(dir) > 0.0 { normal.rev() } else { normal };
+
+            best = Some((distance, normal));
+        }
+    }
+
+    best
+}
+
+/// casts a ray and returns every entity it hits sorted by distance, dir does not need to be normalized
+pub fn ray_cast_all<const N: usize>(
+    world: &ecs::World<N>,
+    origin: math::Vec2,
+    dir: math::Vec2,
+    max_distance: f32,
+    filter: &QueryFilter,
+) -> Vec<RayHit> {
+    if dir.approx_equal_zero() {
+        return Vec::new();
+    }
+
+    let dir = dir.norm();
+    let end = origin.add(dir.scale(max_distance));
+    let ray_hitbox = math::HitBox::new(origin.x.min(end.x), origin.y.min(end.y), origin.x.max(end.x), origin.y.max(end.y));
+
+    let mut hits: Vec<RayHit> = candidates(world, filter, &ray_hitbox)
+        .filter_map(|(entity, shape, _)| {
+            let (distance, normal) = ray_shape(origin, dir, max_distance, &shape)?;
+
+            Some(RayHit {
+                entity,
+                point: origin.add(dir.scale(distance)),
+                normal,
+                distance,
+            })
+        })
+        .collect();
+
+    hits.sort_by(|hit_1, hit_2| hit_1.distance.total_cmp(&hit_2.distance));
+    hits
+}
+
+/// casts a ray and returns the closest entity it hits, dir does not need to be normalized
+#[inline]
+pub fn ray_cast<const N: usize>(
+    world: &ecs::World<N>,
+    origin: math::Vec2,
+    dir: math::Vec2,
+    max_distance: f32,
+    filter: &QueryFilter,
+) -> Option<RayHit> {
+    ray_cast_all(world, origin, dir, max_distance, filter).into_iter().next()
+}
+
+/// returns every entity whose shape contains the point, sorted by layer from the topmost if they have a material
+pub fn point_query<const N: usize>(world: &ecs::World<N>, point: math::Vec2, filter: &QueryFilter) -> Vec<ecs::Entity> {
+    let bounds = math::HitBox::new(point.x, point.y, point.x, point.y);
+
+    let mut ents: Vec<ecs::Entity> = candidates(world, filter, &bounds)
+        .filter(|(_, shape, hitbox)| hitbox_contains(hitbox, point) && math::contains_point(shape, &math::Transform2D::IDENTITY, point))
+        .map(|(entity, _, _)| entity)
+        .collect();
+
+    ents.sort_by_key(|&entity| std::cmp::Reverse(world.engine.material.get(entity).map_or(0, |material| material.layer)));
+    ents
+}
+
+/// returns the entity closest to the point among the ones within max_distance, entities containing the point are at
+/// distance 0; useful to pick thin shapes like segments
+pub fn nearest_query<const N: usize>(
+    world: &ecs::World<N>,
+    point: math::Vec2,
+    max_distance: f32,
+    filter: &QueryFilter,
+) -> Option<PointHit> {
+    let bounds = math::HitBox::new(
+        point.x - max_distance,
+        point.y - max_distance,
+        point.x + max_distance,
+        point.y + max_distance,
+    );
+
+    candidates(world, filter, &bounds)
+        .map(|(entity, shape, _)| {
+            let closest = math::closest_point(&shape, &math::Transform2D::IDENTITY, point);
+
+            PointHit {
+                entity,
+                point: closest,
+                distance: closest.dist(point),
+            }
+        })
+        .filter(|hit| hit.distance <= max_distance)
+        .min_by(|hit_1, hit_2| hit_1.distance.total_cmp(&hit_2.distance))
+}
+
+/// distance between the bodies of two entities, 0 if they touch or overlap
+pub fn entity_distance<const N: usize>(world: &ecs::World<N>, entity_1: ecs::Entity, entity_2: ecs::Entity) -> Option<f32> {
+    let shape_1 = &world.engine.body.get(entity_1)?.shape;
+    let shape_2 = &world.engine.body.get(entity_2)?.shape;
+
+    Some(math::distance(
+        shape_1,
+        &physics::transform_2d(world, entity_1),
+        shape_2,
+        &physics::transform_2d(world, entity_2),
+    ))
+}
+
+/// returns every entity whose shape overlaps the hitbox
+pub fn aabb_query<const N: usize>(
+    world: &ecs::World<N>,
+    hitbox: &math::HitBox,
+    filter: &QueryFilter,
+) -> Result<Vec<ecs::Entity>, base::GeometryError> {
+    let quad = math::Shape::Quad(math::Quad::new_unchecked(
+        math::Vec2::new(hitbox.min_x, hitbox.min_y),
+        math::Vec2::new(hitbox.min_x, hitbox.max_y),
+        math::Vec2::new(hitbox.max_x, hitbox.max_y),
+        math::Vec2::new(hitbox.max_x, hitbox.min_y),
+    ));
+
+    let mut ents = Vec::new();
+
+    for (entity, shape, shape_hitbox) in candidates(world, filter, hitbox) {
+        if physics::check_hitboxes(hitbox, &shape_hitbox) && overlap_normal(&quad, &shape)?.is_some() {
+            ents.push(entity);
+        }
+    }
+
+    Ok(ents)
+}
+
+/// returns the collision normal if the shapes overlap, pointing from shape_1 to shape_2; circles are checked with gjk
+#[inline]
+fn overlap_normal(shape_1: &math::Shape, shape_2: &math::Shape) -> Result<Option<math::Vec2>, base::GeometryError> {
+    let collision = if physics::is_round(shape_1) || physics::is_round(shape_2) {
+        physics::check_gjk(shape_1, shape_2)?.map(physics::SatCollision::from)
+    } else {
+        physics::check_sat(shape_1, shape_2)?
+    };
+
+    Ok(collision.map(|sat_collision| match sat_collision {
+        physics::SatCollision::Cvx(cvx_collision) => cvx_collision.normal,
+        physics::SatCollision::Cave(cave_collisions) => cave_collisions
+            .iter()
+            .fold(math::Vec2::ZERO, |normal, cave_collision| normal.add(cave_collision.normal))
+            .norm(),
+    }))
+}
+
+/// smallest side of a hitbox, at least 1 so that flat hitboxes (e.g. of segments) don't need endless samples
+#[inline]
+fn min_extent(hitbox: &math::HitBox) -> f32 {
+    (hitbox.max_x - hitbox.min_x).min(hitbox.max_y - hitbox.min_y).max(1.0)
+}
+
+/// range of distances along dir (normalized) for which the moved hitbox overlaps target, within [0, max_distance]
+fn hitbox_cast_range(hitbox: &math::HitBox, dir: math::Vec2, max_distance: f32, target: &math::HitBox) -> Option<(f32, f32)> {
+    let mut range = (0.0_f32, max_distance);
+
+    for (min, max, target_min, target_max, dir) in [
+        (hitbox.min_x, hitbox.max_x, target.min_x, target.max_x, dir.x),
+        (hitbox.min_y, hitbox.max_y, target.min_y, target.max_y, dir.y),
+    ] {
+        if dir.abs() < math::EPS {
+            // no movement along this axis, the hitboxes must already overlap on it
+            if max < target_min || target_max < min {
+                return None;
+            }
+            continue;
+        }
+
+        let (t_1, t_2) = ((target_min - max) / dir, (target_max - min) / dir);
+        range = (range.0.max(t_1.min(t_2)), range.1.min(t_1.max(t_2)));
+    }
+
+    (range.0 <= range.1).then_some(range)
+}
+
+/// moves a shape given in world space along dir and returns the first entity it touches, entities already touching the
+/// shape are ignored unless it moves into them; one-way surfaces behave like in the collision detection, they only block
+/// a shape moving against their pass direction from the side entities exit from, and they are ignored while the shape
+/// overlaps them; dir does not need to be normalized
+pub fn shape_cast<const N: usize>(
+    world: &ecs::World<N>,
+    shape: &math::Shape,
+    dir: math::Vec2,
+    max_distance: f32,
+    filter: &QueryFilter,
+) -> Result<Option<ShapeCastHit>, base::GeometryError> {
+    if dir.approx_equal_zero() {
+        return Ok(None);
+    }
+
+    let dir = dir.norm();
+    let start_hitbox = shape.to_hitbox();
+    let end_hitbox = start_hitbox.clone().add_pos(dir.scale(max_distance));
+    let swept_hitbox = math::HitBox::new(
+        start_hitbox.min_x.min(end_hitbox.min_x),
+        start_hitbox.min_y.min(end_hitbox.min_y),
+        start_hitbox.max_x.max(end_hitbox.max_x),
+        start_hitbox.max_y.max(end_hitbox.max_y),
+    );
+
+    let shape_extent = min_extent(&start_hitbox);
+    let mut best: Option<ShapeCastHit> = None;
+
+    for (entity, target, target_hitbox) in candidates(world, filter, &swept_hitbox) {
+        if !physics::check_hitboxes(&swept_hitbox, &target_hitbox) {
+            continue;
+        }
+
+        let pass_dir = world
+            .engine
+            .surface
+            .get(entity)
+            .and_then(|surface| physics::global_pass_dir(surface, world.engine.rotation_matrix.get(entity)));
+
+        if pass_dir.is_some_and(|pass_dir| dir.dot(pass_dir) >= 0.0) {
+            // moving along the pass direction of a one-way surface
+            continue;
+        }
+
+        let max_t = best.map_or(max_distance, |hit| hit.distance);
+
+        if let Some(normal) = overlap_normal(shape, &target)? {
+            // already touching, the entity only blocks the shape if it is moving into it (so shapes can slide along surfaces),
+            // a shape overlapping a one-way surface is passing through it
+            if pass_dir.is_none() && normal.dot(dir) > math::EPS {
+                best = Some(ShapeCastHit {
+                    entity,
+                    normal: normal.rev(),
+                    distance: 0.0,
+                });
+            }
+            continue;
+        }
+
+        // the shapes can only overlap while their hitboxes do, the samples are spaced by half the smaller extent of the
+        // two hitboxes, otherwise thin entities could be skipped
+        let Some((enter_t, exit_t)) = hitbox_cast_range(&start_hitbox, dir, max_t, &target_hitbox) else {
+            continue;
+        };
+
+        let step = 0.5 * shape_extent.min(min_extent(&target_hitbox));
+        let samples = ((exit_t - enter_t) / step).ceil() as usize;
+
+        // before enter_t the hitboxes are apart
+        let mut free_t = (enter_t - step).max(0.0);
+        let mut hit: Option<(f32, math::Vec2)> = None;
+
+        for sample in 0..=samples {
+            let t = (enter_t + step * sample as f32).min(exit_t);
+
+            if let Some(normal) = overlap_normal(&shape.apply_vec2_unchecked(dir.scale(t)), &target)? {
+                hit = Some((t, normal));
+                break;
+            }
+
+            free_t = t;
+        }
+
+        let Some((mut hit_t, mut hit_normal)) = hit else {
+            continue;
+        };
+
+        // refine the time of impact
+        for _ in 0..CAST_REFINE_ITERS {
+            let mid_t = 0.5 * (free_t + hit_t);
+
+            match overlap_normal(&shape.apply_vec2_unchecked(dir.scale(mid_t)), &target)? {
+                Some(normal) => {
+                    hit_t = mid_t;
+                    hit_normal = normal;
+                }
+                None => free_t = mid_t,
+            }
+        }
+
+        if pass_dir.is_some_and(|pass_dir| hit_normal.rev().dot(pass_dir) <= math::EPS) {
+            // the shape reached the one-way surface from a side other than the one entities exit from
+            continue;
+        }
+
+        if best.is_none_or(|best_hit| free_t < best_hit.distance) {
+            best = Some(ShapeCastHit {
+                entity,
+                normal: hit_normal.rev(),
+                distance: free_t,
+            });
+        }
+    }
+
+    Ok(best)
+}
+
+/// convenience wrapper of shape_cast() that casts the current global shape of an entity, the entity itself is excluded
+pub fn entity_cast<const N: usize>(
+    world: &ecs::World<N>,
+    entity: ecs::Entity,
+    dir: math::Vec2,
+    max_distance: f32,
+    filter: &QueryFilter,
+) -> Result<Option<ShapeCastHit>, base::GeometryError> {
+    let Some(shape) = physics::apply_all_trans(world, entity) else {
+        return Ok(None);
+    };
+
+    let mut filter = filter.clone();
+    filter.exclude.push(entity);
+
+    shape_cast(world, &shape, dir, max_distance, &filter)
+}
@@ -219,18 +219,18 @@ pub fn render<const N: usize>(world: &ecs::World<N>, camera: &render::Camera) {
                             mq_math::Vec2::new(a.x - cam_x, a.y - cam_y),
                             mq_math::Vec2::new(b.x - cam_x, b.y - cam_y),
                             mq_math::Vec2::new(c.x - cam_x, c.y - cam_y),
-                            colors[idx],
+                            colors[idx % colors.len()],
                         )
                     }
                 }
-                math::Shape::Circle(_) => {
-                    unimplemented!();
-                    // mq_prelude::draw_circle(
-                    //     pos.x + circle.radius - cam_x, // sum radius because macroquad use center for circles instead of top left
-                    //     pos.y + circle.radius - cam_y,
-                    //     circle.radius,
-                    //     color,
-                    // )
+                math::Shape::Circle(circle) => {
+                    let center = if rot_mat_is_none {
+                        pos.add(circle.center)
+                    } else {
+                        pos.add(rot_mat.pre_mul_vec2(circle.center))
+                    };
+
+                    mq_prelude::draw_circle(center.x - cam_x, center.y - cam_y, circle.radius, color)
                 }
             }
         }
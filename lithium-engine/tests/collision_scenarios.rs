@@ -0,0 +1,401 @@
+// regression tests for the collision scenarios listed in todo.txt that used to crash or misbehave
+//
+// every scenario is simulated with the same loop used by the editor, a scenario fails if the solver returns an error,
+// panics, produces non finite values, or if its own check does not hold at the end of the run; every scenario runs
+// once for each narrow phase
+
+use lithium_engine::prelude;
+
+const GRAVITY: prelude::Vec2 = prelude::Vec2 { x: 0.0, y: 0.3 };
+const TICKS_PER_FRAME: usize = 15;
+const STEP: f32 = 1.0 / (TICKS_PER_FRAME as f32);
+const MAX_COLLISION_ITERATIONS: usize = 10;
+const DENSITY: f32 = 0.001;
+
+type Scenario = fn(prelude::NarrowPhase) -> Result<(), String>;
+
+struct Scene {
+    world: prelude::World<0>,
+    entity_manager: prelude::EntityManager,
+    contacts: prelude::ContactManager,
+}
+
+impl Scene {
+    fn new(narrow_phase: prelude::NarrowPhase) -> Self {
+        let mut contacts = prelude::ContactManager::new();
+        contacts.narrow_phase = narrow_phase;
+
+        Self {
+            world: prelude::World::default(),
+            entity_manager: prelude::EntityManager::new(),
+            contacts,
+        }
+    }
+
+    /// rectangle with its top left corner at pos, rotated by rot_degrees around its center
+    fn spawn_rect(&mut self, pos: prelude::Vec2, width: f32, height: f32, rot_degrees: f32) -> prelude::Entity {
+        let entity = self.entity_manager.create();
+        let engine = self.world.engine_mut();
+
+        let shape = prelude::Quad::new_checked(
+            prelude::Vec2::new(0.0, 0.0),
+            prelude::Vec2::new(0.0, height),
+            prelude::Vec2::new(width, height),
+            prelude::Vec2::new(width, 0.0),
+        )
+        .unwrap();
+
+        let center = prelude::Vec2::new(0.5 * width, 0.5 * height);
+        let rot_mat = prelude::Mat2x3::from_rot_and_pivot(prelude::Radians::from_degrees(rot_degrees), center);
+
+        engine.transform.upsert(entity, prelude::Transform::new(pos));
+        engine.rotation_matrix.upsert(entity, prelude::RotationMatrix::new(rot_mat));
+        engine.surface.upsert(entity, prelude::Surface::new(0.2, 0.4, 0.3));
+        engine.body.upsert(entity, prelude::Body::new(prelude::Shape::Quad(shape)));
+
+        entity
+    }
+
+    /// dynamic rectangle, mass and inertia follow from its area
+    fn spawn_box(&mut self, pos: prelude::Vec2, width: f32, height: f32, rot_degrees: f32) -> prelude::Entity {
+        let entity = self.spawn_rect(pos, width, height, rot_degrees);
+        let mass = DENSITY * width * height;
+        let inertia = mass * (width * width + height * height) / 12.0;
+
+        let engine = self.world.engine_mut();
+        engine.translation.upsert(
+            entity,
+            prelude::Translation::new(prelude::Vec2::ZERO, prelude::Vec2::ZERO, mass).unwrap(),
+        );
+        engine.rotation.upsert(entity, prelude::Rotation::new(0.0, 0.0, inertia).unwrap());
+
+        entity
+    }
+
+    /// dynamic ball with its center at center, mass and inertia follow from its area
+    fn spawn_ball(&mut self, center: prelude::Vec2, radius: f32) -> prelude::Entity {
+        let entity = self.entity_manager.create();
+        let engine = self.world.engine_mut();
+
+        let shape = prelude::Circle::new_checked(prelude::Vec2::ZERO, radius).unwrap();
+        let mass = DENSITY * std::f32::consts::PI * radius * radius;
+        let inertia = 0.5 * mass * radius * radius;
+
+        engine.transform.upsert(entity, prelude::Transform::new(center));
+        engine
+            .rotation_matrix
+            .upsert(entity, prelude::RotationMatrix::new(prelude::Mat2x3::IDENTITY));
+        engine.surface.upsert(entity, prelude::Surface::new(0.2, 0.4, 0.3));
+        engine.body.upsert(entity, prelude::Body::new(prelude::Shape::Circle(shape)));
+        engine.translation.upsert(
+            entity,
+            prelude::Translation::new(prelude::Vec2::ZERO, prelude::Vec2::ZERO, mass).unwrap(),
+        );
+        engine.rotation.upsert(entity, prelude::Rotation::new(0.0, 0.0, inertia).unwrap());
+
+        entity
+    }
+
+    fn set_vel(&mut self, entity: prelude::Entity, lin_vel: prelude::Vec2, ang_vel: f32) {
+        let engine = self.world.engine_mut();
+        *engine.translation.get_mut(entity).unwrap().lin_vel_mut() = lin_vel;
+        *engine.rotation.get_mut(entity).unwrap().ang_vel_mut() = ang_vel;
+    }
+
+    fn run(&mut self, frames: usize) -> Result<(), String> {
+        prelude::set_all_lin_acc(&mut self.world, GRAVITY);
+
+        for _ in 0..frames * TICKS_PER_FRAME {
+            prelude::integrate_all_lin_vel(&mut self.world, STEP);
+            prelude::integrate_all_ang_vel(&mut self.world, STEP);
+            prelude::reset_all_rest(&mut self.world);
+            prelude::resolve_collisions(&mut self.world, &mut self.contacts, MAX_COLLISION_ITERATIONS, STEP)
+                .map_err(|err| err.to_string())?;
+            prelude::integrate_all_pos(&mut self.world, STEP);
+            prelude::integrate_all_rot_mat(&mut self.world, STEP);
+            prelude::set_all_lin_acc(&mut self.world, GRAVITY);
+            prelude::set_all_ang_acc(&mut self.world, 0.0);
+
+            self.check_finite()?;
+        }
+
+        Ok(())
+    }
+
+    fn check_finite(&self) -> Result<(), String> {
+        for (entity, transform) in self.world.engine().transform.iter() {
+            let pos = transform.pos();
+            let lin_vel = self
+                .world
+                .engine()
+                .translation
+                .get(entity)
+                .map_or(prelude::Vec2::ZERO, |t| t.lin_vel());
+            let ang_vel = self.world.engine().rotation.get(entity).map_or(0.0, |r| r.ang_vel());
+
+            if !(pos.x.is_finite() && pos.y.is_finite() && lin_vel.x.is_finite() && lin_vel.y.is_finite() && ang_vel.is_finite()) {
+                return Err(format!("entity {entity} has non finite state"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// lowest and highest y reached by the vertices of an entity
+    fn extent_y(&self, entity: prelude::Entity) -> (f32, f32) {
+        let shape = prelude::apply_all_trans(&self.world, entity).unwrap();
+        let hitbox = prelude::ToHitBox::to_hitbox(&shape);
+        (hitbox.min_y(), hitbox.max_y())
+    }
+
+    fn mass_center(&self, entity: prelude::Entity) -> prelude::Vec2 {
+        prelude::mass_center(&self.world, entity).unwrap()
+    }
+
+    fn speed(&self, entity: prelude::Entity) -> f32 {
+        self.world.engine().translation.get(entity).unwrap().lin_vel().mag()
+    }
+}
+
+/// a box must end up lying on top of a floor, within a tolerance
+fn expect_on_top(scene: &Scene, entity: prelude::Entity, floor_top: f32) -> Result<(), String> {
+    let (_, max_y) = scene.extent_y(entity);
+
+    if (max_y - floor_top).abs() > 2.0 {
+        return Err(format!("entity {entity} rests at {max_y:.2} instead of {floor_top:.2}"));
+    }
+
+    Ok(())
+}
+
+fn expect_still(scene: &Scene, entity: prelude::Entity) -> Result<(), String> {
+    let speed = scene.speed(entity);
+
+    if speed > 0.5 {
+        return Err(format!("entity {entity} is still moving at {speed:.3}"));
+    }
+
+    Ok(())
+}
+
+/// a dynamic box resting on top of another dynamic box
+fn stacked_boxes(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-500.0, 0.0), 1000.0, 100.0, 0.0);
+    let bottom = scene.spawn_box(prelude::Vec2::new(-50.0, -100.0), 100.0, 100.0, 0.0);
+    let top = scene.spawn_box(prelude::Vec2::new(-25.0, -160.0), 50.0, 50.0, 0.0);
+
+    scene.run(300)?;
+
+    expect_on_top(&scene, bottom, 0.0)?;
+    expect_on_top(&scene, top, scene.extent_y(bottom).0)?;
+    expect_still(&scene, top)
+}
+
+/// a box sliding slowly over the edge of a static platform, it must tip over the vertex and fall
+fn slide_off_static_edge(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-500.0, 0.0), 500.0, 100.0, 0.0);
+    scene.spawn_rect(prelude::Vec2::new(-500.0, 400.0), 1500.0, 100.0, 0.0);
+    let slider = scene.spawn_box(prelude::Vec2::new(-30.0, -40.0), 40.0, 40.0, 0.0);
+    scene.set_vel(slider, prelude::Vec2::new(3.0, 0.0), 0.0);
+
+    scene.run(600)?;
+
+    expect_on_top(&scene, slider, 400.0)
+}
+
+/// a box sliding slowly over the edge of a dynamic box resting on the floor
+fn slide_off_dynamic_edge(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-500.0, 0.0), 1000.0, 100.0, 0.0);
+    let base = scene.spawn_box(prelude::Vec2::new(-100.0, -60.0), 200.0, 60.0, 0.0);
+    let slider = scene.spawn_box(prelude::Vec2::new(70.0, -100.0), 40.0, 40.0, 0.0);
+    scene.set_vel(slider, prelude::Vec2::new(3.0, 0.0), 0.0);
+
+    scene.run(600)?;
+
+    expect_on_top(&scene, base, 0.0)?;
+    expect_on_top(&scene, slider, 0.0)
+}
+
+/// 2 boxes hitting each other head on, faster than they can cross in a single step without the swept shapes
+fn fast_head_on(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-1000.0, 0.0), 2000.0, 100.0, 0.0);
+    let left = scene.spawn_box(prelude::Vec2::new(-300.0, -50.0), 50.0, 50.0, 0.0);
+    let right = scene.spawn_box(prelude::Vec2::new(250.0, -50.0), 50.0, 50.0, 0.0);
+    scene.set_vel(left, prelude::Vec2::new(60.0, 0.0), 0.0);
+    scene.set_vel(right, prelude::Vec2::new(-60.0, 0.0), 0.0);
+
+    scene.run(300)?;
+
+    if scene.mass_center(left).x >= scene.mass_center(right).x {
+        return Err(String::from("the boxes passed through each other"));
+    }
+
+    expect_on_top(&scene, left, 0.0)?;
+    expect_on_top(&scene, right, 0.0)
+}
+
+/// 2 boxes falling side by side on top of a wider dynamic box
+fn two_falling_on_one(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-500.0, 0.0), 1000.0, 100.0, 0.0);
+    let base = scene.spawn_box(prelude::Vec2::new(-100.0, -50.0), 200.0, 50.0, 0.0);
+    let left = scene.spawn_box(prelude::Vec2::new(-80.0, -200.0), 50.0, 50.0, 0.0);
+    let right = scene.spawn_box(prelude::Vec2::new(30.0, -230.0), 50.0, 50.0, 0.0);
+
+    scene.run(400)?;
+
+    expect_on_top(&scene, base, 0.0)?;
+    expect_on_top(&scene, left, -50.0)?;
+    expect_on_top(&scene, right, -50.0)?;
+    expect_still(&scene, left)?;
+    expect_still(&scene, right)
+}
+
+/// a tilted box landing on one of its corners, it must settle flat on the floor
+fn corner_landing(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-500.0, 0.0), 1000.0, 100.0, 0.0);
+    let tilted = scene.spawn_box(prelude::Vec2::new(-25.0, -200.0), 50.0, 50.0, 30.0);
+
+    scene.run(600)?;
+
+    expect_on_top(&scene, tilted, 0.0)?;
+    expect_still(&scene, tilted)
+}
+
+/// a box spinning fast while falling on the floor, its corners sweep a lot of space within a single step
+fn spinning_landing(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-500.0, 0.0), 1000.0, 100.0, 0.0);
+    let spinner = scene.spawn_box(prelude::Vec2::new(-40.0, -150.0), 80.0, 20.0, 0.0);
+    scene.set_vel(spinner, prelude::Vec2::new(0.0, 5.0), 0.5);
+
+    scene.run(600)?;
+
+    expect_on_top(&scene, spinner, 0.0)
+}
+
+/// a box resting on a static ramp rotated through its rotation matrix
+fn rotated_static_ramp(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let rot = 10f32.to_radians();
+    let (sin, cos) = rot.sin_cos();
+    let up = prelude::Vec2::new(sin, -cos);
+
+    // middle of the top side of the ramp, after rotating it around its center
+    let top = prelude::Vec2::new(20.0 * sin, 20.0 - 20.0 * cos);
+
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-300.0, 0.0), 600.0, 40.0, 10.0);
+    scene.spawn_rect(prelude::Vec2::new(-1000.0, 400.0), 2000.0, 100.0, 0.0);
+    let resting = scene.spawn_box(top.add(up.scale(22.0)).sub(prelude::Vec2::new(20.0, 20.0)), 40.0, 40.0, 10.0);
+
+    scene.run(100)?;
+
+    // the ramp is steep less than the static friction allows, so the box must stay on its top without sinking
+    let dist = scene.mass_center(resting).sub(top).dot(up);
+
+    if (dist - 20.0).abs() > 2.0 {
+        return Err(format!("the box is {dist:.2} away from the ramp instead of 20.00"));
+    }
+
+    expect_still(&scene, resting)
+}
+
+/// a ball falling on a dynamic box resting on the floor
+fn ball_on_box(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-500.0, 0.0), 1000.0, 100.0, 0.0);
+    let base = scene.spawn_box(prelude::Vec2::new(-50.0, -40.0), 100.0, 40.0, 0.0);
+    let ball = scene.spawn_ball(prelude::Vec2::new(0.0, -150.0), 20.0);
+
+    scene.run(300)?;
+
+    expect_on_top(&scene, base, 0.0)?;
+    expect_on_top(&scene, ball, -40.0)?;
+    expect_still(&scene, ball)
+}
+
+/// a ball released on a static ramp must roll down and end up on the floor below it, stopped by a wall
+fn ball_on_ramp(narrow_phase: prelude::NarrowPhase) -> Result<(), String> {
+    let mut scene = Scene::new(narrow_phase);
+    scene.spawn_rect(prelude::Vec2::new(-300.0, 0.0), 600.0, 40.0, 20.0);
+    scene.spawn_rect(prelude::Vec2::new(-1000.0, 400.0), 2000.0, 100.0, 0.0);
+    scene.spawn_rect(prelude::Vec2::new(700.0, 0.0), 100.0, 400.0, 0.0);
+    let ball = scene.spawn_ball(prelude::Vec2::new(-100.0, -80.0), 15.0);
+
+    scene.run(600)?;
+
+    expect_on_top(&scene, ball, 400.0)?;
+
+    if scene.mass_center(ball).x < 200.0 {
+        return Err(format!(
+            "the ball stopped at {:.2} instead of rolling down",
+            scene.mass_center(ball).x
+        ));
+    }
+
+    Ok(())
+}
+
+/// runs a scenario once for each narrow phase
+fn check(scenario: Scenario) {
+    for narrow_phase in [prelude::NarrowPhase::Sat, prelude::NarrowPhase::Gjk] {
+        if let Err(err) = scenario(narrow_phase) {
+            panic!("narrow phase {narrow_phase:?}: {err}");
+        }
+    }
+}
+
+#[test]
+fn dynamic_box_rests_on_dynamic_box() {
+    check(stacked_boxes);
+}
+
+#[test]
+fn box_slides_off_static_edge() {
+    check(slide_off_static_edge);
+}
+
+#[test]
+fn box_slides_off_dynamic_edge() {
+    check(slide_off_dynamic_edge);
+}
+
+#[test]
+fn fast_boxes_collide_head_on() {
+    check(fast_head_on);
+}
+
+#[test]
+fn two_boxes_fall_on_dynamic_box() {
+    check(two_falling_on_one);
+}
+
+#[test]
+fn tilted_box_lands_on_corner() {
+    check(corner_landing);
+}
+
+#[test]
+fn spinning_box_lands() {
+    check(spinning_landing);
+}
+
+#[test]
+fn box_rests_on_rotated_static_ramp() {
+    check(rotated_static_ramp);
+}
+
+#[test]
+fn ball_rests_on_dynamic_box() {
+    check(ball_on_box);
+}
+
+#[test]
+fn ball_rolls_down_static_ramp() {
+    check(ball_on_ramp);
+}
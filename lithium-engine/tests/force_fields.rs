@@ -0,0 +1,117 @@
+// regression tests for the force fields acting on shapes with holes
+
+use lithium_engine::{physics, prelude};
+
+const DENSITY: f32 = 0.001;
+
+fn v(x: f32, y: f32) -> prelude::Vec2 {
+    prelude::Vec2::new(x, y)
+}
+
+fn square(min: prelude::Vec2, size: f32) -> prelude::Shape {
+    prelude::Shape::Quad(prelude::Quad::new_checked(min, min.add(v(0.0, size)), min.add(v(size, size)), min.add(v(size, 0.0))).unwrap())
+}
+
+/// 100x100 square at min with a 40x40 hole in the middle
+fn holed_square(min: prelude::Vec2) -> prelude::Shape {
+    let outline = [v(0.0, 0.0), v(0.0, 100.0), v(100.0, 100.0), v(100.0, 0.0)];
+    let hole = [v(30.0, 30.0), v(70.0, 30.0), v(70.0, 70.0), v(30.0, 70.0)];
+
+    prelude::Shape::CavePoly(
+        prelude::CavePoly::with_holes_checked(
+            outline.iter().map(|vert| vert.add(min)).collect(),
+            vec![hole.iter().map(|vert| vert.add(min)).collect()],
+            false,
+        )
+        .unwrap(),
+    )
+}
+
+fn spawn_field(
+    world: &mut prelude::World<0>,
+    entity_manager: &mut prelude::EntityManager,
+    shape: prelude::Shape,
+    kind: prelude::ForceFieldKind,
+) {
+    let entity = entity_manager.create();
+    let engine = world.engine_mut();
+
+    engine.transform.upsert(entity, prelude::Transform::new(prelude::Vec2::ZERO));
+    engine.body.upsert(entity, prelude::Body::new(shape));
+    engine.force_field.upsert(entity, prelude::ForceField::new(kind).unwrap());
+}
+
+/// dynamic entity without rotation, its mass follows from area
+fn spawn_body(
+    world: &mut prelude::World<0>,
+    entity_manager: &mut prelude::EntityManager,
+    shape: prelude::Shape,
+    area: f32,
+) -> prelude::Entity {
+    let entity = entity_manager.create();
+    let engine = world.engine_mut();
+
+    engine.transform.upsert(entity, prelude::Transform::new(prelude::Vec2::ZERO));
+    engine.body.upsert(entity, prelude::Body::new(shape));
+    engine.translation.upsert(
+        entity,
+        prelude::Translation::new(prelude::Vec2::ZERO, prelude::Vec2::ZERO, DENSITY * area).unwrap(),
+    );
+
+    entity
+}
+
+fn force(world: &prelude::World<0>, entity: prelude::Entity) -> prelude::Vec2 {
+    world.engine().translation.get(entity).unwrap().force()
+}
+
+/// an entity sitting in the hole of a region is outside of it
+#[test]
+fn region_holes_are_outside() {
+    let mut world = prelude::World::default();
+    let mut entity_manager = prelude::EntityManager::new();
+    let lin_acc = v(0.0, -1.0);
+    spawn_field(
+        &mut world,
+        &mut entity_manager,
+        holed_square(prelude::Vec2::ZERO),
+        prelude::ForceFieldKind::Uniform { lin_acc },
+    );
+
+    let in_hole = spawn_body(&mut world, &mut entity_manager, square(v(45.0, 45.0), 10.0), 100.0);
+    let in_region = spawn_body(&mut world, &mut entity_manager, square(v(5.0, 45.0), 10.0), 100.0);
+
+    physics::apply_all_force_fields(&mut world);
+
+    assert_eq!(force(&world, in_hole), prelude::Vec2::ZERO);
+    assert!(force(&world, in_region).sub(lin_acc.scale(DENSITY * 100.0)).mag() < 1e-6);
+}
+
+/// the water inside the hole of a fully submerged entity doesn't push it up
+#[test]
+fn buoyancy_ignores_body_holes() {
+    let mut world = prelude::World::default();
+    let mut entity_manager = prelude::EntityManager::new();
+    let (density, gravity) = (DENSITY, v(0.0, 1.0));
+    let kind = prelude::ForceFieldKind::Buoyancy {
+        density,
+        gravity,
+        lin_drag: 0.0,
+        ang_drag: 0.0,
+    };
+    spawn_field(&mut world, &mut entity_manager, square(v(-100.0, -100.0), 400.0), kind);
+
+    let area = 100.0 * 100.0 - 40.0 * 40.0;
+    let holed = spawn_body(&mut world, &mut entity_manager, holed_square(prelude::Vec2::ZERO), area);
+
+    physics::apply_all_force_fields(&mut world);
+
+    // a fully submerged entity is pushed by the weight of the water it displaces
+    let expected = gravity.scale(-density * area);
+    assert!(
+        force(&world, holed).sub(expected).mag() < 1e-4,
+        "buoyancy is {} instead of {}",
+        force(&world, holed),
+        expected
+    );
+}
@@ -0,0 +1,361 @@
+// property tests for the exact orientation and segment intersection predicates, focused on degenerate inputs
+//
+// every property is checked on many pseudo random cases built from a fixed seed, points are often taken on a dyadic
+// grid so that their exact orientation can be computed with integers and compared with the predicates
+
+use lithium_engine::prelude;
+
+const CASES: usize = 20_000;
+
+type Property = fn(&mut Rng) -> Result<(), String>;
+
+/// xorshift, good enough to spread the cases and reproducible on every machine
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// integer in [min, max]
+    fn int(&mut self, min: i64, max: i64) -> i64 {
+        min + (self.next() % (max - min + 1) as u64) as i64
+    }
+
+    /// any finite f32 between -max and max, with a random exponent so that very different magnitudes are mixed
+    fn float(&mut self, max_exp: i32) -> f32 {
+        let mantissa = (self.next() % (1 << 24)) as f32 / (1 << 24) as f32;
+        let sign = if self.next().is_multiple_of(2) { 1.0 } else { -1.0 };
+        sign * mantissa * 2f32.powi(self.int(-max_exp as i64, max_exp as i64) as i32)
+    }
+}
+
+/// point of a grid with cells of 2^-shift around base, its coordinates are exact in f32
+fn grid_point(rng: &mut Rng, base: f32, shift: i32, range: i64) -> prelude::Vec2 {
+    let (i, j) = (rng.int(-range, range), rng.int(-range, range));
+    let cell = 2f32.powi(-shift);
+
+    prelude::Vec2::new(base + i as f32 * cell, base + j as f32 * cell)
+}
+
+/// coordinates of a point in units of 2^-shift, exact as long as the point lies on that grid
+fn to_units(point: prelude::Vec2, shift: i32) -> (i128, i128) {
+    let unit = 2f64.powi(shift);
+    ((point.x as f64 * unit) as i128, (point.y as f64 * unit) as i128)
+}
+
+/// exact orientation of grid points, with the same sign convention as prelude::orientation()
+fn int_orientation(a: (i128, i128), b: (i128, i128), c: (i128, i128)) -> prelude::Orientation {
+    let det = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+
+    match det.signum() {
+        -1 => prelude::Orientation::CounterClockWise,
+        1 => prelude::Orientation::ClockWise,
+        _ => prelude::Orientation::Collinear,
+    }
+}
+
+fn reversed(orientation: prelude::Orientation) -> prelude::Orientation {
+    match orientation {
+        prelude::Orientation::CounterClockWise => prelude::Orientation::ClockWise,
+        prelude::Orientation::ClockWise => prelude::Orientation::CounterClockWise,
+        prelude::Orientation::Collinear => prelude::Orientation::Collinear,
+    }
+}
+
+/// nearly collinear points a few ulps apart, where a plain f32 cross product often gets the sign wrong
+fn matches_exact_near_degenerate(rng: &mut Rng) -> Result<(), String> {
+    let mut naive_wrong = 0;
+
+    for _ in 0..CASES {
+        let base: f32 = [0.5, 12.0, 1000.0][rng.int(0, 2) as usize];
+        let shift = 23 - base.log2().floor() as i32; // one ulp of base
+        let a = grid_point(rng, base, shift, 8);
+        let b = grid_point(rng, base * 2.0, shift - 1, 8);
+        let c = grid_point(rng, base * 4.0, shift - 2, 8);
+
+        let expected = int_orientation(to_units(a, shift), to_units(b, shift), to_units(c, shift));
+        let orientation = prelude::orientation(a, b, c);
+
+        if orientation != expected {
+            return Err(format!("orientation of {a}, {b}, {c} is {orientation:?} instead of {expected:?}"));
+        }
+
+        let naive = a.signed_area(b, c);
+        if (naive < 0.0 && expected != prelude::Orientation::CounterClockWise)
+            || (naive > 0.0 && expected != prelude::Orientation::ClockWise)
+            || (naive == 0.0 && expected != prelude::Orientation::Collinear)
+        {
+            naive_wrong += 1;
+        }
+    }
+
+    // otherwise the cases are not close enough to degenerate to test anything
+    if naive_wrong == 0 {
+        return Err(String::from("plain f32 cross products got every sign right"));
+    }
+
+    Ok(())
+}
+
+/// swapping two points reverses the orientation and rotating them keeps it, for any magnitude
+fn symmetric(rng: &mut Rng) -> Result<(), String> {
+    for _ in 0..CASES {
+        let max_exp = rng.int(0, 60) as i32;
+        let (a, b, c) = (
+            prelude::Vec2::new(rng.float(max_exp), rng.float(max_exp)),
+            prelude::Vec2::new(rng.float(max_exp), rng.float(max_exp)),
+            prelude::Vec2::new(rng.float(max_exp), rng.float(max_exp)),
+        );
+
+        let orientation = prelude::orientation(a, b, c);
+
+        if prelude::orientation(b, c, a) != orientation || prelude::orientation(c, a, b) != orientation {
+            return Err(format!("rotating {a}, {b}, {c} changes the orientation"));
+        }
+
+        if prelude::orientation(b, a, c) != reversed(orientation) || prelude::orientation(a, c, b) != reversed(orientation) {
+            return Err(format!("swapping {a}, {b}, {c} does not reverse the orientation"));
+        }
+    }
+
+    Ok(())
+}
+
+/// points on the same line are always collinear, also when a plain cross product would not cancel exactly
+fn collinear(rng: &mut Rng) -> Result<(), String> {
+    for _ in 0..CASES {
+        let scale = 2f32.powi(rng.int(-40, 40) as i32);
+        let a = prelude::Vec2::new(rng.int(-1000, 1000) as f32 * scale, rng.int(-1000, 1000) as f32 * scale);
+        let dir = prelude::Vec2::new(rng.int(-64, 64) as f32 * scale, rng.int(-64, 64) as f32 * scale);
+        let (t_1, t_2) = (rng.int(-1000, 1000) as f32, rng.int(-1000, 1000) as f32);
+
+        let (b, c) = (a.add(dir.scale(t_1)), a.add(dir.scale(t_2)));
+
+        if prelude::orientation(a, b, c) != prelude::Orientation::Collinear {
+            return Err(format!("{a}, {b}, {c} lie on a line but are not collinear"));
+        }
+    }
+
+    Ok(())
+}
+
+/// the intersection of two segments does not depend on the order of the segments or of their end points, and segments
+/// that only touch are never crossing
+fn segments(rng: &mut Rng) -> Result<(), String> {
+    for _ in 0..CASES {
+        // a coarse grid makes touching and overlapping segments common
+        let mut point = || grid_point(rng, 0.0, 0, 4);
+        let (a1, a2, b1, b2) = (point(), point(), point(), point());
+
+        let intersection = prelude::segments_intersection(a1, a2, b1, b2);
+
+        for other in [
+            prelude::segments_intersection(b1, b2, a1, a2),
+            prelude::segments_intersection(a2, a1, b1, b2),
+            prelude::segments_intersection(a1, a2, b2, b1),
+        ] {
+            if other != intersection {
+                return Err(format!(
+                    "segments {a1} {a2} and {b1} {b2} give {intersection:?} or {other:?} depending on the order"
+                ));
+            }
+        }
+
+        let shares_point = a1.equal(b1) || a1.equal(b2) || a2.equal(b1) || a2.equal(b2);
+
+        if shares_point && intersection != prelude::SegmentIntersection::Touching {
+            return Err(format!(
+                "segments {a1} {a2} and {b1} {b2} share an end point but are {intersection:?}"
+            ));
+        }
+
+        if prelude::check_segments_intersection(a1, a2, b1, b2) != (intersection == prelude::SegmentIntersection::Crossing) {
+            return Err(format!("check_segments_intersection() disagrees on {a1} {a2} and {b1} {b2}"));
+        }
+    }
+
+    // a t junction and two overlapping segments
+    let v = prelude::Vec2::new;
+    for (a1, a2, b1, b2) in [
+        (v(0.0, 0.0), v(2.0, 0.0), v(1.0, 0.0), v(1.0, 5.0)),
+        (v(0.0, 0.0), v(2.0, 0.0), v(1.0, 0.0), v(3.0, 0.0)),
+    ] {
+        let intersection = prelude::segments_intersection(a1, a2, b1, b2);
+
+        if intersection != prelude::SegmentIntersection::Touching {
+            return Err(format!("segments {a1} {a2} and {b1} {b2} are {intersection:?} instead of touching"));
+        }
+    }
+
+    Ok(())
+}
+
+/// the hull of points with many duplicates and collinear runs is a valid convex polygon containing all of them
+fn convex_hull(rng: &mut Rng) -> Result<(), String> {
+    for _ in 0..CASES / 20 {
+        let size = rng.int(1, 8) as f32;
+        // the hull merges points closer than EPS, so the grid stays coarser than that
+        let scale = 2f32.powi(rng.int(-10, 20) as i32);
+        let mut verts = Vec::new();
+
+        // points on the edges of a square, with duplicates, and points inside it
+        for _ in 0..rng.int(4, 40) {
+            let t = rng.int(0, 64) as f32 / 64.0 * size;
+            let vert = match rng.int(0, 4) {
+                0 => prelude::Vec2::new(t, 0.0),
+                1 => prelude::Vec2::new(size, t),
+                2 => prelude::Vec2::new(t, size),
+                3 => prelude::Vec2::new(0.0, t),
+                _ => prelude::Vec2::new(rng.int(1, 63) as f32 / 64.0 * size, rng.int(1, 63) as f32 / 64.0 * size),
+            };
+            verts.push(vert.scale(scale));
+            verts.push(vert.scale(scale));
+        }
+
+        let mut hull_input = verts.clone();
+        let Ok(hull) = prelude::convex_hull(&mut hull_input) else {
+            // every edge may have been missed, leaving fewer than 3 distinct corners
+            continue;
+        };
+
+        let hull_verts = hull.verts();
+
+        prelude::CvxPoly::new_checked(hull_verts.to_vec(), false).map_err(|err| format!("hull {hull} is not valid: {err}"))?;
+
+        for &vert in &verts {
+            for idx in 0..hull_verts.len() {
+                let (a, b) = (hull_verts[idx], hull_verts[(idx + 1) % hull_verts.len()]);
+
+                if prelude::orientation(a, b, vert) == prelude::Orientation::ClockWise {
+                    return Err(format!("{vert} is outside the hull {hull}"));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// comb on a grid with teeth of random height, many of its vertices are aligned
+fn comb(rng: &mut Rng) -> Result<prelude::CavePoly, String> {
+    let teeth = rng.int(2, 12);
+    let scale = 2f32.powi(rng.int(-10, 10) as i32);
+    let v = |x: i64, y: i64| prelude::Vec2::new(x as f32 * scale, y as f32 * scale);
+
+    // teeth pointing up from a base, y grows downwards
+    let mut verts = vec![v(0, 0), v(2 * teeth, 0)];
+    for tooth in (0..teeth).rev() {
+        let height = rng.int(2, 6);
+        verts.push(v(2 * tooth + 2, -height));
+        verts.push(v(2 * tooth + 1, -height));
+        verts.push(v(2 * tooth + 1, -1));
+        verts.push(v(2 * tooth, -1));
+    }
+
+    let mut cave_poly = prelude::CavePoly::new_unchecked(verts);
+    prelude::Validate::normalize(&mut cave_poly).map_err(|err| err.to_string())?;
+    prelude::Validate::validate(&cave_poly, false).map_err(|err| format!("comb {cave_poly} is not valid: {err}"))?;
+
+    Ok(cave_poly)
+}
+
+/// area enclosed by a ring, computed in f64
+fn area(ring: &[prelude::Vec2]) -> f64 {
+    (0..ring.len())
+        .map(|idx| ring[idx].cross(ring[(idx + 1) % ring.len()]) as f64)
+        .sum::<f64>()
+        .abs()
+        * 0.5
+}
+
+/// the triangulation of a comb covers the same area as the comb
+fn triangulation(rng: &mut Rng) -> Result<(), String> {
+    for _ in 0..CASES / 100 {
+        let cave_poly = comb(rng)?;
+
+        let triangles = cave_poly
+            .triangles()
+            .map_err(|err| format!("comb {cave_poly} cannot be triangulated: {err}"))?;
+        let triangles_area: f64 = triangles
+            .iter()
+            .map(|triangle| area(&[triangle.a(), triangle.b(), triangle.c()]))
+            .sum();
+        let expected = area(cave_poly.verts());
+
+        if (triangles_area - expected).abs() > 1e-4 * expected {
+            return Err(format!("triangles of {cave_poly} cover {triangles_area} instead of {expected}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// the convex pieces of a comb are valid convex polygons covering the same area as the comb
+fn convex_decomposition(rng: &mut Rng) -> Result<(), String> {
+    for _ in 0..CASES / 100 {
+        let cave_poly = comb(rng)?;
+
+        let cvx_polys = cave_poly
+            .cvx_polys()
+            .map_err(|err| format!("comb {cave_poly} cannot be decomposed: {err}"))?;
+
+        for cvx_poly in cvx_polys {
+            prelude::Validate::validate(cvx_poly, false).map_err(|err| format!("piece {cvx_poly} is not valid: {err}"))?;
+        }
+
+        let pieces_area: f64 = cvx_polys.iter().map(|cvx_poly| area(cvx_poly.verts())).sum();
+        let expected = area(cave_poly.verts());
+
+        if (pieces_area - expected).abs() > 1e-4 * expected {
+            return Err(format!("convex pieces of {cave_poly} cover {pieces_area} instead of {expected}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// checks a property starting from the same seed every time
+fn check(property: Property) {
+    if let Err(err) = property(&mut Rng(0x2545_f491_4f6c_dd1d)) {
+        panic!("{err}");
+    }
+}
+
+#[test]
+fn orientation_matches_exact_near_degenerate() {
+    check(matches_exact_near_degenerate);
+}
+
+#[test]
+fn orientation_is_symmetric() {
+    check(symmetric);
+}
+
+#[test]
+fn points_on_a_line_are_collinear() {
+    check(collinear);
+}
+
+#[test]
+fn segments_intersection_ignores_order() {
+    check(segments);
+}
+
+#[test]
+fn convex_hull_contains_every_point() {
+    check(convex_hull);
+}
+
+#[test]
+fn triangulation_covers_the_polygon() {
+    check(triangulation);
+}
+
+#[test]
+fn convex_decomposition_covers_the_polygon() {
+    check(convex_decomposition);
+}
@@ -0,0 +1,98 @@
+// regression tests for the spatial queries
+
+use lithium_engine::{physics, prelude};
+
+fn quad(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> prelude::Shape {
+    prelude::Shape::Quad(
+        prelude::Quad::new_checked(
+            prelude::Vec2::new(min_x, min_y),
+            prelude::Vec2::new(min_x, max_y),
+            prelude::Vec2::new(max_x, max_y),
+            prelude::Vec2::new(max_x, min_y),
+        )
+        .unwrap(),
+    )
+}
+
+/// 100x100 square with a 40x40 hole in the middle
+fn holed_square() -> prelude::Shape {
+    let v = prelude::Vec2::new;
+
+    prelude::Shape::CavePoly(
+        prelude::CavePoly::with_holes_checked(
+            vec![v(0.0, 0.0), v(0.0, 100.0), v(100.0, 100.0), v(100.0, 0.0)],
+            vec![vec![v(30.0, 30.0), v(70.0, 30.0), v(70.0, 70.0), v(30.0, 70.0)]],
+            false,
+        )
+        .unwrap(),
+    )
+}
+
+/// static entity with the given shape, placed at the origin
+fn spawn(world: &mut prelude::World<0>, entity_manager: &mut prelude::EntityManager, shape: prelude::Shape) -> prelude::Entity {
+    let entity = entity_manager.create();
+    let engine = world.engine_mut();
+
+    engine.transform.upsert(entity, prelude::Transform::new(prelude::Vec2::ZERO));
+    engine.surface.upsert(entity, prelude::Surface::new(0.2, 0.4, 0.3));
+    engine.body.upsert(entity, prelude::Body::new(shape));
+
+    entity
+}
+
+/// a long cast must not step over a wall thinner than the distance between its samples
+#[test]
+fn shape_cast_hits_thin_walls() {
+    let probe = quad(0.0, 0.0, 4.0, 4.0);
+
+    for wall_x in [300.0, 500.0, 505.0, 508.0, 510.0, 995.0] {
+        let mut world = prelude::World::default();
+        let mut entity_manager = prelude::EntityManager::new();
+        let wall = spawn(&mut world, &mut entity_manager, quad(wall_x, -50.0, wall_x + 2.0, 50.0));
+
+        let hit = physics::shape_cast(
+            &world,
+            &probe,
+            prelude::Vec2::new(1.0, 0.0),
+            1000.0,
+            &physics::QueryFilter::default(),
+        )
+        .unwrap()
+        .unwrap_or_else(|| panic!("the wall at {wall_x} was missed"));
+
+        assert_eq!(hit.entity, wall);
+        assert!(
+            (hit.distance - (wall_x - 4.0)).abs() < 0.01,
+            "hit at {} instead of {}",
+            hit.distance,
+            wall_x - 4.0
+        );
+    }
+}
+
+/// rays and points inside a hole are outside the shape, the edges of the hole can be hit
+#[test]
+fn queries_respect_holes() {
+    let mut world = prelude::World::default();
+    let mut entity_manager = prelude::EntityManager::new();
+    let holed = spawn(&mut world, &mut entity_manager, holed_square());
+    let filter = physics::QueryFilter::default();
+
+    let center = prelude::Vec2::new(50.0, 50.0);
+    let hit = physics::ray_cast(&world, center, prelude::Vec2::new(1.0, 0.0), 100.0, &filter).unwrap();
+
+    assert_eq!(hit.entity, holed);
+    assert!((hit.distance - 20.0).abs() < 1e-4, "hit at {} instead of 20", hit.distance);
+    assert!(
+        hit.normal.sub(prelude::Vec2::new(-1.0, 0.0)).mag() < 1e-4,
+        "normal {} does not face the ray",
+        hit.normal
+    );
+    assert!(physics::point_query(&world, center, &filter).is_empty());
+
+    let inside = prelude::Vec2::new(10.0, 50.0);
+    let hit = physics::ray_cast(&world, inside, prelude::Vec2::new(1.0, 0.0), 100.0, &filter).unwrap();
+
+    assert_eq!(hit.distance, 0.0);
+    assert_eq!(physics::point_query(&world, inside, &filter), vec![holed]);
+}